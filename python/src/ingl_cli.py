@@ -179,7 +179,18 @@ async def ingl(keypair, validator, log_level):
         if counter > 0:
             print("Invalid Input.")
         counter += 1
-        proposal_quorum = click.prompt("Enter the Proposal Quorum(%) to be set for governance proposals (>=65, <=100) ", type=int)
+        proposal_quorum = click.prompt("Enter the Proposal Quorum(%) to be set for program upgrade, validator id and threshold proposals (>=65, <=100) ", type=int)
+    other_proposal_quorum = 0
+    counter = 0
+    while other_proposal_quorum < 10 or other_proposal_quorum > 100:
+        if counter > 0:
+            print("Invalid Input.")
+        counter += 1
+        other_proposal_quorum = click.prompt("Enter the Proposal Quorum(%) to be set for the other governance proposals (>=10, <=100) ", type=int)
+    # Critical proposals need 80% approval, the others a simple majority.
+    critical_threshold = dict(quorum = proposal_quorum, approval = 80)
+    other_threshold = dict(quorum = other_proposal_quorum, approval = 51)
+    governance_thresholds = dict(program_upgrade = critical_threshold, validator_id = critical_threshold, commission = other_threshold, economics = other_threshold, profile = other_threshold, uploaders = other_threshold, uris = other_threshold, treasury = other_threshold, thresholds = critical_threshold)
     creator_royalty = 300
     counter = 0
     while creator_royalty > 200 or creator_royalty < 0:
@@ -214,6 +225,7 @@ async def ingl(keypair, validator, log_level):
     collection_uri = json_data['collection_uri']
     rarity_names = json_data['rarity_names']
     rarities = json_data['rarities']
+    rarity_reward_weights = json_data.get('rarity_reward_weights', [])
 
     client = AsyncClient(get_network())
     client_state = await client.is_connected()
@@ -229,7 +241,7 @@ async def ingl(keypair, validator, log_level):
         print("Invalid Validator Input. ")
         return
         
-    t_dets = await ingl_init(payer_keypair, validator_key, init_commission, max_primary_stake, nft_holders_share, initial_redemption_fee, is_validator_switchable, unit_backing, redemption_fee_duration, proposal_quorum, creator_royalty, governance_expiration_time, rarities, rarity_names, twitter_handle, discord_invite, validator_name, collection_uri, website, default_uri, client, log_level,)
+    t_dets = await ingl_init(payer_keypair, validator_key, init_commission, max_primary_stake, nft_holders_share, initial_redemption_fee, is_validator_switchable, unit_backing, redemption_fee_duration, governance_thresholds, creator_royalty, governance_expiration_time, rarities, rarity_names, rarity_reward_weights, twitter_handle, discord_invite, validator_name, collection_uri, website, default_uri, client, log_level,)
     print(t_dets)
     await client.close()
 
@@ -341,7 +353,7 @@ async def process_create_governance(keypair, mint_id, log_level):
         value = click.prompt("Enter the new validator name: ", type=str)
         title = click.prompt("Enter the new Proposal's Title", type=str)
         description = click.prompt("Enter the Proposal's Description", type=str)
-        t_dets = await init_governance(payer_keypair, mint_pubkey, client, title = title, description = description, governance_type=GovernanceType.enum.ConfigAccount(), config_account_type = ConfigAccountType.enum.ValidatorName(value = value), log_level = log_level)
+        t_dets = await init_governance(payer_keypair, mint_pubkey, client, title = title, description = description, actions = [GovernanceType.enum.ConfigAccount(value = ConfigAccountType.enum.ValidatorName(value = value))], log_level = log_level)
     elif numeration == 1:
         try:
             buffer_address = parse_pubkey_input(click.prompt("Enter the buffer address: ", type=str)).pubkey
@@ -352,7 +364,7 @@ async def process_create_governance(keypair, mint_id, log_level):
         title = click.prompt("Enter the new Proposal's Title", type=str)
         description = click.prompt("Enter the Proposal's Description", type=str)
 
-        t_dets = await init_governance(payer_keypair, mint_pubkey, client, title = title, description = description, governance_type = GovernanceType.enum.ProgramUpgrade(buffer_account = buffer_address, code_link = code_link), log_level = log_level)
+        t_dets = await init_governance(payer_keypair, mint_pubkey, client, title = title, description = description, actions = [GovernanceType.enum.ProgramUpgrade(buffer_account = list(bytes(buffer_address)), code_link = code_link)], log_level = log_level)
     print(t_dets)
     await client.close()
 
@@ -400,11 +412,12 @@ async def process_finalize_governance(keypair, numeration, log_level):
     print(t_dets)
     await client.close()    
 
-@click.command(name='execute_governance', help="Execute a Governance Proposal, Arguments: Numeration(int), Options: --keypair/-k, --log_level/-l")
+@click.command(name='execute_governance', help="Execute a Governance Proposal, Arguments: Numeration(int), Options: --max_actions/-m, --keypair/-k, --log_level/-l")
 @click.argument('numeration', type=int,)
+@click.option('--max_actions', '-m', default = None, type=int, help="Enter the maximum number of actions to execute in this transaction, for proposals whose actions don't fit in one. Defaults to all the remaining actions")
 @click.option('--keypair', '-k', default = get_keypair_path(), help="Enter the path to the keypair that will be used to sign this transaction. Defaults to the set config keypair")
 @click.option('--log_level', '-l', default = 2, type=int, help="Precise Log_level you want the transaction to be logged at, and above(0 -> 5). 0: All logs,  ... 5: Only Errors")
-async def process_execute_governance(keypair, numeration, log_level):
+async def process_execute_governance(keypair, numeration, max_actions, log_level):
     client = AsyncClient(get_network())
     client_state = await client.is_connected()
     print("Client is connected" if client_state else "Client is Disconnected")
@@ -414,7 +427,7 @@ async def process_execute_governance(keypair, numeration, log_level):
         print("Invalid Keypair Input. ")
         return
 
-    t_dets = await execute_governance(payer_keypair, numeration, client, log_level)
+    t_dets = await execute_governance(payer_keypair, numeration, client, max_actions, log_level)
     print(t_dets)
     await client.close()    
 
@@ -4,6 +4,45 @@ from solders.pubkey import Pubkey
 from solders.instruction import Instruction, AccountMeta
 
 
+ProposalThreshold = CStruct(
+    "quorum" / U8,
+    "approval" / U8,
+)
+
+GovernanceThresholds = CStruct(
+    "program_upgrade" / ProposalThreshold,
+    "validator_id" / ProposalThreshold,
+    "commission" / ProposalThreshold,
+    "economics" / ProposalThreshold,
+    "profile" / ProposalThreshold,
+    "uploaders" / ProposalThreshold,
+    "uris" / ProposalThreshold,
+    "treasury" / ProposalThreshold,
+    "thresholds" / ProposalThreshold,
+)
+
+LockBoostTier = CStruct(
+    "min_duration" / U32,
+    "boost_bps" / U16,
+)
+
+QuorumBasis = Enum(
+    "MaxPrimaryStake",
+    "DelegatedNfts",
+
+    enum_name = "QuorumBasis",
+)
+
+Uploader = CStruct(
+    "key" / U8[32],
+    "rarities" / Option(Vec(U8)),
+)
+
+UrisRoot = CStruct(
+    "root" / U8[32],
+    "count" / U32,
+)
+
 InitStruct = CStruct(
     "log_level" / U8,
     "init_commission" / U8,
@@ -13,11 +52,23 @@ InitStruct = CStruct(
     "is_validator_id_switchable" / Bool,
     "unit_backing" / U64,
     "redemption_fee_duration" / U32,
-    "proposal_quorum" / U8,
+    "governance_thresholds" / GovernanceThresholds,
     "creator_royalties" / U16,
     "governance_expiration_time" / U32,
+    "rarity_reroll_fee" / Option(U64),
+    "rarity_reroll_cooldown" / U32,
+    "mint_fee" / U64,
+    "referral_fee_bps" / U16,
+    "programmable_rule_set" / Option(U8[32]),
+    "uses_token_2022" / Bool,
+    "lock_boost_tiers" / Vec(LockBoostTier),
+    "quorum_basis" / QuorumBasis,
+    "proposal_deposit" / U64,
+    "treasury_share" / U8,
+    "guardian" / Option(U8[32]),
     "rarities" / Vec(U16),
     "rarity_names" / Vec(String),
+    "rarity_reward_weights" / Vec(U16),
     "twitter_handle" / String,
     "discord_invite" / String,
     "validator_name" / String,
@@ -26,6 +77,69 @@ InitStruct = CStruct(
     "default_uri" / String,
 )
 
+ConfigAccountType = Enum(
+    "MaxPrimaryStake" / CStruct("value" / U64),
+    "NftHolderShare" / CStruct("value" / U8),
+    "InitialRedemptionFee" / CStruct("value" / U8),
+    "RedemptionFeeDuration" / CStruct("value" / U32),
+    "ValidatorName" / CStruct("value" / String),
+    "TwitterHandle" / CStruct("value" / String),
+    "DiscordInvite" / CStruct("value" / String),
+    "GovernanceThresholds" / CStruct("value" / GovernanceThresholds),
+    "Website" / CStruct("value" / String),
+    "DefaultUri" / CStruct("value" / String),
+    "CreatorRoyalties" / CStruct("value" / U16),
+    "GovernanceExpirationTime" / CStruct("value" / U32),
+    "IsValidatorIdSwitchable" / CStruct("value" / Bool),
+    "RarityRerollFee" / CStruct("value" / Option(U64)),
+    "RarityRerollCooldown" / CStruct("value" / U32),
+    "MintFee" / CStruct("value" / U64),
+    "ReferralFeeBps" / CStruct("value" / U16),
+    "ProgrammableRuleSet" / CStruct("value" / Option(U8[32])),
+    "LockBoostTiers" / CStruct("value" / Vec(LockBoostTier)),
+    "QuorumBasis" / CStruct("value" / QuorumBasis),
+    "ProposalDeposit" / CStruct("value" / U64),
+    "TreasuryShare" / CStruct("value" / U8),
+    "Guardian" / CStruct("value" / Option(U8[32])),
+    "IsPaused" / CStruct("value" / Bool),
+
+    enum_name = "ConfigAccountType",
+)
+
+VoteAccountGovernance = Enum(
+    "ValidatorId" / CStruct("value" / U8[32]),
+    "Commission" / CStruct("value" / U8),
+    enum_name = "VoteAccountGovernance",
+)
+
+UploaderGovernance = Enum(
+    "AddUploader" / CStruct("value" / Uploader),
+    "RemoveUploader" / CStruct("value" / U8[32]),
+    enum_name = "UploaderGovernance",
+)
+
+UrisGovernance = Enum(
+    "ReplaceUris" / CStruct("rarity" / U8, "uris" / Vec(String)),
+    "SetBaseUri" / CStruct("value" / String),
+    "CommitUrisRoot" / CStruct("rarity" / U8, "uris_root" / Option(UrisRoot)),
+    "ReplaceUrisPage" / CStruct("rarity" / U8, "page" / U16, "uris" / Vec(String)),
+    "SetRarities" / CStruct("rarities" / Vec(U16), "rarity_names" / Vec(String)),
+    "SetRewardWeights" / CStruct("value" / Vec(U16)),
+    enum_name = "UrisGovernance",
+)
+
+# A proposal holds a list of these actions, executed in order.
+GovernanceType = Enum(
+    "ConfigAccount" / CStruct("value" / ConfigAccountType),
+    "ProgramUpgrade" / CStruct("buffer_account" / U8[32], "code_link" / String),
+    "VoteAccountGovernance" / CStruct("value" / VoteAccountGovernance),
+    "UploaderGovernance" / CStruct("value" / UploaderGovernance),
+    "UrisGovernance" / CStruct("value" / UrisGovernance),
+    "TreasurySpend" / CStruct("recipient" / U8[32], "lamports" / U64, "memo" / String),
+
+    enum_name = "GovernanceType",
+)
+
 InstructionEnum = Enum(
     "MintNft" / CStruct("switchboard_state_bump"/U8, "permission_bump"/U8, "log_level"/U8),
     "ImprintRarity" / CStruct("log_level" / U8),
@@ -40,41 +154,15 @@ InstructionEnum = Enum(
     "UnDelegateNFT" / CStruct("log_level"/U8),
     "DelegateNFT" / CStruct("log_level"/U8),
     "CreateVoteAccount" / CStruct("log_level"/U8),
-    "InitGovernance",
+    "InitGovernance" / CStruct("actions" / Vec(GovernanceType), "title" / String, "description" / String, "log_level" / U8),
     "VoteGovernance" / CStruct("numeration" / U32, "vote"/Bool, "cnt"/U8, "log_level"/U8),
     "FinalizeGovernance" / CStruct("numeration"/U32, "log_level"/U8),
-    "ExecuteGovernance" / CStruct("numeration"/U32, "log_level"/U8),
+    "ExecuteGovernance" / CStruct("numeration"/U32, "max_actions"/Option(U8), "log_level"/U8),
     "InjectTestingData" / CStruct("num_mints"/U8, "log_level"/U8),
     
     enum_name = "InstructionEnum",
 )
 
-GovernanceType = Enum(
-    "ConfigAccount",
-    "ProgramUpgrade" / CStruct("buffer_account" / U8[32], "code_link" / String),
-    "VoteAccountGovernance",
-
-    enum_name = "GovernanceType",
-)
-
-ConfigAccountType = Enum(
-    "MaxPrimaryStake" / CStruct("value" / U64),
-    "NftHolderShare" / CStruct("value" / U8),
-    "InitialRedemptionFee" / CStruct("value" / U8),
-    "RedemptionFeeDuration" / CStruct("value" / U32),
-    "ValidatorName" / CStruct("value" / String),
-    "TwitterHandle" / CStruct("value" / String),
-    "DiscordInvite" / CStruct("value" / String),
-
-    enum_name = "ConfigAccountType",
-)
-
-VoteAccountGovernance = Enum(
-    "ValidatorId" / CStruct("value" / U8[32]),
-    "Commission" / CStruct("value" / U8),
-    enum_name = "VoteAccountGovernance",
-)
-
 RegistryEnum = Enum(
     "InitConfig",
     "AddProgram",
@@ -85,19 +173,8 @@ RegistryEnum = Enum(
  enum_name="RegistryEnum",
 )
 
-def build_governance_type(governance_type: GovernanceType.enum, config_account_type:Optional[ConfigAccountType.enum] = None, vote_account_governance: Optional[VoteAccountGovernance.enum] = None):
-    if governance_type == GovernanceType.enum.ConfigAccount():
-        return GovernanceType.build(governance_type) + ConfigAccountType.build(config_account_type)
-    elif governance_type == GovernanceType.enum.VoteAccountGovernance():
-        return GovernanceType.build(governance_type) + VoteAccountGovernance.build(vote_account_governance)
-    else:
-        return GovernanceType.build(governance_type)
-
-def build_instruction(instruction: InstructionEnum.enum, title: Optional[str] = None, description: Optional[str] = None, governance_type: Optional[GovernanceType.enum] = None, config_account_type:Optional[ConfigAccountType.enum] = None, vote_account_governance: Optional[VoteAccountGovernance.enum] = None, log_level: int = 0):
-    if instruction == InstructionEnum.enum.InitGovernance():
-        return InstructionEnum.build(instruction) +  build_governance_type(governance_type, config_account_type=config_account_type, vote_account_governance=vote_account_governance) + String.build(title) + String.build(description) + (log_level).to_bytes(1, "big")
-    else:
-        return InstructionEnum.build(instruction)
+def build_instruction(instruction: InstructionEnum.enum):
+    return InstructionEnum.build(instruction)
 
 
 class ComputeBudgetInstruction:
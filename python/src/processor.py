@@ -11,7 +11,7 @@ from solana.rpc.async_api import AsyncClient
 from solana.rpc.api import Client
 from rich import print
 
-async def ingl_init(payer_keypair: KeypairInput, validator_pubkey: PubkeyInput, init_commission: int, max_primary_stake: int, nft_holders_share: int, initial_redemption_fee: int, is_validator_id_switchable: bool, unit_backing: int, redemption_fee_duration: int, proposal_quorum: int, creator_royalties: int, governance_expiration_time: int, rarities: List[int], rarity_names: List[str], twitter_handle: str, discord_invite: str, validator_name: str, collection_uri: str, website: str, default_uri: str, client: AsyncClient, log_level: int = 0) -> str:
+async def ingl_init(payer_keypair: KeypairInput, validator_pubkey: PubkeyInput, init_commission: int, max_primary_stake: int, nft_holders_share: int, initial_redemption_fee: int, is_validator_id_switchable: bool, unit_backing: int, redemption_fee_duration: int, governance_thresholds: GovernanceThresholds, creator_royalties: int, governance_expiration_time: int, rarities: List[int], rarity_names: List[str], rarity_reward_weights: List[int], twitter_handle: str, discord_invite: str, validator_name: str, collection_uri: str, website: str, default_uri: str, client: AsyncClient, log_level: int = 0, rarity_reroll_fee: Optional[int] = None, rarity_reroll_cooldown: int = 0, mint_fee: int = 0, referral_fee_bps: int = 0, programmable_rule_set: Optional[Pubkey] = None, uses_token_2022: bool = False, lock_boost_tiers: List[LockBoostTier] = [], quorum_basis: QuorumBasis.enum = QuorumBasis.enum.MaxPrimaryStake(), proposal_deposit: int = 2_000_000_000, treasury_share: int = 0, guardian: Optional[Pubkey] = None) -> str:
     mint_pubkey, _mint_pubkey_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_NFT_COLLECTION_KEY, 'UTF-8')], get_program_id())
     mint_authority_pubkey, _mint_authority_pubkey_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_MINT_AUTHORITY_KEY, 'UTF-8')], get_program_id())
     collection_holder_pubkey, _collection_holder_pubkey_bump = Pubkey.find_program_address([bytes(ingl_constants.COLLECTION_HOLDER_KEY, 'UTF-8')], get_program_id())
@@ -79,7 +79,7 @@ async def ingl_init(payer_keypair: KeypairInput, validator_pubkey: PubkeyInput,
         registry_program_meta,
     ]
     # print(accounts)
-    data = build_instruction(InstructionEnum.enum.Init(init_commission = init_commission, max_primary_stake = max_primary_stake, nft_holders_share = nft_holders_share, initial_redemption_fee = initial_redemption_fee, is_validator_id_switchable = is_validator_id_switchable, unit_backing = unit_backing, redemption_fee_duration = redemption_fee_duration, proposal_quorum = proposal_quorum, creator_royalties = creator_royalties, governance_expiration_time = governance_expiration_time, rarities = rarities, rarity_names = rarity_names, twitter_handle = twitter_handle, discord_invite = discord_invite, validator_name = validator_name, collection_uri = collection_uri, website = website, default_uri = default_uri, log_level = log_level))
+    data = build_instruction(InstructionEnum.enum.Init(init_commission = init_commission, max_primary_stake = max_primary_stake, nft_holders_share = nft_holders_share, initial_redemption_fee = initial_redemption_fee, is_validator_id_switchable = is_validator_id_switchable, unit_backing = unit_backing, redemption_fee_duration = redemption_fee_duration, governance_thresholds = governance_thresholds, creator_royalties = creator_royalties, governance_expiration_time = governance_expiration_time, rarity_reroll_fee = rarity_reroll_fee, rarity_reroll_cooldown = rarity_reroll_cooldown, mint_fee = mint_fee, referral_fee_bps = referral_fee_bps, programmable_rule_set = None if programmable_rule_set is None else list(bytes(programmable_rule_set)), uses_token_2022 = uses_token_2022, lock_boost_tiers = lock_boost_tiers, quorum_basis = quorum_basis, proposal_deposit = proposal_deposit, treasury_share = treasury_share, guardian = None if guardian is None else list(bytes(guardian)), rarities = rarities, rarity_names = rarity_names, rarity_reward_weights = rarity_reward_weights, twitter_handle = twitter_handle, discord_invite = discord_invite, validator_name = validator_name, collection_uri = collection_uri, website = website, default_uri = default_uri, log_level = log_level))
     transaction = Transaction()
     # print(data)
     transaction.add(ComputeBudgetInstruction().set_compute_unit_limit(300_000, payer_keypair.pubkey))
@@ -546,10 +546,11 @@ async def inject_testing_data(payer_keypair: KeypairInput, mints: List[Pubkey],
     except Exception as e:
         return(f"Error: {e}")
 
-async def init_governance(payer_keypair: KeypairInput, mint: PubkeyInput, client: AsyncClient, title: str, description: str, governance_type: Optional[GovernanceType.enum] = None, config_account_type:Optional[ConfigAccountType.enum] = None, vote_account_governance: Optional[VoteAccountGovernance.enum] = None,  log_level: int = 0) -> str:
-    vote_account_pubkey, _vote_account_bump = Pubkey.find_program_address([bytes(ingl_constants.VOTE_ACCOUNT_KEY, 'UTF-8')], get_program_id())
+async def init_governance(payer_keypair: KeypairInput, mint: PubkeyInput, client: AsyncClient, title: str, description: str, actions: List[GovernanceType.enum], log_level: int = 0) -> str:
     general_account_pubkey, _general_account_bump = Pubkey.find_program_address([bytes(ingl_constants.GENERAL_ACCOUNT_SEED, 'UTF-8')], get_program_id())
     config_account_pubkey, _config_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_CONFIG_SEED, 'UTF-8')], get_program_id())
+    config_data = await client.get_account_info(config_account_pubkey)
+    vote_account_pubkey = Pubkey(bytes(ValidatorConfig.parse(config_data.value.data).vote_account))
     nft_account_data_pubkey, _nft_account_data_bump = Pubkey.find_program_address([bytes(ingl_constants.NFT_ACCOUNT_CONST, 'UTF-8'), bytes(mint.pubkey)], get_program_id())
     associated_account_key = assoc_instructions.get_associated_token_address(payer_keypair.pubkey, mint.pubkey)
 
@@ -585,14 +586,14 @@ async def init_governance(payer_keypair: KeypairInput, mint: PubkeyInput, client
         config_account_meta,
     ]
 
-    instruction_data = build_instruction(InstructionEnum.enum.InitGovernance(),  title = title, description = description, governance_type=governance_type, config_account_type=config_account_type, vote_account_governance=vote_account_governance, log_level=log_level)
-    print("instruction_data: ", instruction_data)
-    if instruction_data[1] == 1:
-        buffer_account_key = Pubkey(instruction_data[2:34])
-        buffer_account_meta = AccountMeta(pubkey = buffer_account_key, is_signer = False, is_writable = True)
-        print("buffer_account_key: ", buffer_account_key)
-        accounts.append(buffer_account_meta)
-        
+    instruction_data = build_instruction(InstructionEnum.enum.InitGovernance(actions = actions, title = title, description = description, log_level = log_level))
+    # Each program upgrade action takes its buffer account, in the order of the actions.
+    for action in actions:
+        if isinstance(action, GovernanceType.enum.ProgramUpgrade):
+            buffer_account_key = Pubkey(bytes(action.buffer_account))
+            print("buffer_account_key: ", buffer_account_key)
+            accounts.append(AccountMeta(pubkey = buffer_account_key, is_signer = False, is_writable = True))
+
     accounts += [
         system_program_meta,
         ]
@@ -648,18 +649,26 @@ async def vote_governance(payer_keypair: KeypairInput, vote: Bool, proposal_nume
         return(f"Error: {e}")
 
 async def finalize_governance(payer_keypair: KeypairInput, proposal_numeration: int, client: AsyncClient, log_level: int = 0) -> str:
-    proposal_account_key, _proposal_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_PROPOSAL_KEY, 'UTF-8'), (proposal_numeration)], get_program_id())
-    config_account_key, _config_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_CONFIG_KEY, 'UTF-8')], get_program_id())
-    general_account_key, _general_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_GENERAL_KEY, 'UTF-8')], get_program_id())
+    proposal_account_key, _proposal_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_PROPOSAL_KEY, 'UTF-8'), (proposal_numeration).to_bytes(4, "big")], get_program_id())
+    config_account_key, _config_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_CONFIG_SEED, 'UTF-8')], get_program_id())
+    general_account_key, _general_account_bump = Pubkey.find_program_address([bytes(ingl_constants.GENERAL_ACCOUNT_SEED, 'UTF-8')], get_program_id())
+    config_data = await client.get_account_info(config_account_key)
+    vote_account_key = Pubkey(bytes(ValidatorConfig.parse(config_data.value.data).vote_account))
 
     print(f"Proposal_Account: {proposal_account_key}");
 
+    proposal_data = await client.get_account_info(proposal_account_key)
+    proposal_data = GovernanceData.parse(proposal_data.value.data)
+
     payer_account_meta = AccountMeta(pubkey = payer_keypair.pubkey, is_signer = True, is_writable = True) 
     sysvar_rent_account_meta = AccountMeta(pubkey = RENT, is_signer = False, is_writable = False)
     sysvar_clock_account_meta = AccountMeta(pubkey = CLOCK, is_signer = False, is_writable = False)
     proposal_account_meta = AccountMeta(pubkey = proposal_account_key, is_signer = False, is_writable = True)
     config_account_meta = AccountMeta(pubkey = config_account_key, is_signer = False, is_writable = True)
     general_account_meta = AccountMeta(pubkey = general_account_key, is_signer = False, is_writable = True)
+    # The deposit goes back to the proposer, or to the vote account if the proposal was slashed.
+    proposer_account_meta = AccountMeta(pubkey = Pubkey(bytes(proposal_data.proposer)), is_signer = False, is_writable = True)
+    vote_account_meta = AccountMeta(pubkey = vote_account_key, is_signer = False, is_writable = True)
 
     accounts = [
         payer_account_meta,
@@ -668,6 +677,8 @@ async def finalize_governance(payer_keypair: KeypairInput, proposal_numeration:
         proposal_account_meta,
         config_account_meta,
         general_account_meta,
+        proposer_account_meta,
+        vote_account_meta,
     ]
 
     # print(accounts)
@@ -681,15 +692,15 @@ async def finalize_governance(payer_keypair: KeypairInput, proposal_numeration:
     except Exception as e:
         return(f"Error: {e}")
 
-async def execute_governance(payer_keypair: KeypairInput, proposal_numeration: int, client: AsyncClient, log_level: int = 0) -> str:
-    proposal_account_key, _proposal_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_PROPOSAL_KEY, 'UTF-8'), (proposal_numeration)], get_program_id())
-    config_account_key, _config_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_CONFIG_KEY, 'UTF-8')], get_program_id())
-    general_account_key, _general_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_GENERAL_KEY, 'UTF-8')], get_program_id())
+async def execute_governance(payer_keypair: KeypairInput, proposal_numeration: int, client: AsyncClient, max_actions: Optional[int] = None, log_level: int = 0) -> str:
+    proposal_account_key, _proposal_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_PROPOSAL_KEY, 'UTF-8'), (proposal_numeration).to_bytes(4, "big")], get_program_id())
+    config_account_key, _config_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_CONFIG_SEED, 'UTF-8')], get_program_id())
+    general_account_key, _general_account_bump = Pubkey.find_program_address([bytes(ingl_constants.GENERAL_ACCOUNT_SEED, 'UTF-8')], get_program_id())
     
     print(f"Proposal_Account: {proposal_account_key}");
 
     proposal_data = await client.get_account_info(proposal_account_key)
-    proposal_data = proposal_data.value.data
+    proposal_data = GovernanceData.parse(proposal_data.value.data)
 
     payer_account_meta = AccountMeta(pubkey = payer_keypair.pubkey, is_signer = True, is_writable = True) # payer is the validator ID.
     sysvar_rent_account_meta = AccountMeta(pubkey = RENT, is_signer = False, is_writable = False)
@@ -697,6 +708,7 @@ async def execute_governance(payer_keypair: KeypairInput, proposal_numeration: i
     proposal_account_meta = AccountMeta(pubkey = proposal_account_key, is_signer = False, is_writable = True)
     config_account_meta = AccountMeta(pubkey = config_account_key, is_signer = False, is_writable = True)
     general_account_meta = AccountMeta(pubkey = general_account_key, is_signer = False, is_writable = True)
+    system_program_meta = AccountMeta(pubkey = system_program.ID, is_signer = False, is_writable = False)
 
     accounts = [
         payer_account_meta,
@@ -706,66 +718,66 @@ async def execute_governance(payer_keypair: KeypairInput, proposal_numeration: i
         general_account_meta,
     ]
 
-    loc = 4+4+1
-    if proposal_data[loc] == 1:
-        loc += 1 + 4
-    else:
-        loc += 1
-    
-    if proposal_data[loc] == 1:
-        loc += 1 + 1
-    else:
-        loc += 1
-    
-    loc += 1
-
-    num_votes = int.from_bytes(proposal_data[loc:loc+4], "big")
-    loc += 4
-    loc += num_votes * 5
-    governance_type = proposal_data[loc]
-    loc += 1
-    if governance_type == 0:
-        pass
-    elif governance_type == 1:
-        buffer_account_key = Pubkey(proposal_data[loc:loc+32])
-        programdata_key, _programdata_bump = Pubkey.find_program_address([bytes(get_program_id())], ingl_constants.BPF_LOADER_UPGRADEABLE)
-        upgrade_authority_key, _upgrade_authority_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_PROGRAM_AUTHORITY_KEY, 'UTF-8'), bytes(get_program_id())], get_program_id())
-
-        upgraded_program_meta = AccountMeta(pubkey = get_program_id(), is_signer = False, is_writable = False)
-        buffer_account_meta = AccountMeta(pubkey = buffer_account_key, is_signer = False, is_writable = True)
-        programdata_account_meta = AccountMeta(pubkey = programdata_key, is_signer = False, is_writable = True)
-        upgrade_authority_account_meta = AccountMeta(pubkey = upgrade_authority_key, is_signer = False, is_writable = True)
-
-        accounts += [
-            upgraded_program_meta,
-            buffer_account_meta,
-            payer_account_meta,
-            programdata_account_meta,
-            upgrade_authority_account_meta,
-            sysvar_rent_account_meta,
-            sysvar_clock_account_meta,
-        ]
-    elif governance_type == 2:
-        vote_account_governance_type = proposal_data[loc]
-        loc += 1
-        vote_account_key, _vote_account_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_VOTE_KEY, 'UTF-8'),], get_program_id())
-        authorized_withdrawer_key, _authorized_withdrawer_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_VOTE_AUTHORIZED_WITHDRAWER_KEY, 'UTF-8'),], get_program_id())
-
-        vote_account_meta = AccountMeta(pubkey = vote_account_key, is_signer = False, is_writable = True)
-        authorized_withdrawer_account_meta = AccountMeta(pubkey = authorized_withdrawer_key, is_signer = False, is_writable = False)
-        
-        accounts += [
-            vote_account_meta,
-            authorized_withdrawer_account_meta,
-        ]
-
-        if vote_account_governance_type == 0:
-            accounts.append(sysvar_clock_account_meta)
-            accounts.append(payer_account_meta)
-
+    # Only the actions this execution runs take their accounts, in the order of the actions.
+    first_action = proposal_data.executed_actions
+    last_action = len(proposal_data.actions) if max_actions is None else min(len(proposal_data.actions), first_action + max_actions)
+    for action in proposal_data.actions[first_action:last_action]:
+        if isinstance(action, GovernanceType.enum.ProgramUpgrade):
+            buffer_account_key = Pubkey(bytes(action.buffer_account))
+            programdata_key, _programdata_bump = Pubkey.find_program_address([bytes(get_program_id())], ingl_constants.BPF_LOADER_UPGRADEABLE)
+            upgrade_authority_key, _upgrade_authority_bump = Pubkey.find_program_address([bytes(ingl_constants.INGL_PROGRAM_AUTHORITY_KEY, 'UTF-8')], get_program_id())
+
+            upgraded_program_meta = AccountMeta(pubkey = get_program_id(), is_signer = False, is_writable = True)
+            buffer_account_meta = AccountMeta(pubkey = buffer_account_key, is_signer = False, is_writable = True)
+            programdata_account_meta = AccountMeta(pubkey = programdata_key, is_signer = False, is_writable = True)
+            upgrade_authority_account_meta = AccountMeta(pubkey = upgrade_authority_key, is_signer = False, is_writable = False)
+
+            accounts += [
+                upgraded_program_meta,
+                buffer_account_meta,
+                payer_account_meta,
+                programdata_account_meta,
+                upgrade_authority_account_meta,
+                sysvar_rent_account_meta,
+                sysvar_clock_account_meta,
+            ]
+        elif isinstance(action, GovernanceType.enum.VoteAccountGovernance):
+            config_data = await client.get_account_info(config_account_key)
+            vote_account_key = Pubkey(bytes(ValidatorConfig.parse(config_data.value.data).vote_account))
+            authorized_withdrawer_key, _authorized_withdrawer_bump = Pubkey.find_program_address([bytes(ingl_constants.AUTHORIZED_WITHDRAWER_KEY, 'UTF-8')], get_program_id())
+
+            vote_account_meta = AccountMeta(pubkey = vote_account_key, is_signer = False, is_writable = True)
+            authorized_withdrawer_account_meta = AccountMeta(pubkey = authorized_withdrawer_key, is_signer = False, is_writable = False)
+
+            accounts += [
+                authorized_withdrawer_account_meta,
+                vote_account_meta,
+                config_account_meta,
+            ]
+
+            if isinstance(action.value, VoteAccountGovernance.enum.ValidatorId):
+                # The new validator id signs its own appointment.
+                accounts.append(sysvar_clock_account_meta)
+                accounts.append(AccountMeta(pubkey = Pubkey(bytes(action.value.value)), is_signer = True, is_writable = False))
+        elif isinstance(action, GovernanceType.enum.UploaderGovernance):
+            uploaders_account_key, _uploaders_account_bump = Pubkey.find_program_address([bytes(ingl_constants.UPLOADERS_SEED, 'UTF-8')], get_program_id())
+            accounts.append(AccountMeta(pubkey = uploaders_account_key, is_signer = False, is_writable = True))
+        elif isinstance(action, GovernanceType.enum.UrisGovernance):
+            uris_account_key, _uris_account_bump = Pubkey.find_program_address([bytes(ingl_constants.URIS_ACCOUNT_SEED, 'UTF-8')], get_program_id())
+            accounts.append(AccountMeta(pubkey = uris_account_key, is_signer = False, is_writable = True))
+            if isinstance(action.value, UrisGovernance.enum.ReplaceUrisPage):
+                uris_page_key, _uris_page_bump = Pubkey.find_program_address([bytes(ingl_constants.URIS_PAGE_KEY, 'UTF-8'), bytes([action.value.rarity]), (action.value.page).to_bytes(2, "big")], get_program_id())
+                accounts.append(AccountMeta(pubkey = uris_page_key, is_signer = False, is_writable = True))
+        elif isinstance(action, GovernanceType.enum.TreasurySpend):
+            treasury_key, _treasury_bump = Pubkey.find_program_address([bytes(ingl_constants.TREASURY_KEY, 'UTF-8')], get_program_id())
+            accounts += [
+                AccountMeta(pubkey = treasury_key, is_signer = False, is_writable = True),
+                AccountMeta(pubkey = Pubkey(bytes(action.recipient)), is_signer = False, is_writable = True),
+            ]
+    accounts.append(system_program_meta)
 
     # print(accounts)
-    instruction_data = build_instruction(InstructionEnum.enum.ExecuteGovernance(log_level = log_level, numeration = proposal_numeration))
+    instruction_data = build_instruction(InstructionEnum.enum.ExecuteGovernance(log_level = log_level, numeration = proposal_numeration, max_actions = max_actions))
     transaction = Transaction()
     transaction.add(Instruction(accounts = accounts, program_id = get_program_id(), data = instruction_data))
     try:
@@ -3,6 +3,7 @@ from typing import Optional
 import base58
 from borsh_construct import *
 from .ledger import *
+from .instruction import GovernanceThresholds, GovernanceType, LockBoostTier, ProposalThreshold, QuorumBasis
 from solders.keypair import Keypair
 from solders.pubkey import Pubkey
 from solana.rpc import types
@@ -27,6 +28,9 @@ class Constants:
     NFT_ACCOUNT_CONST = "nft_account";
     INGL_PROGRAM_AUTHORITY_KEY = "ingl_program_authority";
     INGL_PROPOSAL_KEY = "ingl_proposal";
+    URIS_PAGE_KEY = "uris_page";
+    UPLOADERS_SEED = "uploaders";
+    TREASURY_KEY = "treasury";
     VALIDATOR_ID_SEED = "validator_ID___________________";
     T_STAKE_ACCOUNT_KEY = "t_stake_account_key";
     T_WITHDRAW_KEY = "t_withdraw_key";
@@ -58,13 +62,26 @@ ValidatorConfig = CStruct(
     "max_primary_stake" / U64,
     "nft_holders_share" / U8,
     "initial_redemption_fee" / U8,
-    "unit_stake" / U64,
+    "unit_backing" / U64,
     "redemption_fee_duration" / U32,
-    "proposal_quorum" / U8,
+    "governance_thresholds" / GovernanceThresholds,
     "creator_royalties" / U16,
     "commission" / U8,
     "validator_id" / U8[32],
+    "vote_account" / U8[32],
     "governance_expiration_time" / U32,
+    "rarity_reroll_fee" / Option(U64),
+    "rarity_reroll_cooldown" / U32,
+    "mint_fee" / U64,
+    "referral_fee_bps" / U16,
+    "programmable_rule_set" / Option(U8[32]),
+    "uses_token_2022" / Bool,
+    "lock_boost_tiers" / Vec(LockBoostTier),
+    "quorum_basis" / QuorumBasis,
+    "proposal_deposit" / U64,
+    "treasury_share" / U8,
+    "guardian" / Option(U8[32]),
+    "is_paused" / Bool,
     "default_uri" / String,
     "validator_name" / String,
     "twitter_handle" / String,
@@ -92,6 +109,27 @@ GeneralData = CStruct(
     "rebalancing_data" / RebalancingData,
     "vote_rewards" / Vec(VoteReward),
 )
+GovernanceData = CStruct(
+    "validation_phrase" / U32,
+    "expiration_time" / U32,
+    "is_still_ongoing" / Bool,
+    "date_finalized" / Option(U32),
+    "did_proposal_pass" / Option(Bool),
+    "is_proposal_executed" / Bool,
+    "title" / String,
+    "description" / String,
+    "votes" / HashMap(U32, Bool),
+    "actions" / Vec(GovernanceType),
+    "executed_actions" / U8,
+    "expected_votes" / U32,
+    "threshold" / ProposalThreshold,
+    "proposer" / U8[32],
+    "deposit" / U64,
+    "is_cancelled" / Bool,
+    "is_vetoed" / Bool,
+    "is_execution_failed" / Bool,
+)
+
 RegistryConfig  = CStruct(
     "validation_phase" / U32,
     "validator_numeration" / U32,
@@ -67,4 +67,34 @@ pub enum InglError {
 
     #[err("Uris Account is too big")]
     UrisAccountTooBig, //19
+
+    #[err("The revealed value does not match the committed hash")]
+    InvalidReveal, //20
+
+    #[err("The provided allowlist proof is invalid for the current mint phase")]
+    InvalidAllowlistProof, //21
+
+    #[err("The wallet has reached its mint cap for the current mint phase")]
+    MintCapReached, //22
+
+    #[err("The bid must exceed the listing's highest bid and be below its price")]
+    InvalidBid, //23
+
+    #[err("The NFT is locked")]
+    NftLocked, //24
+
+    #[err("The provided uri proof is invalid for the committed uris")]
+    InvalidUriProof, //25
+
+    #[err("The uris are frozen")]
+    UrisFrozen, //26
+
+    #[err("The program is paused by its guardian")]
+    ProgramPaused, //27
+
+    #[err("Programmable NFTs can't be escrowed by the marketplace")]
+    ProgrammableNftNotListable, //28
+
+    #[err("Token-2022 gems are traded directly, their transfer hook settles their rewards")]
+    Token2022GemNotListable, //29
 }
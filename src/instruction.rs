@@ -6,7 +6,10 @@ use solana_program::{
     system_program,
 };
 
-use crate::state::{constants, GovernanceType};
+use crate::state::{
+    constants, GovernanceThresholds, GovernanceType, LockBoostTier, MintPhase, QuorumBasis,
+    Uploader, UriProof, UrisRoot,
+};
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct InitArgs {
@@ -18,11 +21,23 @@ pub struct InitArgs {
     pub is_validator_id_switchable: bool,
     pub unit_backing: u64,
     pub redemption_fee_duration: u32,
-    pub proposal_quorum: u8,
+    pub governance_thresholds: GovernanceThresholds,
     pub creator_royalties: u16,
     pub governance_expiration_time: u32,
+    pub rarity_reroll_fee: Option<u64>,
+    pub rarity_reroll_cooldown: u32,
+    pub mint_fee: u64,
+    pub referral_fee_bps: u16,
+    pub programmable_rule_set: Option<Pubkey>,
+    pub uses_token_2022: bool,
+    pub lock_boost_tiers: Vec<LockBoostTier>,
+    pub quorum_basis: QuorumBasis,
+    pub proposal_deposit: u64,
+    pub treasury_share: u8,
+    pub guardian: Option<Pubkey>,
     pub rarities: Vec<u16>,
     pub rarity_names: Vec<String>,
+    pub rarity_reward_weights: Vec<u16>,
     pub twitter_handle: String,
     pub discord_invite: String,
     pub validator_name: String,
@@ -35,10 +50,13 @@ pub struct InitArgs {
 pub enum InstructionEnum {
     MintNft {
         // Tested
+        allowlist_proof: Vec<[u8; 32]>,
         log_level: u8,
     },
     ImprintRarity {
         //Tested
+        reveal: Option<[u8; 32]>,
+        uri_proof: Option<UriProof>,
         log_level: u8,
     },
     Init(InitArgs),
@@ -87,7 +105,7 @@ pub enum InstructionEnum {
     },
     InitGovernance {
         //Tested
-        governance_type: GovernanceType,
+        actions: Vec<GovernanceType>,
         title: String,
         description: String,
         log_level: u8,
@@ -107,6 +125,7 @@ pub enum InstructionEnum {
     ExecuteGovernance {
         //Tested
         numeration: u32,
+        max_actions: Option<u8>,
         log_level: u8,
     },
     InjectTestingData {
@@ -115,6 +134,138 @@ pub enum InstructionEnum {
         log_level: u8,
     },
     FractionalizeExisting(InitArgs),
+    CommitRaritySeed {
+        commitment: [u8; 32],
+        log_level: u8,
+    },
+    SetVrfOracle {
+        oracle_program: Option<Pubkey>,
+        oracle_authority: Pubkey,
+        log_level: u8,
+    },
+    RerollRarity {
+        uri_proof: Option<UriProof>,
+        log_level: u8,
+    },
+    SetMintPhases {
+        phases: Vec<MintPhase>,
+        log_level: u8,
+    },
+    SettleBeforeTransfer {
+        log_level: u8,
+    },
+    ClaimSettledRewards {
+        log_level: u8,
+    },
+    ListNft {
+        price: u64,
+        log_level: u8,
+    },
+    DelistNft {
+        log_level: u8,
+    },
+    BuyNft {
+        log_level: u8,
+    },
+    PlaceBid {
+        amount: u64,
+        log_level: u8,
+    },
+    AcceptBid {
+        log_level: u8,
+    },
+    QuoteListing {
+        log_level: u8,
+    },
+    SyncMetadata {
+        log_level: u8,
+    },
+    LockNft {
+        duration: u32,
+        log_level: u8,
+    },
+    UnlockNft {
+        log_level: u8,
+    },
+    UploadUrisPage {
+        rarity: u8,
+        page: u16,
+        uris: Vec<String>,
+        log_level: u8,
+    },
+    ResetUrisPage {
+        rarity: u8,
+        page: u16,
+        uris: Vec<String>,
+        log_level: u8,
+    },
+    SetBaseUri {
+        base_uri: String,
+        log_level: u8,
+    },
+    CommitUrisRoot {
+        rarity: u8,
+        uris_root: Option<UrisRoot>,
+        log_level: u8,
+    },
+    AddUploader {
+        uploader: Uploader,
+        log_level: u8,
+    },
+    RemoveUploader {
+        uploader: Pubkey,
+        log_level: u8,
+    },
+    FreezeUris {
+        log_level: u8,
+    },
+    ChangeVote {
+        numeration: u32,
+        vote: bool,
+        cnt: u8,
+        log_level: u8,
+    },
+    WithdrawVote {
+        numeration: u32,
+        cnt: u8,
+        log_level: u8,
+    },
+    DelegateVote {
+        delegate: Pubkey,
+        log_level: u8,
+    },
+    RevokeVoteDelegation {
+        log_level: u8,
+    },
+    CancelProposal {
+        numeration: u32,
+        log_level: u8,
+    },
+    CloseProposal {
+        numeration: u32,
+        log_level: u8,
+    },
+    PruneVotes {
+        numerations: Vec<u32>,
+        log_level: u8,
+    },
+    VetoProposal {
+        numeration: u32,
+        log_level: u8,
+    },
+    SetEmergencyPause {
+        is_paused: bool,
+        log_level: u8,
+    },
+    CloseMinterRecord {
+        log_level: u8,
+    },
+    CancelBid {
+        log_level: u8,
+    },
+    MigrateAccount {
+        log_level: u8,
+    },
 }
 
 impl InstructionEnum {
@@ -3,6 +3,7 @@ pub mod instruction;
 pub mod processes;
 pub mod processor;
 pub mod state;
+pub mod token_2022;
 pub mod utils;
 
 use crate::processor::process_instruction;
@@ -12,12 +13,2556 @@ entrypoint!(process_instruction);
 
 #[cfg(test)]
 pub mod tests {
+    use crate::{
+        instruction::InitArgs,
+        processes::{
+            governance_processes::{
+                cancel_proposal::cancel_proposal,
+                close_proposal::close_proposal,
+                execute_governance::{execute_governance, handle_uris_governance_change},
+                veto_proposal::veto_proposal,
+            },
+            init_processes::migrate_account::{
+                migrate_account, LegacyGeneralData, LegacyGovernanceData, LegacyNftData,
+                LegacyValidatorConfig, LegacyVoteReward,
+            },
+            marketplace_processes::cancel_bid::cancel_bid,
+            nft_processes::{
+                commit_rarity_seed::commit_rarity_seed,
+                imprint_rarity::{get_rarity_seed, get_vrf_result, RaritySeedRequest},
+                lock_nft::lock_nft,
+                mint_nft::get_programmable_asset_data,
+                redeem_nft::redeem_nft,
+                reroll_rarity::{get_reroll_seed, reroll_rarity},
+                unlock_nft::unlock_nft,
+            },
+            rewards_processes::{
+                claim_settled_rewards::claim_settled_rewards,
+                nft_withdraw::{
+                    calculate_total_reward, credit_reward_claim, settle_rewards,
+                    split_total_reward, RewardSettlement,
+                },
+            },
+        },
+        state::{
+            constants::{
+                AUTHORIZED_WITHDRAWER_KEY, CUMMULATED_RARITY, GENERAL_ACCOUNT_SEED,
+                GOVERNANCE_DATA_VAL_PHRASE, GOVERNANCE_SAFETY_LEEWAY, INGL_CONFIG_SEED,
+                INGL_MINT_AUTHORITY_KEY, INGL_NFT_COLLECTION_KEY, INGL_PROPOSAL_KEY,
+                LEGACY_GENERAL_ACCOUNT_VAL_PHRASE, LEGACY_GOVERNANCE_DATA_VAL_PHRASE,
+                LEGACY_INGL_CONFIG_VAL_PHRASE, LEGACY_NFT_DATA_VAL_PHRASE, LISTING_KEY,
+                LISTING_VAL_PHRASE, MINT_PHASES_VAL_PHRASE, MIN_CRITICAL_PROPOSAL_QUORUM,
+                MIN_PROPOSAL_DEPOSIT, NFT_ACCOUNT_CONST, NFT_DATA_VAL_PHRASE, PD_POOL_ACCOUNT_KEY,
+                PROPOSAL_RETENTION_PERIOD, RARITY_IMPRINT_WAIT_SLOTS, RARITY_SEED_OFFSET,
+                REWARD_CLAIM_KEY, REWARD_CLAIM_VAL_PHRASE, URIS_ACCOUNT_SEED, URIS_PAGE_KEY,
+                URIS_PAGE_VAL_PHRASE, VRF_STATE_KEY, VRF_STATE_VAL_PHRASE,
+            },
+            Bid, ConfigAccountType, FundsLocation, GeneralData, GovernanceData,
+            GovernanceThresholds, GovernanceType, Listing, LockBoostTier, MintPhase, MintPhases,
+            NftData, ProposalThreshold, QuorumBasis, RebalancingData, RewardClaim, UrisAccount,
+            UrisGovernance, UrisPage, UrisRoot, ValidatorConfig, VoteAccountGovernance, VoteReward,
+            VrfState,
+        },
+        utils::{get_slot_hash, verify_merkle_proof},
+    };
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use mpl_token_metadata::{
+        pda::find_metadata_account,
+        state::{Data, Key, Metadata, TokenStandard, PREFIX},
+    };
+    use solana_program::{
+        account_info::AccountInfo,
+        hash::hashv,
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        slot_hashes, system_program,
+        sysvar::{
+            self,
+            instructions::{construct_instructions_data, store_current_index, BorrowedInstruction},
+            rent::Rent,
+        },
+    };
+    use spl_associated_token_account::get_associated_token_address;
+    use spl_token::state::{Account as TokenAccount, AccountState};
+    use std::collections::{BTreeMap, BTreeSet};
+
     pub fn add(number1: u64, number2: u64) -> u64 {
         return number1 + number2;
     }
 
+    /// A proposal with the given number of votes, the first yes_votes of them in favour.
+    pub fn proposal(expected_votes: u32, votes: u32, yes_votes: u32) -> GovernanceData {
+        GovernanceData {
+            validation_phrase: GOVERNANCE_DATA_VAL_PHRASE,
+            expiration_time: 0,
+            is_still_ongoing: true,
+            date_finalized: None,
+            did_proposal_pass: None,
+            is_proposal_executed: false,
+            title: String::new(),
+            description: String::new(),
+            votes: (0..votes)
+                .map(|x| (x, x < yes_votes))
+                .collect::<BTreeMap<u32, bool>>(),
+            actions: vec![GovernanceType::VoteAccountGovernance(
+                VoteAccountGovernance::Commission(5),
+            )],
+            executed_actions: 0,
+            expected_votes,
+            threshold: ProposalThreshold {
+                quorum: 65,
+                approval: 80,
+            },
+            proposer: Pubkey::new_unique(),
+            deposit: 0,
+            is_cancelled: false,
+            is_vetoed: false,
+            is_execution_failed: false,
+        }
+    }
+
+    /// A valid config with the given mint fee and referral fee, for 2 Sol NFTs.
+    pub fn config(mint_fee: u64, referral_fee_bps: u16) -> ValidatorConfig {
+        let threshold = ProposalThreshold {
+            quorum: 65,
+            approval: 80,
+        };
+        let init_args = InitArgs {
+            log_level: 0,
+            init_commission: 5,
+            max_primary_stake: 1000 * LAMPORTS_PER_SOL,
+            nft_holders_share: 80,
+            initial_redemption_fee: 10,
+            is_validator_id_switchable: true,
+            unit_backing: 2 * LAMPORTS_PER_SOL,
+            redemption_fee_duration: 86400 * 30,
+            governance_thresholds: GovernanceThresholds {
+                program_upgrade: threshold,
+                validator_id: threshold,
+                commission: threshold,
+                economics: threshold,
+                profile: threshold,
+                uploaders: threshold,
+                uris: threshold,
+                treasury: threshold,
+                thresholds: threshold,
+            },
+            creator_royalties: 100,
+            governance_expiration_time: 86400 * 40,
+            rarity_reroll_fee: None,
+            rarity_reroll_cooldown: 0,
+            mint_fee,
+            referral_fee_bps,
+            programmable_rule_set: None,
+            uses_token_2022: false,
+            lock_boost_tiers: vec![],
+            quorum_basis: QuorumBasis::MaxPrimaryStake,
+            proposal_deposit: 2 * LAMPORTS_PER_SOL,
+            treasury_share: 0,
+            guardian: None,
+            rarities: vec![],
+            rarity_names: vec![],
+            rarity_reward_weights: vec![],
+            twitter_handle: String::new(),
+            discord_invite: String::new(),
+            validator_name: String::new(),
+            collection_uri: String::new(),
+            website: String::new(),
+            default_uri: String::new(),
+        };
+        ValidatorConfig::new(&init_args, Pubkey::new_unique(), Pubkey::new_unique()).unwrap()
+    }
+
     #[test]
     pub fn custom_test() {
         assert_eq!(add(1430, 1780), 3210);
     }
+
+    #[test]
+    pub fn expected_votes_follow_the_quorum_basis() {
+        let unit_backing = 2 * LAMPORTS_PER_SOL;
+        // 30 of the 100 nfts of the instance are delegated.
+        let max_primary_stake = 100 * unit_backing;
+        let total_delegated = 30 * unit_backing;
+        assert_eq!(
+            QuorumBasis::MaxPrimaryStake.get_expected_votes(
+                max_primary_stake,
+                total_delegated,
+                unit_backing
+            ),
+            Some(100)
+        );
+        assert_eq!(
+            QuorumBasis::DelegatedNfts.get_expected_votes(
+                max_primary_stake,
+                total_delegated,
+                unit_backing
+            ),
+            Some(30)
+        );
+        assert_eq!(
+            QuorumBasis::DelegatedNfts.get_expected_votes(max_primary_stake, total_delegated, 0),
+            None
+        );
+        // Nothing delegated yet still expects a vote, so no proposal passes without one.
+        assert_eq!(
+            QuorumBasis::DelegatedNfts.get_expected_votes(max_primary_stake, 0, unit_backing),
+            Some(1)
+        );
+        assert!(!proposal(1, 0, 0).has_reached_quorum());
+    }
+
+    #[test]
+    pub fn partially_sold_instance_reaches_quorum_of_delegated_nfts() {
+        // Every delegated nft votes on an instance that sold 30% of its capacity.
+        assert!(!proposal(100, 30, 30).has_reached_quorum());
+        assert!(proposal(30, 30, 30).has_reached_quorum());
+    }
+
+    #[test]
+    pub fn quorum_must_be_exceeded() {
+        assert!(!proposal(30, 19, 19).has_reached_quorum());
+        assert!(proposal(30, 20, 20).has_reached_quorum());
+        assert!(!proposal(20, 13, 13).has_reached_quorum());
+        assert!(proposal(20, 14, 14).has_reached_quorum());
+        assert!(!proposal(0, 0, 0).has_reached_quorum());
+    }
+
+    #[test]
+    pub fn approval_follows_the_proposal_threshold() {
+        assert!(proposal(20, 20, 16).has_reached_approval());
+        assert!(!proposal(20, 20, 15).has_reached_approval());
+
+        let mut lighter = proposal(20, 20, 11);
+        lighter.threshold.approval = 51;
+        assert!(lighter.has_reached_approval());
+    }
+
+    #[test]
+    pub fn config_proposals_share_the_config_validation() {
+        assert!(ConfigAccountType::NftHolderShare(49).verify().is_err());
+        assert!(ConfigAccountType::NftHolderShare(50).verify().is_ok());
+        assert!(ConfigAccountType::InitialRedemptionFee(26)
+            .verify()
+            .is_err());
+        assert!(ConfigAccountType::GovernanceExpirationTime(86400)
+            .verify()
+            .is_err());
+        assert!(ConfigAccountType::Website("a".repeat(65)).verify().is_err());
+        assert!(ConfigAccountType::ProposalDeposit(LAMPORTS_PER_SOL)
+            .verify()
+            .is_err());
+        assert!(ConfigAccountType::ProposalDeposit(2 * LAMPORTS_PER_SOL)
+            .verify()
+            .is_ok());
+        assert!(ConfigAccountType::ProposalDeposit(101 * LAMPORTS_PER_SOL)
+            .verify()
+            .is_err());
+
+        let unsorted = vec![
+            LockBoostTier {
+                min_duration: 86400 * 60,
+                boost_bps: 100,
+            },
+            LockBoostTier {
+                min_duration: 86400 * 30,
+                boost_bps: 200,
+            },
+        ];
+        assert!(ConfigAccountType::LockBoostTiers(unsorted)
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    pub fn referral_fees_can_reach_the_whole_mint_fee_without_overflowing() {
+        assert_eq!(
+            config(LAMPORTS_PER_SOL, 250).get_referral_fee(),
+            LAMPORTS_PER_SOL / 40
+        );
+        assert_eq!(config(u64::MAX, 10000).get_referral_fee(), u64::MAX);
+        assert!(ConfigAccountType::ReferralFeeBps(10000).verify().is_ok());
+        assert!(ConfigAccountType::ReferralFeeBps(10001).verify().is_err());
+    }
+
+    #[test]
+    pub fn batched_proposal_space_matches_its_serialization() {
+        let mut batched = proposal(20, 3, 2);
+        batched.date_finalized = Some(0);
+        batched.did_proposal_pass = Some(true);
+        batched.actions.push(GovernanceType::ConfigAccount(
+            ConfigAccountType::ValidatorName("validator".to_string()),
+        ));
+        batched.actions.push(GovernanceType::ConfigAccount(
+            ConfigAccountType::RarityRerollFee(Some(LAMPORTS_PER_SOL)),
+        ));
+        batched.actions.push(GovernanceType::TreasurySpend {
+            recipient: Pubkey::new_unique(),
+            lamports: LAMPORTS_PER_SOL,
+            memo: "audit".to_string(),
+        });
+        assert!(batched.verify().is_ok());
+        assert_eq!(batched.get_space(), batched.try_to_vec().unwrap().len());
+
+        batched.actions.clear();
+        assert!(batched.verify().is_err());
+    }
+
+    #[test]
+    pub fn proposals_can_only_spend_from_the_treasury_once() {
+        let mut spending = proposal(20, 3, 2);
+        let spend = GovernanceType::TreasurySpend {
+            recipient: Pubkey::new_unique(),
+            lamports: LAMPORTS_PER_SOL,
+            memo: "audit".to_string(),
+        };
+        spending.actions.push(spend.clone());
+        assert!(spending.verify().is_ok());
+        // Several spends would each take their capped share of what the previous ones left.
+        spending.actions.push(spend);
+        assert!(spending.verify().is_err());
+    }
+
+    #[test]
+    pub fn uris_account_space_matches_its_serialization() {
+        let mut uris_data = uris_account();
+        assert_eq!(uris_data.get_space(), uris_data.try_to_vec().unwrap().len());
+
+        uris_data.reward_weights = vec![100, 150, 300];
+        uris_data.uris = vec![
+            vec!["a".to_string(), "bc".to_string()],
+            vec!["d".to_string()],
+        ];
+        uris_data.uri_pages = vec![0, 2];
+        uris_data.base_uri = "https://arweave.net/".to_string();
+        uris_data.uri_roots = vec![
+            None,
+            None,
+            Some(UrisRoot {
+                root: [1; 32],
+                count: 4,
+            }),
+        ];
+        assert_eq!(uris_data.get_space(), uris_data.try_to_vec().unwrap().len());
+    }
+
+    #[test]
+    pub fn batched_proposals_meet_the_strictest_threshold() {
+        let threshold = ProposalThreshold::strictest(
+            [
+                ProposalThreshold {
+                    quorum: 65,
+                    approval: 51,
+                },
+                ProposalThreshold {
+                    quorum: 10,
+                    approval: 80,
+                },
+            ]
+            .into_iter(),
+        );
+        assert_eq!(threshold.quorum, 65);
+        assert_eq!(threshold.approval, 80);
+    }
+
+    pub fn account<'a>(
+        key: &'a Pubkey,
+        owner: &'a Pubkey,
+        lamports: &'a mut u64,
+        data: &'a mut [u8],
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, true, lamports, data, owner, false, 0)
+    }
+
+    /// SlotHashes sysvar data holding the given (slot, hash) entries, newest first.
+    pub fn slot_hashes_data(entries: &[(u64, [u8; 32])]) -> Vec<u8> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+        data
+    }
+
+    pub fn unimprinted_nft(rarity_seed_slot: u64, rarity_commitment: Option<[u8; 32]>) -> NftData {
+        NftData {
+            validation_phrase: NFT_DATA_VAL_PHRASE,
+            rarity: None,
+            rarity_seed_slot: Some(rarity_seed_slot),
+            funds_location: FundsLocation::Undelegated,
+            numeration: 0,
+            date_created: 0,
+            last_withdrawal_epoch: None,
+            last_delegation_epoch: None,
+            all_withdraws: Vec::new(),
+            all_votes: BTreeMap::new(),
+            rarity_commitment,
+            reroll_count: 0,
+            last_reroll_date: None,
+            is_programmable: false,
+            last_known_owner: None,
+            lock_until: None,
+            lock_boost_tier: None,
+            last_owner_seen_epoch: None,
+            weighted_backing: 0,
+            boost_weight: 0,
+        }
+    }
+
+    pub fn uris_account() -> UrisAccount {
+        UrisAccount::new(
+            vec![6000, 3000, 1000],
+            vec!["Common".to_string(), "Rare".to_string(), "Epic".to_string()],
+            Vec::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn slot_hashes_are_found_by_slot() {
+        let key = sysvar::slot_hashes::id();
+        let owner = sysvar::id();
+        let (mut lamports, mut data) = (
+            0,
+            slot_hashes_data(&[(30, [3; 32]), (20, [2; 32]), (10, [1; 32])]),
+        );
+        let slot_hashes = account(&key, &owner, &mut lamports, &mut data);
+
+        assert_eq!(
+            get_slot_hash(&slot_hashes, 20).unwrap().unwrap().to_bytes(),
+            [2; 32]
+        );
+        assert_eq!(
+            get_slot_hash(&slot_hashes, 10).unwrap().unwrap().to_bytes(),
+            [1; 32]
+        );
+        assert!(get_slot_hash(&slot_hashes, 25).unwrap().is_none());
+        assert!(get_slot_hash(&slot_hashes, 5).unwrap().is_none());
+    }
+
+    #[test]
+    pub fn vrf_results_are_read_from_the_stand_in_oracle() {
+        let program_id = Pubkey::new_unique();
+        let oracle_id = stand_in_oracle::id();
+        let oracle_authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (vrf_state_key, _) = Pubkey::find_program_address(&[VRF_STATE_KEY], &program_id);
+        let (vrf_result_key, _) = stand_in_oracle::get_vrf_result_address(&mint);
+
+        let mut vrf_state_data = VrfState {
+            validation_phrase: VRF_STATE_VAL_PHRASE,
+            oracle_program: Some(oracle_id),
+            oracle_authority,
+        }
+        .try_to_vec()
+        .unwrap();
+        let mut vrf_state_lamports = 0;
+        let vrf_state = account(
+            &vrf_state_key,
+            &program_id,
+            &mut vrf_state_lamports,
+            &mut vrf_state_data,
+        );
+
+        let vrf_result = |authority: Pubkey, mint: Pubkey, fulfilled_slot: u64| {
+            stand_in_oracle::VrfResult {
+                authority,
+                mint,
+                fulfilled_slot,
+                result: [7; 32],
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let read = |vrf_result_key: &Pubkey, data: &mut Vec<u8>| {
+            let mut lamports = 0;
+            let vrf_result_info = account(vrf_result_key, &oracle_id, &mut lamports, data);
+            let accounts = [vrf_result_info];
+            get_vrf_result(&program_id, &vrf_state, &mut accounts.iter(), &mint, 90)
+        };
+
+        assert_eq!(
+            read(
+                &vrf_result_key,
+                &mut vrf_result(oracle_authority, mint, 100)
+            )
+            .unwrap(),
+            Some([7; 32])
+        );
+        assert!(read(&vrf_result_key, &mut vrf_result(oracle_authority, mint, 80)).is_err());
+        assert!(read(
+            &vrf_result_key,
+            &mut vrf_result(oracle_authority, Pubkey::new_unique(), 100)
+        )
+        .is_err());
+        // Only the results fulfilled by the oracle authority are accepted.
+        assert!(read(&vrf_result_key, &mut vrf_result(vrf_state_key, mint, 100)).is_err());
+        // The result must be the request of the mint, not another account of the oracle.
+        let (other_request_key, _) = stand_in_oracle::get_vrf_result_address(&Pubkey::new_unique());
+        assert!(read(
+            &other_request_key,
+            &mut vrf_result(oracle_authority, mint, 100)
+        )
+        .is_err());
+    }
+
+    #[test]
+    pub fn rarity_seeds_come_from_the_seed_slot_hash_and_the_reveal() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (vrf_state_key, _) = Pubkey::find_program_address(&[VRF_STATE_KEY], &program_id);
+        let system_program = solana_program::system_program::id();
+        let (slot_hashes_key, sysvar_owner) = (sysvar::slot_hashes::id(), sysvar::id());
+        let (mut lamports, mut vrf_lamports, mut vrf_data) = (0, 0, Vec::new());
+        let mut data = slot_hashes_data(&[(120, [9; 32]), (100, [4; 32])]);
+        let slot_hashes = account(&slot_hashes_key, &sysvar_owner, &mut lamports, &mut data);
+        let vrf_state = account(
+            &vrf_state_key,
+            &system_program,
+            &mut vrf_lamports,
+            &mut vrf_data,
+        );
+        let seed = |nft_data: &NftData, current_slot: u64, reveal: Option<[u8; 32]>| {
+            let request = RaritySeedRequest {
+                nft_data,
+                mint_key: &mint,
+                current_slot,
+                reveal,
+            };
+            get_rarity_seed(
+                &program_id,
+                &request,
+                &slot_hashes,
+                &vrf_state,
+                &mut [].iter(),
+            )
+            .map(|(seed, _seed_hash)| seed)
+        };
+
+        let nft = unimprinted_nft(100, None);
+        assert!(seed(&nft, 100, None).is_err());
+        let plain_seed = seed(&nft, 130, None).unwrap();
+        assert!(plain_seed < CUMMULATED_RARITY);
+        assert_eq!(seed(&nft, 140, None).unwrap(), plain_seed);
+
+        let secret = [5; 32];
+        let committed = unimprinted_nft(100, Some(hashv(&[&secret]).to_bytes()));
+        assert!(seed(&committed, 130, None).is_err());
+        assert!(seed(&committed, 130, Some([6; 32])).is_err());
+        assert!(seed(&committed, 130, Some(secret)).unwrap() < CUMMULATED_RARITY);
+
+        // A seed slot that aged out of the SlotHashes sysvar must be committed again.
+        let aged_out = unimprinted_nft(50, None);
+        assert!(seed(&aged_out, 130, None).is_err());
+
+        // Even when it was committed, as the holder could otherwise pick the slot their reveal is seeded by.
+        let aged_out = unimprinted_nft(50, Some(hashv(&[&secret]).to_bytes()));
+        assert!(seed(&aged_out, 130, Some(secret)).is_err());
+    }
+
+    #[test]
+    pub fn aged_out_rarity_seeds_are_recommitted_as_rerolls() {
+        let program_id = Pubkey::new_unique();
+        let spl_token_id = spl_token::id();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (nft_key, _) =
+            Pubkey::find_program_address(&[NFT_ACCOUNT_CONST, mint.as_ref()], &program_id);
+        let (config_key, _) = Pubkey::find_program_address(&[INGL_CONFIG_SEED], &program_id);
+        let token_account_key = get_associated_token_address(&payer, &mint);
+        let (clock_key, rent_key) = (sysvar::clock::id(), sysvar::rent::id());
+
+        let mut config_data = config(0, 0);
+        config_data.rarity_reroll_cooldown = 86400;
+        let nft_data = unimprinted_nft(100, None);
+        let mut nft_bytes = nft_data.try_to_vec().unwrap();
+        nft_bytes.resize(nft_data.get_space() + 32, 0);
+        let mut config_bytes = config_data.try_to_vec().unwrap();
+        let mut token_account_bytes = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint,
+                owner: payer,
+                amount: 1,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            },
+            &mut token_account_bytes,
+        )
+        .unwrap();
+        let rent = Rent::default();
+        let mut rent_bytes = [
+            &rent.lamports_per_byte_year.to_le_bytes()[..],
+            &rent.exemption_threshold.to_le_bytes(),
+            &[rent.burn_percent],
+        ]
+        .concat();
+        let mut clock_data = vec![0; 40];
+        let (mut payer_lamports, mut nft_lamports, mut mint_lamports) = (0, LAMPORTS_PER_SOL, 0);
+        let (mut token_account_lamports, mut config_lamports) = (0, 0);
+        let (mut clock_lamports, mut rent_lamports) = (0, 0);
+        let (mut payer_data, mut mint_data) = (vec![], vec![]);
+
+        let payer_info = AccountInfo::new(
+            &payer,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let nft_info = account(&nft_key, &program_id, &mut nft_lamports, &mut nft_bytes);
+        let mint_info = account(&mint, &spl_token_id, &mut mint_lamports, &mut mint_data);
+        let token_account_info = account(
+            &token_account_key,
+            &spl_token_id,
+            &mut token_account_lamports,
+            &mut token_account_bytes,
+        );
+        let config_info = account(
+            &config_key,
+            &program_id,
+            &mut config_lamports,
+            &mut config_bytes,
+        );
+        let clock_info = account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data);
+        let rent_info = account(&rent_key, &sysvar_id, &mut rent_lamports, &mut rent_bytes);
+        let accounts = [
+            payer_info,
+            nft_info.clone(),
+            mint_info,
+            token_account_info,
+            config_info,
+            clock_info.clone(),
+            rent_info,
+        ];
+        let commit = |slot: u64, unix_timestamp: i64, commitment: [u8; 32]| {
+            let clock = [
+                slot.to_le_bytes(),
+                0i64.to_le_bytes(),
+                0u64.to_le_bytes(),
+                0u64.to_le_bytes(),
+                unix_timestamp.to_le_bytes(),
+            ]
+            .concat();
+            clock_info.data.borrow_mut().copy_from_slice(&clock);
+            commit_rarity_seed(&program_id, &accounts, commitment, 0, true, true)?;
+            NftData::parse(&nft_info, &program_id)
+        };
+
+        let nft_data = commit(50, 0, [1; 32]).unwrap();
+        assert_eq!(nft_data.rarity_commitment, Some([1; 32]));
+        assert!(commit(60, 0, [2; 32]).is_err());
+        // Committing once the seed slot is reached would choose the seed after seeing its slot hash.
+        assert!(commit(100, 0, [2; 32]).is_err());
+
+        let aged_out_slot = 100 + slot_hashes::MAX_ENTRIES as u64 + 1;
+        let nft_data = commit(aged_out_slot, 1000, [2; 32]).unwrap();
+        assert_eq!(nft_data.rarity_commitment, Some([2; 32]));
+        assert_eq!(
+            nft_data.rarity_seed_slot,
+            Some(aged_out_slot + RARITY_IMPRINT_WAIT_SLOTS)
+        );
+        assert_eq!(nft_data.reroll_count, 1);
+        assert_eq!(nft_data.last_reroll_date, Some(1000));
+
+        // Recommits wait out the reroll cooldown, so withholding imprints to grind rarities is slow.
+        let aged_out_slot =
+            nft_data.rarity_seed_slot.unwrap() + slot_hashes::MAX_ENTRIES as u64 + 1;
+        assert!(commit(aged_out_slot, 1000 + 86399, [3; 32]).is_err());
+        let nft_data = commit(aged_out_slot, 1000 + 86400, [3; 32]).unwrap();
+        assert_eq!(nft_data.reroll_count, 2);
+    }
+
+    #[test]
+    pub fn rerolls_reimprint_a_rarity_seeded_by_the_newest_slot_hash() {
+        let program_id = Pubkey::new_unique();
+        let spl_token_id = spl_token::id();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let mpl_token_metadata_id = mpl_token_metadata::id();
+        let vote_program_id = solana_program::vote::program::id();
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id).0;
+        let (config_key, general_key, uris_key) = (
+            find(&[INGL_CONFIG_SEED]),
+            find(&[GENERAL_ACCOUNT_SEED]),
+            find(&[URIS_ACCOUNT_SEED]),
+        );
+        let nft_key = find(&[NFT_ACCOUNT_CONST, mint.as_ref()]);
+        let mint_authority_key = find(&[INGL_MINT_AUTHORITY_KEY]);
+        let edition_key = Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                mpl_token_metadata_id.as_ref(),
+                mint.as_ref(),
+                b"edition",
+            ],
+            &mpl_token_metadata_id,
+        )
+        .0;
+        let metadata_key = find_metadata_account(&mint).0;
+        let token_account_key = get_associated_token_address(&payer, &mint);
+        let (slot_hashes_key, instructions_key) =
+            (sysvar::slot_hashes::id(), sysvar::instructions::id());
+        let clock_key = sysvar::clock::id();
+
+        let mut config_data = config(0, 0);
+        config_data.rarity_reroll_fee = Some(LAMPORTS_PER_SOL);
+        let vote_key = config_data.vote_account;
+        let mut uris_data = uris_account();
+        uris_data.reward_weights = vec![100, 150, 300];
+        uris_data.uris = ["common", "rare", "epic"]
+            .map(|uri| vec![uri.to_string()])
+            .to_vec();
+        let mut nft_data = unimprinted_nft(0, None);
+        nft_data.rarity = Some(0);
+        nft_data.funds_location = FundsLocation::Delegated;
+        nft_data.last_delegation_epoch = Some(0);
+        nft_data.weighted_backing = 2 * LAMPORTS_PER_SOL;
+        let general_data = GeneralData {
+            total_delegated: 2 * LAMPORTS_PER_SOL,
+            total_weighted_delegated: 2 * LAMPORTS_PER_SOL,
+            ..GeneralData::default()
+        };
+        let padded = |mut data: Vec<u8>| {
+            data.resize(data.len() + 64, 0);
+            data
+        };
+        let mut config_bytes = padded(config_data.try_to_vec().unwrap());
+        let mut general_bytes = padded(general_data.try_to_vec().unwrap());
+        let mut uris_bytes = padded(uris_data.try_to_vec().unwrap());
+        let mut nft_bytes = padded(nft_data.try_to_vec().unwrap());
+        let mut metadata_bytes = padded(
+            Metadata {
+                key: Key::MetadataV1,
+                update_authority: mint_authority_key,
+                mint,
+                data: Data::default(),
+                primary_sale_happened: false,
+                is_mutable: true,
+                edition_nonce: None,
+                token_standard: None,
+                collection: None,
+                uses: None,
+                collection_details: None,
+                programmable_config: None,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        let mut token_account_bytes = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint,
+                owner: payer,
+                amount: 1,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            },
+            &mut token_account_bytes,
+        )
+        .unwrap();
+        let mut slot_hashes_bytes = slot_hashes_data(&[(41, [7; 32]), (40, [3; 32])]);
+        let program_key = Pubkey::new_unique();
+        let instruction = || BorrowedInstruction {
+            program_id: &program_key,
+            accounts: Vec::new(),
+            data: &[],
+        };
+        // The reroll is the first of two instructions of its transaction.
+        let mut instructions_bytes = construct_instructions_data(&[instruction(), instruction()]);
+        let mut clock_data = clock_bytes(86400);
+        let (mut payer_lamports, mut nft_lamports, mut mint_lamports) = (0, 0, 0);
+        let (mut token_account_lamports, mut mint_authority_lamports) = (0, 0);
+        let (mut edition_lamports, mut metadata_lamports, mut vote_lamports) = (0, 0, 0);
+        let (mut config_lamports, mut uris_lamports, mut general_lamports) = (0, 0, 0);
+        let (mut slot_hashes_lamports, mut instructions_lamports) = (0, 0);
+        let (mut system_program_lamports, mut clock_lamports) = (0, 0);
+        let (mut payer_data, mut mint_data, mut mint_authority_data) = (vec![], vec![], vec![]);
+        let (mut edition_data, mut vote_data, mut system_program_data) = (vec![], vec![], vec![]);
+
+        let payer_info = AccountInfo::new(
+            &payer,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let nft_info = account(&nft_key, &program_id, &mut nft_lamports, &mut nft_bytes);
+        let mint_info = account(&mint, &spl_token_id, &mut mint_lamports, &mut mint_data);
+        let token_account_info = account(
+            &token_account_key,
+            &spl_token_id,
+            &mut token_account_lamports,
+            &mut token_account_bytes,
+        );
+        let mint_authority_info = account(
+            &mint_authority_key,
+            &program_id,
+            &mut mint_authority_lamports,
+            &mut mint_authority_data,
+        );
+        let edition_info = account(
+            &edition_key,
+            &mpl_token_metadata_id,
+            &mut edition_lamports,
+            &mut edition_data,
+        );
+        let metadata_info = account(
+            &metadata_key,
+            &mpl_token_metadata_id,
+            &mut metadata_lamports,
+            &mut metadata_bytes,
+        );
+        let vote_info = account(
+            &vote_key,
+            &vote_program_id,
+            &mut vote_lamports,
+            &mut vote_data,
+        );
+        let config_info = account(
+            &config_key,
+            &program_id,
+            &mut config_lamports,
+            &mut config_bytes,
+        );
+        let uris_info = account(&uris_key, &program_id, &mut uris_lamports, &mut uris_bytes);
+        let general_info = account(
+            &general_key,
+            &program_id,
+            &mut general_lamports,
+            &mut general_bytes,
+        );
+        let slot_hashes_info = account(
+            &slot_hashes_key,
+            &sysvar_id,
+            &mut slot_hashes_lamports,
+            &mut slot_hashes_bytes,
+        );
+        let instructions_info = account(
+            &instructions_key,
+            &sysvar_id,
+            &mut instructions_lamports,
+            &mut instructions_bytes,
+        );
+        let system_program_info = account(
+            &system_program_id,
+            &system_program_id,
+            &mut system_program_lamports,
+            &mut system_program_data,
+        );
+        let clock_info = account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data);
+        let accounts = [
+            payer_info,
+            nft_info.clone(),
+            mint_info,
+            token_account_info,
+            mint_authority_info,
+            edition_info,
+            metadata_info,
+            vote_info,
+            config_info,
+            uris_info.clone(),
+            general_info.clone(),
+            slot_hashes_info.clone(),
+            instructions_info.clone(),
+            system_program_info,
+            clock_info,
+        ];
+
+        // A later instruction could revert the rerolls whose outcome the holder doesn't like.
+        assert!(reroll_rarity(&program_id, &accounts, None, 0, true).is_err());
+        store_current_index(&mut instructions_info.data.borrow_mut(), 1);
+        reroll_rarity(&program_id, &accounts, None, 0, true).unwrap();
+
+        let nft_data = NftData::parse(&nft_info, &program_id).unwrap();
+        let (_, seed, _) = get_reroll_seed(&slot_hashes_info, &mint, &nft_data).unwrap();
+        let uris_data = UrisAccount::parse(&uris_info, &program_id).unwrap();
+        let rarity = uris_data.get_rarity(seed);
+        assert_eq!(nft_data.rarity, Some(rarity));
+        assert_eq!(nft_data.rarity_seed_slot, Some(41));
+        assert_eq!(nft_data.reroll_count, 1);
+        assert_eq!(nft_data.last_reroll_date, Some(86400));
+
+        // The gem now earns the reward weight of its new rarity.
+        let weighted_backing =
+            2 * LAMPORTS_PER_SOL * uris_data.reward_weights[rarity as usize] as u64 / 100;
+        assert_eq!(nft_data.weighted_backing, weighted_backing);
+        assert_eq!(
+            GeneralData::parse(&general_info, &program_id)
+                .unwrap()
+                .total_weighted_delegated,
+            weighted_backing
+        );
+
+        // Each reroll draws a new seed, even from the same slot hash.
+        let mut next_reroll = NftData::parse(&nft_info, &program_id).unwrap();
+        next_reroll.reroll_count += 1;
+        assert_ne!(
+            get_reroll_seed(&slot_hashes_info, &mint, &next_reroll)
+                .unwrap()
+                .2,
+            get_reroll_seed(&slot_hashes_info, &mint, &nft_data)
+                .unwrap()
+                .2
+        );
+    }
+
+    #[test]
+    pub fn programmable_gems_carry_the_creator_royalties_of_the_config() {
+        let mut config_data = config(0, 0);
+        config_data.creator_royalties = 150;
+        let rule_set = Pubkey::new_unique();
+        let asset_data = get_programmable_asset_data(
+            &config_data,
+            rule_set,
+            "Validator #1".to_string(),
+            "Valida#1".to_string(),
+            Vec::new(),
+            Pubkey::new_unique(),
+        );
+        assert_eq!(asset_data.seller_fee_basis_points, 150);
+        assert_eq!(asset_data.rule_set, Some(rule_set));
+        assert_eq!(
+            asset_data.token_standard,
+            TokenStandard::ProgrammableNonFungible
+        );
+    }
+
+    #[test]
+    pub fn minter_records_can_be_closed_once_their_phase_is_over() {
+        let phase = |start_time: u32, end_time: Option<u32>| MintPhase {
+            start_time,
+            end_time,
+            allowlist_root: None,
+            wallet_cap: Some(2),
+            price_premium: 0,
+        };
+        let mint_phases = MintPhases {
+            validation_phrase: MINT_PHASES_VAL_PHRASE,
+            phases: vec![phase(100, None), phase(200, Some(300)), phase(400, None)],
+            schedule_version: 0,
+        };
+        assert!(!mint_phases.is_phase_over(0, 150));
+        assert!(mint_phases.is_phase_over(0, 200));
+        assert!(!mint_phases.is_phase_over(1, 250));
+        assert!(mint_phases.is_phase_over(1, 300));
+        assert!(!mint_phases.is_phase_over(2, 350));
+        assert!(!mint_phases.is_phase_over(2, u32::MAX));
+        assert!(mint_phases.is_phase_over(3, 150));
+        assert!(mint_phases.try_to_vec().unwrap().len() <= mint_phases.get_space());
+    }
+
+    /// General data with 1000 lamports of holder rewards processed for each of the epochs 1 to 4,
+    /// half of which is owed to a gem of the regular reward weight.
+    pub fn rewarded_general_data() -> GeneralData {
+        GeneralData {
+            vote_rewards: (1..=4)
+                .map(|epoch_number| VoteReward {
+                    epoch_number,
+                    total_reward: 2000,
+                    total_stake: 4 * LAMPORTS_PER_SOL,
+                    total_weighted_stake: 4 * LAMPORTS_PER_SOL,
+                    nft_holders_reward: 1000,
+                    total_boost_weight: 0,
+                    boost_reward: 0,
+                })
+                .collect(),
+            ..GeneralData::default()
+        }
+    }
+
+    #[test]
+    pub fn unsettled_rewards_are_split_at_the_last_epoch_the_previous_owner_was_seen() {
+        let general_data = rewarded_general_data();
+        let previous_owner = Pubkey::new_unique();
+        let holder = Pubkey::new_unique();
+        let mut nft_data = unimprinted_nft(0, None);
+        nft_data.funds_location = FundsLocation::Delegated;
+        nft_data.last_delegation_epoch = Some(0);
+        nft_data.weighted_backing = 2 * LAMPORTS_PER_SOL;
+        nft_data.last_known_owner = Some(previous_owner);
+        nft_data.last_owner_seen_epoch = Some(2);
+
+        let split = |nft_data: &NftData, holder: &Pubkey| {
+            split_total_reward(nft_data, &general_data, holder, 0).unwrap()
+        };
+        assert_eq!(split(&nft_data, &holder), (1000, 1000));
+        assert_eq!(split(&nft_data, &previous_owner), (0, 2000));
+
+        nft_data.last_withdrawal_epoch = Some(2);
+        assert_eq!(split(&nft_data, &holder), (0, 1000));
+    }
+
+    #[test]
+    pub fn rewards_are_shared_in_proportion_of_the_rarity_reward_weights() {
+        let config_data = config(0, 0);
+        let mut uris_data = uris_account();
+        uris_data.reward_weights = vec![100, 150, 300];
+        let weighted_nft = |rarity: u8| {
+            let mut nft_data = unimprinted_nft(0, None);
+            nft_data.rarity = Some(rarity);
+            nft_data.funds_location = FundsLocation::Delegated;
+            nft_data.last_delegation_epoch = Some(0);
+            nft_data.weighted_backing =
+                config_data.get_weighted_backing(uris_data.get_reward_weight(Some(rarity)));
+            nft_data
+        };
+        let (common, epic) = (weighted_nft(0), weighted_nft(2));
+        assert_eq!(epic.weighted_backing, 3 * common.weighted_backing);
+
+        // Each epoch, 800 lamports are shared between the common and the epic gem.
+        let mut general_data = rewarded_general_data();
+        for vote_reward in general_data.vote_rewards.iter_mut() {
+            vote_reward.nft_holders_reward = 800;
+            vote_reward.total_weighted_stake = common.weighted_backing + epic.weighted_backing;
+        }
+        let reward =
+            |nft_data: &NftData| calculate_total_reward(nft_data, &general_data, None, 0).unwrap();
+        assert_eq!(reward(&common), 4 * 200);
+        assert_eq!(reward(&epic), 4 * 600);
+    }
+
+    #[test]
+    pub fn legacy_accounts_are_migrated_in_place() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let payer = Pubkey::new_unique();
+        let validator_id = Pubkey::new_unique();
+        let (config_key, _) = Pubkey::find_program_address(&[INGL_CONFIG_SEED], &program_id);
+        let (general_key, _) = Pubkey::find_program_address(&[GENERAL_ACCOUNT_SEED], &program_id);
+        let (nft_key, proposal_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rent_key = sysvar::rent::id();
+
+        let legacy_config = LegacyValidatorConfig {
+            validation_phrase: LEGACY_INGL_CONFIG_VAL_PHRASE,
+            is_validator_id_switchable: true,
+            max_primary_stake: 1000 * LAMPORTS_PER_SOL,
+            nft_holders_share: 80,
+            initial_redemption_fee: 10,
+            unit_backing: 2 * LAMPORTS_PER_SOL,
+            redemption_fee_duration: 86400 * 30,
+            proposal_quorum: 60,
+            creator_royalties: 100,
+            commission: 5,
+            validator_id,
+            vote_account: Pubkey::new_unique(),
+            governance_expiration_time: 86400 * 40,
+            default_uri: String::new(),
+            validator_name: "Validator".to_string(),
+            twitter_handle: String::new(),
+            discord_invite: String::new(),
+            website: String::new(),
+        };
+        let legacy_general = LegacyGeneralData {
+            validation_phrase: LEGACY_GENERAL_ACCOUNT_VAL_PHRASE,
+            mint_numeration: 2,
+            pending_delegation_total: 0,
+            dealloced: 0,
+            total_delegated: 4 * LAMPORTS_PER_SOL,
+            last_withdraw_epoch: 0,
+            last_total_staked: 0,
+            is_t_stake_initialized: true,
+            proposal_numeration: 1,
+            last_feeless_redemption_date: 0,
+            last_validated_validator_id_proposal: 0,
+            rebalancing_data: RebalancingData::default(),
+            unfinalized_proposals: BTreeSet::from([0]),
+            vote_rewards: (1..=4)
+                .map(|epoch_number| LegacyVoteReward {
+                    epoch_number,
+                    total_reward: 2000,
+                    total_stake: 4 * LAMPORTS_PER_SOL,
+                    nft_holders_reward: 1000,
+                })
+                .collect(),
+        };
+        let legacy_nft = LegacyNftData {
+            validation_phrase: LEGACY_NFT_DATA_VAL_PHRASE,
+            rarity: Some(1),
+            rarity_seed_slot: Some(10),
+            funds_location: FundsLocation::Delegated,
+            numeration: 1,
+            date_created: 0,
+            last_withdrawal_epoch: None,
+            last_delegation_epoch: Some(0),
+            all_withdraws: Vec::new(),
+            all_votes: BTreeMap::from([(0, true)]),
+        };
+        let legacy_action =
+            GovernanceType::ConfigAccount(ConfigAccountType::ValidatorName("Renamed".to_string()));
+        let legacy_proposal = LegacyGovernanceData {
+            validation_phrase: LEGACY_GOVERNANCE_DATA_VAL_PHRASE,
+            expiration_time: 86400 * 40,
+            is_still_ongoing: true,
+            date_finalized: None,
+            did_proposal_pass: None,
+            is_proposal_executed: false,
+            title: "Rename".to_string(),
+            description: String::new(),
+            votes: BTreeMap::from([(1, true)]),
+            governance_type: legacy_action.clone(),
+        };
+        // Accounts can't be reallocated in tests, so the legacy accounts have room for their current layouts.
+        let legacy_bytes = |data: Vec<u8>| {
+            let mut data = data;
+            data.resize(1024, 0);
+            data
+        };
+        let mut config_bytes = legacy_bytes(legacy_config.try_to_vec().unwrap());
+        let mut general_bytes = legacy_bytes(legacy_general.try_to_vec().unwrap());
+        let mut nft_bytes = legacy_bytes(legacy_nft.try_to_vec().unwrap());
+        let mut proposal_bytes = legacy_bytes(legacy_proposal.try_to_vec().unwrap());
+        let rent = Rent::default();
+        let mut rent_bytes = [
+            &rent.lamports_per_byte_year.to_le_bytes()[..],
+            &rent.exemption_threshold.to_le_bytes(),
+            &[rent.burn_percent],
+        ]
+        .concat();
+        let (mut payer_lamports, mut config_lamports, mut general_lamports) = (0, 0, 0);
+        let (mut nft_lamports, mut proposal_lamports) = (0, 0);
+        let (mut system_program_lamports, mut rent_lamports) = (0, 0);
+        let (mut payer_data, mut system_program_data) = (vec![], vec![]);
+
+        let payer_info = AccountInfo::new(
+            &payer,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let config_info = account(
+            &config_key,
+            &program_id,
+            &mut config_lamports,
+            &mut config_bytes,
+        );
+        let general_info = account(
+            &general_key,
+            &program_id,
+            &mut general_lamports,
+            &mut general_bytes,
+        );
+        let nft_info = account(&nft_key, &program_id, &mut nft_lamports, &mut nft_bytes);
+        let proposal_info = account(
+            &proposal_key,
+            &program_id,
+            &mut proposal_lamports,
+            &mut proposal_bytes,
+        );
+        let system_program_info = account(
+            &system_program_id,
+            &system_program_id,
+            &mut system_program_lamports,
+            &mut system_program_data,
+        );
+        let rent_info = account(&rent_key, &sysvar_id, &mut rent_lamports, &mut rent_bytes);
+        let infos = [
+            payer_info,
+            config_info,
+            general_info,
+            nft_info,
+            proposal_info,
+            system_program_info,
+            rent_info,
+        ];
+        // The payer, the config account, the migrated account, the system program and the rent sysvar.
+        let migrate = |target: usize| {
+            migrate_account(
+                &program_id,
+                &[0, 1, target, 5, 6].map(|i| infos[i].clone()),
+                0,
+                true,
+            )
+        };
+
+        // The other accounts are converted with the current config.
+        assert!(migrate(2).is_err());
+        migrate(1).unwrap();
+        let config_data = ValidatorConfig::parse(&infos[1], &program_id).unwrap();
+        let thresholds = config_data.governance_thresholds;
+        assert_eq!(
+            thresholds.program_upgrade.quorum,
+            MIN_CRITICAL_PROPOSAL_QUORUM
+        );
+        assert_eq!(thresholds.profile.quorum, 60);
+        assert_eq!(thresholds.profile.approval, 80);
+        assert_eq!(config_data.proposal_deposit, MIN_PROPOSAL_DEPOSIT);
+        assert_eq!(config_data.validator_name, "Validator");
+
+        for target in 2..=4 {
+            migrate(target).unwrap();
+            // Migrated accounts are no longer legacy ones.
+            assert!(migrate(target).is_err());
+        }
+        let general_data = GeneralData::parse(&infos[2], &program_id).unwrap();
+        assert_eq!(general_data.total_weighted_delegated, 4 * LAMPORTS_PER_SOL);
+        assert_eq!(general_data.unfinalized_proposals, BTreeSet::from([0]));
+        let nft_data = NftData::parse(&infos[3], &program_id).unwrap();
+        assert_eq!(nft_data.weighted_backing, config_data.unit_backing);
+        assert_eq!(nft_data.all_votes, BTreeMap::from([(0, true)]));
+        // Unweighted legacy rewards are still owed in full to the delegated nft.
+        assert_eq!(
+            calculate_total_reward(&nft_data, &general_data, None, 0).unwrap(),
+            4 * 500
+        );
+
+        let governance_data = GovernanceData::parse(&infos[4], &program_id).unwrap();
+        assert_eq!(
+            governance_data.actions.try_to_vec().unwrap(),
+            vec![legacy_action].try_to_vec().unwrap()
+        );
+        assert_eq!(governance_data.threshold, thresholds.profile);
+        assert_eq!(governance_data.expected_votes, 500);
+        assert_eq!(governance_data.proposer, validator_id);
+        assert_eq!(governance_data.votes, BTreeMap::from([(1, true)]));
+    }
+
+    #[test]
+    pub fn settled_rewards_are_credited_and_claimed_by_their_owner_only() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let intruder = Pubkey::new_unique();
+        let (reward_claim_key, _) = Pubkey::find_program_address(
+            &[REWARD_CLAIM_KEY, mint.as_ref(), owner.as_ref()],
+            &program_id,
+        );
+        let (authorized_withdrawer_key, authorized_withdrawer_bump) =
+            Pubkey::find_program_address(&[AUTHORIZED_WITHDRAWER_KEY], &program_id);
+        let mut claim_data = RewardClaim {
+            validation_phrase: REWARD_CLAIM_VAL_PHRASE,
+            mint,
+            owner,
+            amount: 300,
+        }
+        .try_to_vec()
+        .unwrap();
+        let (mut claim_lamports, mut withdrawer_lamports, mut mint_lamports) = (1_000_000, 0, 0);
+        let (mut withdrawer_data, mut mint_data) = (Vec::new(), Vec::new());
+        let (mut owner_lamports, mut intruder_lamports) = (0, 0);
+        let (mut owner_data, mut intruder_data) = (Vec::new(), Vec::new());
+        let reward_claim_info = account(
+            &reward_claim_key,
+            &program_id,
+            &mut claim_lamports,
+            &mut claim_data,
+        );
+        let authorized_withdrawer_info = account(
+            &authorized_withdrawer_key,
+            &system_program_id,
+            &mut withdrawer_lamports,
+            &mut withdrawer_data,
+        );
+        let mint_info = account(
+            &mint,
+            &system_program_id,
+            &mut mint_lamports,
+            &mut mint_data,
+        );
+        let rent_data = Rent::default();
+        let settlement = RewardSettlement {
+            mint: &mint,
+            reward_claim: &reward_claim_info,
+            authorized_withdrawer: &authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: 5,
+            rent_data: &rent_data,
+        };
+
+        credit_reward_claim(&program_id, &settlement, &owner, 200).unwrap();
+        assert_eq!(
+            RewardClaim::parse(&reward_claim_info, &program_id)
+                .unwrap()
+                .amount,
+            500
+        );
+        // The claim is keyed by its owner, so it can't be credited to anyone else.
+        assert!(credit_reward_claim(&program_id, &settlement, &intruder, 200,).is_err());
+
+        let signer = |key, lamports, data| {
+            AccountInfo::new(
+                key,
+                true,
+                true,
+                lamports,
+                data,
+                &system_program_id,
+                false,
+                0,
+            )
+        };
+        let intruder_info = signer(&intruder, &mut intruder_lamports, &mut intruder_data);
+        assert!(claim_settled_rewards(
+            &program_id,
+            &[
+                intruder_info,
+                mint_info.clone(),
+                reward_claim_info.clone(),
+                authorized_withdrawer_info.clone(),
+            ],
+            0,
+        )
+        .is_err());
+        let owner_info = signer(&owner, &mut owner_lamports, &mut owner_data);
+        claim_settled_rewards(
+            &program_id,
+            &[
+                owner_info.clone(),
+                mint_info,
+                reward_claim_info.clone(),
+                authorized_withdrawer_info,
+            ],
+            0,
+        )
+        .unwrap();
+        assert_eq!(owner_info.lamports(), 1_000_000);
+        assert_eq!(reward_claim_info.lamports(), 0);
+    }
+
+    #[test]
+    pub fn sales_pay_royalties_and_settle_the_previous_holder_before_the_seller() {
+        let mut config_data = config(0, 0);
+        config_data.creator_royalties = 500;
+        let listing = Listing {
+            validation_phrase: LISTING_VAL_PHRASE,
+            seller: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            price: 10 * LAMPORTS_PER_SOL,
+            highest_bid: None,
+        };
+        assert_eq!(
+            listing.get_royalty(listing.price, &config_data),
+            LAMPORTS_PER_SOL / 2
+        );
+        assert_eq!(listing.get_royalty(u64::MAX, &config_data), u64::MAX / 20);
+
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let previous_owner = Pubkey::new_unique();
+        let (reward_claim_key, _) = Pubkey::find_program_address(
+            &[
+                REWARD_CLAIM_KEY,
+                listing.mint.as_ref(),
+                previous_owner.as_ref(),
+            ],
+            &program_id,
+        );
+        let (authorized_withdrawer_key, authorized_withdrawer_bump) =
+            Pubkey::find_program_address(&[AUTHORIZED_WITHDRAWER_KEY], &program_id);
+        let mut claim_data = RewardClaim {
+            validation_phrase: REWARD_CLAIM_VAL_PHRASE,
+            mint: listing.mint,
+            owner: previous_owner,
+            amount: 0,
+        }
+        .try_to_vec()
+        .unwrap();
+        let (mut claim_lamports, mut withdrawer_lamports) = (LAMPORTS_PER_SOL, 0);
+        let (mut buyer_lamports, mut buyer_data) = (LAMPORTS_PER_SOL, Vec::new());
+        let mut withdrawer_data = Vec::new();
+        let buyer = Pubkey::new_unique();
+        let buyer_info = AccountInfo::new(
+            &buyer,
+            true,
+            true,
+            &mut buyer_lamports,
+            &mut buyer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let reward_claim_info = account(
+            &reward_claim_key,
+            &program_id,
+            &mut claim_lamports,
+            &mut claim_data,
+        );
+        let authorized_withdrawer_info = account(
+            &authorized_withdrawer_key,
+            &system_program_id,
+            &mut withdrawer_lamports,
+            &mut withdrawer_data,
+        );
+        let mut nft_data = unimprinted_nft(0, None);
+        nft_data.funds_location = FundsLocation::Delegated;
+        nft_data.last_delegation_epoch = Some(0);
+        nft_data.weighted_backing = 2 * LAMPORTS_PER_SOL;
+        nft_data.last_known_owner = Some(previous_owner);
+        nft_data.last_owner_seen_epoch = Some(1);
+
+        let rent_data = Rent::default();
+        let settlement = RewardSettlement {
+            mint: &listing.mint,
+            reward_claim: &reward_claim_info,
+            authorized_withdrawer: &authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: 5,
+            rent_data: &rent_data,
+        };
+        let seller_reward = settle_rewards(
+            &program_id,
+            &mut nft_data,
+            &rewarded_general_data(),
+            &listing.seller,
+            &buyer_info,
+            &settlement,
+            0,
+        )
+        .unwrap();
+        assert_eq!(seller_reward, 1500);
+        assert_eq!(
+            RewardClaim::parse(&reward_claim_info, &program_id)
+                .unwrap()
+                .amount,
+            500
+        );
+        assert_eq!(nft_data.all_withdraws, vec![2000]);
+        assert_eq!(nft_data.last_withdrawal_epoch, Some(5));
+    }
+
+    #[test]
+    pub fn rewards_below_the_claim_rent_are_topped_up_into_a_new_claim() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let mint = Pubkey::new_unique();
+        let (holder, previous_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (reward_claim_key, _) = Pubkey::find_program_address(
+            &[REWARD_CLAIM_KEY, mint.as_ref(), previous_owner.as_ref()],
+            &program_id,
+        );
+        let (authorized_withdrawer_key, authorized_withdrawer_bump) =
+            Pubkey::find_program_address(&[AUTHORIZED_WITHDRAWER_KEY], &program_id);
+        let (mut claim_lamports, mut withdrawer_lamports) = (0, LAMPORTS_PER_SOL);
+        let (mut holder_lamports, mut holder_data) = (LAMPORTS_PER_SOL, Vec::new());
+        let (mut claim_data, mut withdrawer_data) = (vec![0; RewardClaim::get_space()], Vec::new());
+        let holder_info = AccountInfo::new(
+            &holder,
+            true,
+            true,
+            &mut holder_lamports,
+            &mut holder_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        // The claim doesn't exist yet.
+        let reward_claim_info = account(
+            &reward_claim_key,
+            &system_program_id,
+            &mut claim_lamports,
+            &mut claim_data,
+        );
+        let authorized_withdrawer_info = account(
+            &authorized_withdrawer_key,
+            &system_program_id,
+            &mut withdrawer_lamports,
+            &mut withdrawer_data,
+        );
+        let rent_data = Rent::default();
+        assert!(rent_data.minimum_balance(RewardClaim::get_space()) > 500);
+        let settlement = RewardSettlement {
+            mint: &mint,
+            reward_claim: &reward_claim_info,
+            authorized_withdrawer: &authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: 5,
+            rent_data: &rent_data,
+        };
+
+        // A new claim can't be credited less than its rent, which would leave the reward unclaimable.
+        assert!(credit_reward_claim(&program_id, &settlement, &previous_owner, 500,).is_err());
+
+        let mut nft_data = unimprinted_nft(0, None);
+        nft_data.funds_location = FundsLocation::Delegated;
+        nft_data.last_delegation_epoch = Some(0);
+        nft_data.weighted_backing = 2 * LAMPORTS_PER_SOL;
+        nft_data.last_known_owner = Some(previous_owner);
+        nft_data.last_owner_seen_epoch = Some(1);
+        let holder_reward = settle_rewards(
+            &program_id,
+            &mut nft_data,
+            &rewarded_general_data(),
+            &holder,
+            &holder_info,
+            &settlement,
+            0,
+        )
+        .unwrap();
+        assert_eq!(holder_reward, 1500);
+        // The previous holder's 500 and the holder's top-up fund the rent of the claim,
+        // which is returned to the previous holder when they claim it.
+        let reward_claim =
+            RewardClaim::deserialize(&mut &reward_claim_info.data.borrow()[..]).unwrap();
+        assert_eq!(reward_claim.validation_phrase, REWARD_CLAIM_VAL_PHRASE);
+        assert_eq!(reward_claim.owner, previous_owner);
+        assert_eq!(reward_claim.amount, 0);
+        assert_eq!(nft_data.all_withdraws, vec![2000]);
+    }
+
+    #[test]
+    pub fn rewards_are_credited_to_a_claim_address_funded_beforehand() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let mint = Pubkey::new_unique();
+        let (holder, previous_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (reward_claim_key, _) = Pubkey::find_program_address(
+            &[REWARD_CLAIM_KEY, mint.as_ref(), previous_owner.as_ref()],
+            &program_id,
+        );
+        let (authorized_withdrawer_key, authorized_withdrawer_bump) =
+            Pubkey::find_program_address(&[AUTHORIZED_WITHDRAWER_KEY], &program_id);
+        let rent_data = Rent::default();
+        // Lamports were sent to the claim's address before it was created.
+        let mut claim_lamports = rent_data.minimum_balance(RewardClaim::get_space()) - 300;
+        let mut withdrawer_lamports = LAMPORTS_PER_SOL;
+        let (mut holder_lamports, mut holder_data) = (LAMPORTS_PER_SOL, Vec::new());
+        let (mut claim_data, mut withdrawer_data) = (vec![0; RewardClaim::get_space()], Vec::new());
+        let holder_info = AccountInfo::new(
+            &holder,
+            true,
+            true,
+            &mut holder_lamports,
+            &mut holder_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let reward_claim_info = account(
+            &reward_claim_key,
+            &system_program_id,
+            &mut claim_lamports,
+            &mut claim_data,
+        );
+        let authorized_withdrawer_info = account(
+            &authorized_withdrawer_key,
+            &system_program_id,
+            &mut withdrawer_lamports,
+            &mut withdrawer_data,
+        );
+        let settlement = RewardSettlement {
+            mint: &mint,
+            reward_claim: &reward_claim_info,
+            authorized_withdrawer: &authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: 5,
+            rent_data: &rent_data,
+        };
+        // Only the rent the claim still lacks is owed.
+        assert_eq!(settlement.claim_rent(&program_id), 300);
+
+        let mut nft_data = unimprinted_nft(0, None);
+        nft_data.funds_location = FundsLocation::Delegated;
+        nft_data.last_delegation_epoch = Some(0);
+        nft_data.weighted_backing = 2 * LAMPORTS_PER_SOL;
+        nft_data.last_known_owner = Some(previous_owner);
+        nft_data.last_owner_seen_epoch = Some(1);
+        let holder_reward = settle_rewards(
+            &program_id,
+            &mut nft_data,
+            &rewarded_general_data(),
+            &holder,
+            &holder_info,
+            &settlement,
+            0,
+        )
+        .unwrap();
+        assert_eq!(holder_reward, 1500);
+        let reward_claim =
+            RewardClaim::deserialize(&mut &reward_claim_info.data.borrow()[..]).unwrap();
+        assert_eq!(reward_claim.owner, previous_owner);
+        assert_eq!(reward_claim.amount, 200);
+        assert_eq!(nft_data.all_withdraws, vec![2000]);
+    }
+
+    #[test]
+    pub fn bids_can_only_be_cancelled_by_their_bidder() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let mint = Pubkey::new_unique();
+        let bidder = Pubkey::new_unique();
+        let intruder = Pubkey::new_unique();
+        let (listing_key, _) =
+            Pubkey::find_program_address(&[LISTING_KEY, mint.as_ref()], &program_id);
+        let mut listing_data = Listing {
+            validation_phrase: LISTING_VAL_PHRASE,
+            seller: Pubkey::new_unique(),
+            mint,
+            price: 10 * LAMPORTS_PER_SOL,
+            highest_bid: Some(Bid {
+                bidder,
+                amount: 3 * LAMPORTS_PER_SOL,
+            }),
+        }
+        .try_to_vec()
+        .unwrap();
+        listing_data.resize(Listing::get_space(), 0);
+        let (mut listing_lamports, mut mint_lamports) = (4 * LAMPORTS_PER_SOL, 0);
+        let (mut bidder_lamports, mut intruder_lamports) = (0, 0);
+        let (mut mint_data, mut bidder_data, mut intruder_data) =
+            (Vec::new(), Vec::new(), Vec::new());
+        let listing_info = account(
+            &listing_key,
+            &program_id,
+            &mut listing_lamports,
+            &mut listing_data,
+        );
+        let mint_info = account(
+            &mint,
+            &system_program_id,
+            &mut mint_lamports,
+            &mut mint_data,
+        );
+        let signer = |key, lamports, data| {
+            AccountInfo::new(
+                key,
+                true,
+                true,
+                lamports,
+                data,
+                &system_program_id,
+                false,
+                0,
+            )
+        };
+        let intruder_info = signer(&intruder, &mut intruder_lamports, &mut intruder_data);
+        let bidder_info = signer(&bidder, &mut bidder_lamports, &mut bidder_data);
+
+        assert!(cancel_bid(
+            &program_id,
+            &[intruder_info, mint_info.clone(), listing_info.clone()],
+            0,
+        )
+        .is_err());
+        cancel_bid(
+            &program_id,
+            &[bidder_info.clone(), mint_info.clone(), listing_info.clone()],
+            0,
+        )
+        .unwrap();
+        assert_eq!(bidder_info.lamports(), 3 * LAMPORTS_PER_SOL);
+        assert_eq!(listing_info.lamports(), LAMPORTS_PER_SOL);
+        assert!(Listing::parse(&listing_info, &program_id)
+            .unwrap()
+            .highest_bid
+            .is_none());
+        assert!(cancel_bid(&program_id, &[bidder_info, mint_info, listing_info], 0).is_err());
+    }
+
+    #[test]
+    pub fn unlocks_end_the_boost_the_gem_was_locked_with() {
+        let program_id = Pubkey::new_unique();
+        let spl_token_id = spl_token::id();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let mpl_token_metadata_id = mpl_token_metadata::id();
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id).0;
+        let (config_key, general_key, uris_key) = (
+            find(&[INGL_CONFIG_SEED]),
+            find(&[GENERAL_ACCOUNT_SEED]),
+            find(&[URIS_ACCOUNT_SEED]),
+        );
+        let nft_key = find(&[NFT_ACCOUNT_CONST, mint.as_ref()]);
+        let mint_authority_key = find(&[INGL_MINT_AUTHORITY_KEY]);
+        let edition_key = Pubkey::find_program_address(
+            &[
+                PREFIX.as_bytes(),
+                mpl_token_metadata_id.as_ref(),
+                mint.as_ref(),
+                b"edition",
+            ],
+            &mpl_token_metadata_id,
+        )
+        .0;
+        let token_account_key = get_associated_token_address(&payer, &mint);
+        let metadata_key = Pubkey::new_unique();
+        let clock_key = sysvar::clock::id();
+
+        let tier = |boost_bps| LockBoostTier {
+            min_duration: 86400 * 30,
+            boost_bps,
+        };
+        let mut config_data = config(0, 0);
+        config_data.lock_boost_tiers = vec![tier(200)];
+        let mut nft_data = unimprinted_nft(0, None);
+        nft_data.rarity = Some(1);
+        nft_data.funds_location = FundsLocation::Delegated;
+        nft_data.last_delegation_epoch = Some(0);
+        nft_data.weighted_backing = 2 * LAMPORTS_PER_SOL;
+        let padded = |mut data: Vec<u8>| {
+            data.resize(data.len() + 64, 0);
+            data
+        };
+        let mut config_bytes = padded(config_data.try_to_vec().unwrap());
+        let mut general_bytes = padded(GeneralData::default().try_to_vec().unwrap());
+        let mut uris_bytes = padded(uris_account().try_to_vec().unwrap());
+        let mut nft_bytes = padded(nft_data.try_to_vec().unwrap());
+        let mut token_account_bytes = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint,
+                owner: payer,
+                amount: 1,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            },
+            &mut token_account_bytes,
+        )
+        .unwrap();
+        let clock_bytes = |unix_timestamp: i64| {
+            [
+                0u64.to_le_bytes(),
+                0i64.to_le_bytes(),
+                0u64.to_le_bytes(),
+                0u64.to_le_bytes(),
+            ]
+            .concat()
+            .into_iter()
+            .chain(unix_timestamp.to_le_bytes())
+            .collect::<Vec<u8>>()
+        };
+        let mut clock_data = clock_bytes(0);
+        let (mut payer_lamports, mut config_lamports, mut general_lamports) = (0, 0, 0);
+        let (mut uris_lamports, mut mint_lamports, mut nft_lamports) = (0, 0, 0);
+        let (mut token_account_lamports, mut mint_authority_lamports) = (0, 0);
+        let (mut edition_lamports, mut metadata_lamports) = (0, 0);
+        let (mut spl_token_lamports, mut clock_lamports) = (0, 0);
+        let (mut payer_data, mut mint_data, mut mint_authority_data) = (vec![], vec![], vec![]);
+        let (mut edition_data, mut metadata_data, mut spl_token_data) = (vec![], vec![], vec![]);
+
+        let payer_info = AccountInfo::new(
+            &payer,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let config_info = account(
+            &config_key,
+            &program_id,
+            &mut config_lamports,
+            &mut config_bytes,
+        );
+        let general_info = account(
+            &general_key,
+            &program_id,
+            &mut general_lamports,
+            &mut general_bytes,
+        );
+        let uris_info = account(&uris_key, &program_id, &mut uris_lamports, &mut uris_bytes);
+        let mint_info = account(&mint, &spl_token_id, &mut mint_lamports, &mut mint_data);
+        let nft_info = account(&nft_key, &program_id, &mut nft_lamports, &mut nft_bytes);
+        let token_account_info = account(
+            &token_account_key,
+            &spl_token_id,
+            &mut token_account_lamports,
+            &mut token_account_bytes,
+        );
+        let mint_authority_info = account(
+            &mint_authority_key,
+            &program_id,
+            &mut mint_authority_lamports,
+            &mut mint_authority_data,
+        );
+        let edition_info = account(
+            &edition_key,
+            &mpl_token_metadata_id,
+            &mut edition_lamports,
+            &mut edition_data,
+        );
+        let metadata_info = account(
+            &metadata_key,
+            &mpl_token_metadata_id,
+            &mut metadata_lamports,
+            &mut metadata_data,
+        );
+        let spl_token_info = account(
+            &spl_token_id,
+            &system_program_id,
+            &mut spl_token_lamports,
+            &mut spl_token_data,
+        );
+        let clock_info = account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data);
+
+        lock_nft(
+            &program_id,
+            &[
+                payer_info.clone(),
+                config_info.clone(),
+                general_info.clone(),
+                uris_info.clone(),
+                mint_info.clone(),
+                nft_info.clone(),
+                token_account_info.clone(),
+                mint_authority_info.clone(),
+                edition_info.clone(),
+                metadata_info.clone(),
+                spl_token_info.clone(),
+                clock_info.clone(),
+            ],
+            86400 * 30,
+            0,
+            true,
+        )
+        .unwrap();
+        let boost_weight = 2 * LAMPORTS_PER_SOL / 50;
+        assert_eq!(
+            NftData::parse(&nft_info, &program_id).unwrap().boost_weight,
+            boost_weight
+        );
+        assert_eq!(
+            GeneralData::parse(&general_info, &program_id)
+                .unwrap()
+                .total_boost_weight,
+            boost_weight
+        );
+
+        // A governance change of the tier doesn't change the weight the gem was locked with.
+        config_data.lock_boost_tiers = vec![tier(400)];
+        config_data
+            .serialize(&mut &mut config_info.data.borrow_mut()[..])
+            .unwrap();
+        clock_info
+            .data
+            .borrow_mut()
+            .copy_from_slice(&clock_bytes(86400 * 30));
+        unlock_nft(
+            &program_id,
+            &[
+                payer_info,
+                general_info.clone(),
+                mint_info,
+                nft_info.clone(),
+                token_account_info,
+                mint_authority_info,
+                edition_info,
+                metadata_info,
+                spl_token_info,
+                clock_info,
+            ],
+            0,
+            true,
+        )
+        .unwrap();
+        let nft_data = NftData::parse(&nft_info, &program_id).unwrap();
+        assert_eq!((nft_data.lock_until, nft_data.boost_weight), (None, 0));
+        assert_eq!(
+            GeneralData::parse(&general_info, &program_id)
+                .unwrap()
+                .total_boost_weight,
+            0
+        );
+    }
+
+    /// Seed hashes falling in the common rarity, whose page and uri bits vary.
+    pub fn common_seed_hashes(count: u32) -> Vec<[u8; 32]> {
+        (0..count)
+            .map(|i| {
+                let mut seed_hash = hashv(&[&i.to_le_bytes()]).to_bytes();
+                seed_hash[RARITY_SEED_OFFSET..RARITY_SEED_OFFSET + 8].fill(0);
+                seed_hash
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn uris_are_picked_independently_of_the_rarity_seed() {
+        let mut uris_data = uris_account();
+        uris_data.uri_pages = vec![4];
+        let uris_page = UrisPage {
+            validation_phrase: URIS_PAGE_VAL_PHRASE,
+            rarity: 0,
+            page: 0,
+            uris: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        };
+        let picks = common_seed_hashes(200)
+            .iter()
+            .map(|seed_hash| {
+                (
+                    uris_data.get_page(0, seed_hash).unwrap(),
+                    uris_page.get_uri(seed_hash),
+                )
+            })
+            .collect::<std::collections::BTreeSet<(u16, String)>>();
+        assert_eq!(picks.len(), 12);
+        assert_eq!(uris_data.get_page(1, &[0; 32]), None);
+
+        // Uris beyond the width of their rarity are reachable too.
+        let mut uris_data = uris_account();
+        uris_data.uris = vec![(0..7000).map(|i| i.to_string()).collect()];
+        assert!(common_seed_hashes(100).iter().any(|seed_hash| {
+            uris_data.get_uri(0, seed_hash).0.parse::<u32>().unwrap() >= 6000
+        }));
+    }
+
+    #[test]
+    pub fn committed_uris_are_verified_against_their_merkle_root() {
+        let uris = ["a", "b", "c", "d"];
+        let leaves = uris
+            .iter()
+            .enumerate()
+            .map(|(index, uri)| UrisRoot::get_leaf(index as u32, uri))
+            .collect::<Vec<[u8; 32]>>();
+        let node = |a: [u8; 32], b: [u8; 32]| {
+            if a <= b {
+                hashv(&[&a, &b]).to_bytes()
+            } else {
+                hashv(&[&b, &a]).to_bytes()
+            }
+        };
+        let pairs = [node(leaves[0], leaves[1]), node(leaves[2], leaves[3])];
+        let uris_root = UrisRoot {
+            root: node(pairs[0], pairs[1]),
+            count: uris.len() as u32,
+        };
+        let proof = |index: usize| vec![leaves[index ^ 1], pairs[1 - index / 2]];
+
+        for seed_hash in common_seed_hashes(8) {
+            let index = uris_root.get_index(&seed_hash) as usize;
+            let leaf = UrisRoot::get_leaf(index as u32, uris[index]);
+            assert!(verify_merkle_proof(&proof(index), &uris_root.root, leaf));
+            // A committed uri can't be passed off at another index, nor another uri at its index.
+            let other = (index + 1) % uris.len();
+            let moved = UrisRoot::get_leaf(other as u32, uris[index]);
+            assert!(!verify_merkle_proof(&proof(other), &uris_root.root, moved));
+            let swapped = UrisRoot::get_leaf(index as u32, uris[other]);
+            assert!(!verify_merkle_proof(
+                &proof(index),
+                &uris_root.root,
+                swapped
+            ));
+        }
+    }
+
+    #[test]
+    pub fn frozen_uris_pages_rarities_and_weights_change_through_governance() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let payer = Pubkey::new_unique();
+        let uris_key = Pubkey::find_program_address(&[URIS_ACCOUNT_SEED], &program_id).0;
+        let page_key =
+            Pubkey::find_program_address(&[URIS_PAGE_KEY, &[0], &0u16.to_be_bytes()], &program_id)
+                .0;
+        let mut uris_data = uris_account();
+        uris_data.uri_pages = vec![1];
+        uris_data.is_frozen = true;
+        let mut uris_bytes = uris_data.try_to_vec().unwrap();
+        uris_bytes.resize(uris_bytes.len() + 64, 0);
+        let mut page_bytes = UrisPage {
+            validation_phrase: URIS_PAGE_VAL_PHRASE,
+            rarity: 0,
+            page: 0,
+            uris: vec!["a".to_string(), "b".to_string()],
+        }
+        .try_to_vec()
+        .unwrap();
+        page_bytes.resize(page_bytes.len() + 64, 0);
+        let (mut payer_lamports, mut uris_lamports, mut page_lamports) = (0, 0, 0);
+        let mut payer_data = Vec::new();
+        let payer_info = account(
+            &payer,
+            &system_program_id,
+            &mut payer_lamports,
+            &mut payer_data,
+        );
+        let uris_info = account(&uris_key, &program_id, &mut uris_lamports, &mut uris_bytes);
+        let page_info = account(&page_key, &program_id, &mut page_lamports, &mut page_bytes);
+        let execute = |governance_type: UrisGovernance| {
+            governance_type.verify()?;
+            handle_uris_governance_change(
+                &program_id,
+                &payer_info,
+                &mut [uris_info.clone(), page_info.clone()].iter(),
+                governance_type,
+                0,
+            )
+        };
+        let names = |count: usize| vec!["Name".to_string(); count];
+
+        execute(UrisGovernance::SetRarities {
+            rarities: vec![5000, 4000, 1000],
+            rarity_names: names(3),
+        })
+        .unwrap();
+        assert!(execute(UrisGovernance::SetRarities {
+            rarities: vec![5000, 5000],
+            rarity_names: names(2),
+        })
+        .is_err());
+        assert!(execute(UrisGovernance::SetRarities {
+            rarities: vec![5000, 4000, 2000],
+            rarity_names: names(3),
+        })
+        .is_err());
+        execute(UrisGovernance::SetRewardWeights(vec![100, 200, 300])).unwrap();
+        assert!(execute(UrisGovernance::SetRewardWeights(vec![100, 200])).is_err());
+        let uris_data = UrisAccount::parse(&uris_info, &program_id).unwrap();
+        assert_eq!(uris_data.rarities, vec![5000, 9000, 10000]);
+        assert_eq!(uris_data.reward_weights, vec![100, 200, 300]);
+
+        execute(UrisGovernance::ReplaceUrisPage {
+            rarity: 0,
+            page: 0,
+            uris: vec!["c".to_string()],
+        })
+        .unwrap();
+        assert_eq!(
+            UrisPage::parse(&page_info, &program_id).unwrap().uris,
+            vec!["c".to_string()]
+        );
+        assert!(execute(UrisGovernance::ReplaceUrisPage {
+            rarity: 0,
+            page: 1,
+            uris: vec!["c".to_string()],
+        })
+        .is_err());
+    }
+
+    #[test]
+    pub fn proposals_can_only_be_cancelled_before_they_expire() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let clock_key = sysvar::clock::id();
+        let proposer = Pubkey::new_unique();
+        let (proposal_key, _) =
+            Pubkey::find_program_address(&[INGL_PROPOSAL_KEY, &0u32.to_be_bytes()], &program_id);
+        let (general_key, _) = Pubkey::find_program_address(&[GENERAL_ACCOUNT_SEED], &program_id);
+        let mut governance_data = proposal(20, 0, 0);
+        governance_data.proposer = proposer;
+        governance_data.expiration_time = 86400;
+        governance_data.deposit = LAMPORTS_PER_SOL;
+        let mut proposal_data = governance_data.try_to_vec().unwrap();
+        proposal_data.resize(governance_data.get_space(), 0);
+        let mut general_data = GeneralData::default();
+        general_data.unfinalized_proposals.insert(0);
+        let mut general_bytes = general_data.try_to_vec().unwrap();
+        let mut clock_data = clock_bytes(86400);
+        let (mut proposer_lamports, mut proposal_lamports) = (0, 2 * LAMPORTS_PER_SOL);
+        let (mut general_lamports, mut clock_lamports) = (0, 0);
+        let mut proposer_data = Vec::new();
+        let accounts = [
+            AccountInfo::new(
+                &proposer,
+                true,
+                true,
+                &mut proposer_lamports,
+                &mut proposer_data,
+                &system_program_id,
+                false,
+                0,
+            ),
+            account(
+                &proposal_key,
+                &program_id,
+                &mut proposal_lamports,
+                &mut proposal_data,
+            ),
+            account(
+                &general_key,
+                &program_id,
+                &mut general_lamports,
+                &mut general_bytes,
+            ),
+            account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data),
+        ];
+
+        // Once expired, an unsupported proposal can only be finalized, which slashes its deposit.
+        assert!(cancel_proposal(&program_id, &accounts, 0, 0, true).is_err());
+        accounts[3]
+            .data
+            .borrow_mut()
+            .copy_from_slice(&clock_bytes(86399));
+        cancel_proposal(&program_id, &accounts, 0, 0, true).unwrap();
+        assert_eq!(accounts[0].lamports(), LAMPORTS_PER_SOL);
+        assert!(
+            GovernanceData::parse(&accounts[1], &program_id)
+                .unwrap()
+                .is_cancelled
+        );
+    }
+
+    /// Clock sysvar data at the given unix timestamp.
+    pub fn clock_bytes(unix_timestamp: i64) -> Vec<u8> {
+        [0u8; 32]
+            .into_iter()
+            .chain(unix_timestamp.to_le_bytes())
+            .collect()
+    }
+
+    /// Executes a passed proposal of the given actions once per max_actions, at unix_timestamp on an instance
+    /// of the given guardian, returning whether each execution succeeded along with the resulting proposal and config.
+    pub fn execute_passed_proposal(
+        actions: Vec<GovernanceType>,
+        guardian: Option<Pubkey>,
+        unix_timestamp: i64,
+        max_actions: &[Option<u8>],
+    ) -> (Vec<bool>, GovernanceData, ValidatorConfig) {
+        execute_passed_proposal_with(actions, guardian, unix_timestamp, max_actions, |_, _| ())
+    }
+
+    /// Executes the proposal once per max_actions, editing the config before each execution.
+    pub fn execute_passed_proposal_with(
+        actions: Vec<GovernanceType>,
+        guardian: Option<Pubkey>,
+        unix_timestamp: i64,
+        max_actions: &[Option<u8>],
+        edit_config: fn(usize, &mut ValidatorConfig),
+    ) -> (Vec<bool>, GovernanceData, ValidatorConfig) {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let payer = Pubkey::new_unique();
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id).0;
+        let (proposal_key, config_key, general_key) = (
+            find(&[INGL_PROPOSAL_KEY, &0u32.to_be_bytes()]),
+            find(&[INGL_CONFIG_SEED]),
+            find(&[GENERAL_ACCOUNT_SEED]),
+        );
+        let clock_key = sysvar::clock::id();
+        let mut governance_data = proposal(1, 1, 1);
+        governance_data.is_still_ongoing = false;
+        governance_data.date_finalized = Some(0);
+        governance_data.did_proposal_pass = Some(true);
+        governance_data.actions = actions;
+        let padded = |mut data: Vec<u8>| {
+            data.resize(data.len() + 64, 0);
+            data
+        };
+        let mut proposal_bytes = padded(governance_data.try_to_vec().unwrap());
+        let mut config_data = config(0, 0);
+        config_data.guardian = guardian;
+        let mut config_bytes = padded(config_data.try_to_vec().unwrap());
+        let mut general_bytes = padded(GeneralData::default().try_to_vec().unwrap());
+        let mut clock_data = clock_bytes(unix_timestamp);
+        let (mut payer_lamports, mut clock_lamports, mut proposal_lamports) = (0, 0, 0);
+        let (mut config_lamports, mut general_lamports) = (0, 0);
+        let mut payer_data = vec![];
+        let accounts = [
+            AccountInfo::new(
+                &payer,
+                true,
+                true,
+                &mut payer_lamports,
+                &mut payer_data,
+                &system_program_id,
+                false,
+                0,
+            ),
+            account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data),
+            account(
+                &proposal_key,
+                &program_id,
+                &mut proposal_lamports,
+                &mut proposal_bytes,
+            ),
+            account(
+                &config_key,
+                &program_id,
+                &mut config_lamports,
+                &mut config_bytes,
+            ),
+            account(
+                &general_key,
+                &program_id,
+                &mut general_lamports,
+                &mut general_bytes,
+            ),
+        ];
+        let results = max_actions
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut config_data = ValidatorConfig::parse(&accounts[3], &program_id).unwrap();
+                edit_config(i, &mut config_data);
+                config_data
+                    .serialize(&mut &mut accounts[3].data.borrow_mut()[..])
+                    .unwrap();
+                execute_governance(&program_id, &accounts, 0, *x, 0).is_ok()
+            })
+            .collect();
+        (
+            results,
+            GovernanceData::parse(&accounts[2], &program_id).unwrap(),
+            ValidatorConfig::parse(&accounts[3], &program_id).unwrap(),
+        )
+    }
+
+    #[test]
+    pub fn config_actions_are_validated_together_once_executed() {
+        let leeway = GOVERNANCE_SAFETY_LEEWAY as i64;
+        // Each action alone leaves a redemption fee on an instance whose validator id can't be switched.
+        let actions = vec![
+            GovernanceType::ConfigAccount(ConfigAccountType::IsValidatorIdSwitchable(false)),
+            GovernanceType::ConfigAccount(ConfigAccountType::InitialRedemptionFee(0)),
+        ];
+        let (results, governance_data, config_data) =
+            execute_passed_proposal(actions.clone(), None, leeway, &[Some(1), None]);
+        // Config actions don't need extra accounts, so they can't be executed in chunks.
+        assert_eq!(results, vec![false, true]);
+        assert!(governance_data.is_proposal_executed);
+        assert!(!governance_data.is_execution_failed);
+        assert!(!config_data.is_validator_id_switchable);
+        assert_eq!(config_data.initial_redemption_fee, 0);
+
+        let (results, governance_data, config_data) =
+            execute_passed_proposal(actions[..1].to_vec(), None, leeway, &[None, None]);
+        // The invalid config fails the execution for good, leaving the config untouched.
+        assert_eq!(results, vec![true, false]);
+        assert!(!governance_data.is_proposal_executed);
+        assert!(governance_data.is_execution_failed);
+        assert!(config_data.is_validator_id_switchable);
+        assert_eq!(config_data.initial_redemption_fee, 10);
+
+        // A batch executed in chunks is validated as a whole before its first chunk runs,
+        // so its treasury spend never goes through.
+        let mut batch = vec![GovernanceType::TreasurySpend {
+            recipient: Pubkey::new_unique(),
+            lamports: LAMPORTS_PER_SOL,
+            memo: "audit".to_string(),
+        }];
+        batch.extend(actions[..1].to_vec());
+        let (results, governance_data, config_data) =
+            execute_passed_proposal(batch, None, leeway, &[Some(1), Some(1)]);
+        assert_eq!(results, vec![true, false]);
+        assert_eq!(governance_data.executed_actions, 0);
+        assert!(governance_data.is_execution_failed);
+        assert!(config_data.is_validator_id_switchable);
+
+        // A redemption fee set by another proposal between chunks makes the config invalid,
+        // failing the remaining actions so that the proposal can still be closed.
+        let batch = vec![
+            actions[0].clone(),
+            GovernanceType::TreasurySpend {
+                recipient: Pubkey::new_unique(),
+                lamports: LAMPORTS_PER_SOL,
+                memo: "audit".to_string(),
+            },
+        ];
+        let (results, governance_data, config_data) = execute_passed_proposal_with(
+            batch,
+            None,
+            leeway,
+            &[Some(1), Some(1), Some(1)],
+            |i, config_data| config_data.initial_redemption_fee = if i == 0 { 0 } else { 10 },
+        );
+        assert_eq!(results, vec![true, true, false]);
+        assert_eq!(governance_data.executed_actions, 1);
+        assert!(!governance_data.is_proposal_executed);
+        assert!(governance_data.is_execution_failed);
+        assert!(config_data.is_validator_id_switchable);
+    }
+
+    #[test]
+    pub fn proposals_wait_out_the_veto_leeway_once_a_guardian_is_set() {
+        let actions = vec![GovernanceType::ConfigAccount(
+            ConfigAccountType::ValidatorName("validator".to_string()),
+        )];
+        let leeway = GOVERNANCE_SAFETY_LEEWAY as i64;
+        // Without a guardian, only the actions deemed sensitive wait out the leeway.
+        let (results, _, config_data) =
+            execute_passed_proposal(actions.clone(), None, leeway - 1, &[None]);
+        assert_eq!(results, vec![true]);
+        assert_eq!(config_data.validator_name, "validator");
+
+        // With one, every action does, so that the guardian can veto any proposal before it takes effect.
+        let guardian = Some(Pubkey::new_unique());
+        let (results, governance_data, config_data) =
+            execute_passed_proposal(actions.clone(), guardian, leeway - 1, &[None]);
+        assert_eq!(results, vec![false]);
+        assert!(!governance_data.is_proposal_executed);
+        assert_eq!(config_data.validator_name, "");
+        let (results, _, config_data) = execute_passed_proposal(actions, guardian, leeway, &[None]);
+        assert_eq!(results, vec![true]);
+        assert_eq!(config_data.validator_name, "validator");
+    }
+
+    #[test]
+    pub fn vetoed_proposals_can_be_closed_once_retained() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let (guardian, proposer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let config_key = Pubkey::find_program_address(&[INGL_CONFIG_SEED], &program_id).0;
+        let proposal_key =
+            Pubkey::find_program_address(&[INGL_PROPOSAL_KEY, &0u32.to_be_bytes()], &program_id).0;
+        let clock_key = sysvar::clock::id();
+        let mut config_data = config(0, 0);
+        config_data.guardian = Some(guardian);
+        let mut governance_data = proposal(1, 1, 1);
+        governance_data.is_still_ongoing = false;
+        governance_data.date_finalized = Some(0);
+        governance_data.did_proposal_pass = Some(true);
+        governance_data.proposer = proposer;
+        let mut config_bytes = config_data.try_to_vec().unwrap();
+        let mut proposal_bytes = governance_data.try_to_vec().unwrap();
+        let mut clock_data = clock_bytes(GOVERNANCE_SAFETY_LEEWAY as i64);
+        let (mut guardian_lamports, mut proposer_lamports) = (0, 0);
+        let (mut config_lamports, mut proposal_lamports, mut clock_lamports) = (0, 1_000_000, 0);
+        let (mut guardian_data, mut proposer_data) = (vec![], vec![]);
+        let guardian_info = AccountInfo::new(
+            &guardian,
+            true,
+            true,
+            &mut guardian_lamports,
+            &mut guardian_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let proposer_info = account(
+            &proposer,
+            &system_program_id,
+            &mut proposer_lamports,
+            &mut proposer_data,
+        );
+        let config_info = account(
+            &config_key,
+            &program_id,
+            &mut config_lamports,
+            &mut config_bytes,
+        );
+        let proposal_info = account(
+            &proposal_key,
+            &program_id,
+            &mut proposal_lamports,
+            &mut proposal_bytes,
+        );
+        let clock_info = account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data);
+        let set_clock = |unix_timestamp: i64| {
+            clock_info
+                .data
+                .borrow_mut()
+                .copy_from_slice(&clock_bytes(unix_timestamp))
+        };
+        let veto = || {
+            veto_proposal(
+                &program_id,
+                &[
+                    guardian_info.clone(),
+                    config_info.clone(),
+                    proposal_info.clone(),
+                    clock_info.clone(),
+                ],
+                0,
+                0,
+                true,
+            )
+        };
+        let close = || {
+            close_proposal(
+                &program_id,
+                &[
+                    guardian_info.clone(),
+                    proposal_info.clone(),
+                    proposer_info.clone(),
+                    clock_info.clone(),
+                ],
+                0,
+                0,
+                true,
+            )
+        };
+
+        // The guardian can only veto until the governance safety leeway elapses.
+        assert!(veto().is_err());
+        set_clock(GOVERNANCE_SAFETY_LEEWAY as i64 - 1);
+        veto().unwrap();
+        assert!(
+            GovernanceData::parse(&proposal_info, &program_id)
+                .unwrap()
+                .is_vetoed
+        );
+
+        // The vetoed proposal, though passed, is closed once its retention period is over.
+        assert!(close().is_err());
+        set_clock(PROPOSAL_RETENTION_PERIOD as i64);
+        close().unwrap();
+        assert_eq!(proposer_info.lamports(), 1_000_000);
+        assert!(proposal_info.data.borrow().iter().all(|x| *x == 0));
+    }
+
+    #[test]
+    pub fn paused_instances_still_redeem_nfts() {
+        let program_id = Pubkey::new_unique();
+        let spl_token_id = spl_token::id();
+        let system_program_id = system_program::id();
+        let sysvar_id = sysvar::id();
+        let vote_program_id = solana_program::vote::program::id();
+        let mpl_token_metadata_id = mpl_token_metadata::id();
+        let (payer, mint, vote_account) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &program_id).0;
+        let metadata = |mint: &Pubkey, suffix: &[u8]| {
+            Pubkey::find_program_address(
+                &[
+                    PREFIX.as_bytes(),
+                    mpl_token_metadata_id.as_ref(),
+                    mint.as_ref(),
+                    suffix,
+                ],
+                &mpl_token_metadata_id,
+            )
+            .0
+        };
+        let (config_key, general_key, pd_pool_key) = (
+            find(&[INGL_CONFIG_SEED]),
+            find(&[GENERAL_ACCOUNT_SEED]),
+            find(&[PD_POOL_ACCOUNT_KEY]),
+        );
+        let (nft_key, mint_authority_key) = (
+            find(&[NFT_ACCOUNT_CONST, mint.as_ref()]),
+            find(&[INGL_MINT_AUTHORITY_KEY]),
+        );
+        let (metadata_key, edition_key, collection_metadata_key) = (
+            metadata(&mint, b""),
+            metadata(&mint, b"edition"),
+            metadata(&find(&[INGL_NFT_COLLECTION_KEY]), b""),
+        );
+        let token_account_key = get_associated_token_address(&payer, &mint);
+        let clock_key = sysvar::clock::id();
+
+        let mut config_data = config(0, 0);
+        config_data.is_paused = true;
+        config_data.vote_account = vote_account;
+        assert!(config_data.assert_not_paused().is_err());
+        let mut config_bytes = config_data.try_to_vec().unwrap();
+        let mut general_bytes = GeneralData::default().try_to_vec().unwrap();
+        let mut nft_bytes = unimprinted_nft(0, None).try_to_vec().unwrap();
+        let mut token_account_bytes = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint,
+                owner: payer,
+                amount: 1,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            },
+            &mut token_account_bytes,
+        )
+        .unwrap();
+        // Past the redemption fee duration, so the whole unit backing is returned.
+        let mut clock_data = clock_bytes(config_data.redemption_fee_duration as i64);
+        let (mut payer_lamports, mut mint_lamports, mut pd_pool_lamports) = (0, 0, 0);
+        let (mut token_account_lamports, mut nft_lamports, mut metadata_lamports) = (0, 5000, 0);
+        let (mut edition_lamports, mut collection_metadata_lamports) = (0, 0);
+        let (mut spl_token_lamports, mut config_lamports, mut general_lamports) = (0, 0, 0);
+        let (mut vote_lamports, mut mint_authority_lamports, mut clock_lamports) = (0, 0, 0);
+        let (mut payer_data, mut mint_data, mut pd_pool_data) = (vec![], vec![], vec![]);
+        let (mut metadata_data, mut edition_data) = (vec![], vec![]);
+        let (mut collection_metadata_data, mut spl_token_data) = (vec![], vec![]);
+        let (mut vote_data, mut mint_authority_data) = (vec![], vec![]);
+        let payer_info = AccountInfo::new(
+            &payer,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let nft_info = account(&nft_key, &program_id, &mut nft_lamports, &mut nft_bytes);
+
+        redeem_nft(
+            &program_id,
+            &[
+                payer_info.clone(),
+                account(&mint, &spl_token_id, &mut mint_lamports, &mut mint_data),
+                account(
+                    &pd_pool_key,
+                    &program_id,
+                    &mut pd_pool_lamports,
+                    &mut pd_pool_data,
+                ),
+                account(
+                    &token_account_key,
+                    &spl_token_id,
+                    &mut token_account_lamports,
+                    &mut token_account_bytes,
+                ),
+                nft_info.clone(),
+                account(
+                    &metadata_key,
+                    &mpl_token_metadata_id,
+                    &mut metadata_lamports,
+                    &mut metadata_data,
+                ),
+                account(
+                    &edition_key,
+                    &mpl_token_metadata_id,
+                    &mut edition_lamports,
+                    &mut edition_data,
+                ),
+                account(
+                    &collection_metadata_key,
+                    &mpl_token_metadata_id,
+                    &mut collection_metadata_lamports,
+                    &mut collection_metadata_data,
+                ),
+                account(
+                    &spl_token_id,
+                    &system_program_id,
+                    &mut spl_token_lamports,
+                    &mut spl_token_data,
+                ),
+                account(
+                    &config_key,
+                    &program_id,
+                    &mut config_lamports,
+                    &mut config_bytes,
+                ),
+                account(
+                    &general_key,
+                    &program_id,
+                    &mut general_lamports,
+                    &mut general_bytes,
+                ),
+                account(
+                    &vote_account,
+                    &vote_program_id,
+                    &mut vote_lamports,
+                    &mut vote_data,
+                ),
+                account(
+                    &mint_authority_key,
+                    &program_id,
+                    &mut mint_authority_lamports,
+                    &mut mint_authority_data,
+                ),
+                account(&clock_key, &sysvar_id, &mut clock_lamports, &mut clock_data),
+            ],
+            0,
+            true,
+        )
+        .unwrap();
+        // The gem account is closed to the redeemer.
+        assert_eq!(payer_info.lamports(), 5000);
+        assert_eq!(nft_info.lamports(), 0);
+        assert!(nft_info.data.borrow().iter().all(|x| *x == 0));
+    }
 }
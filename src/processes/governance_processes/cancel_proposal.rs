@@ -0,0 +1,94 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::init_processes::reset_uris_page::refund_lamports,
+    state::{constants::*, GeneralData, GovernanceData},
+    utils::{get_clock_data, AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Cancels a proposal no NFT has voted on yet, refunding its deposit to the proposer. Only proposals that haven't expired can be cancelled.
+pub fn cancel_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proposal_numeration: u32,
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Cancel proposal ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    proposal_account_info
+        .assert_seed(
+            program_id,
+            &[INGL_PROPOSAL_KEY, &proposal_numeration.to_be_bytes()],
+        )
+        .error_log("failed to assert_pda_input for proposal_account_info")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("failed to assert_pda_input for general_account_info")?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let mut governance_data = Box::new(GovernanceData::parse(proposal_account_info, program_id)?);
+    let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+
+    payer_account_info
+        .assert_key_match(&governance_data.proposer)
+        .error_log("Error: Payer account is not the proposer")?;
+    if !governance_data.is_still_ongoing {
+        Err(InglError::TooLate.utilize("This proposal is currently Closed"))?
+    }
+    // An expired proposal nobody supported is finalized, slashing its deposit to the vote account.
+    if clock_data.unix_timestamp as u32 >= governance_data.expiration_time {
+        Err(InglError::TooLate.utilize("Expired proposals can't be cancelled"))?
+    }
+    if !governance_data.votes.is_empty() {
+        Err(InglError::TooLate.utilize("Proposals can't be cancelled once voted on"))?
+    }
+    if !general_data
+        .unfinalized_proposals
+        .remove(&proposal_numeration)
+    {
+        Err(InglError::InvalidData
+            .utilize("Could not find the proposal in the unfinalized_proposals"))?
+    }
+
+    log!(
+        log_level,
+        2,
+        "Refunding the {} lamports deposit ...",
+        governance_data.deposit
+    );
+    refund_lamports(
+        proposal_account_info,
+        payer_account_info,
+        governance_data.deposit,
+    )?;
+    governance_data.deposit = 0;
+    governance_data.is_cancelled = true;
+    governance_data.is_still_ongoing = false;
+    governance_data.did_proposal_pass = Some(false);
+    governance_data.date_finalized = Some(clock_data.unix_timestamp as u32);
+
+    governance_data
+        .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into proposal_account_info")?;
+    general_data
+        .serialize(&mut &mut general_account_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into general_account_info")?;
+    log!(log_level, 4, "Proposal cancelled !!!");
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use crate::{
+    error::InglError,
+    log,
+    state::{constants::*, FundsLocation, GovernanceData, GovernanceEvent, NftData},
+    utils::{get_clock_data, verify_voting_authority, AccountInfoHelpers, OptionExt, ResultExt},
+};
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Changes the votes of NFTs that already voted on an ongoing proposal. Both vote maps keep their size.
+pub fn change_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    numeration: u32,
+    vote: bool,
+    cnt: u8,
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Change vote ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    proposal_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Proposal account is not owned by the program")?;
+    proposal_account_info
+        .assert_seed(program_id, &[INGL_PROPOSAL_KEY, &numeration.to_be_bytes()])
+        .error_log("failed to assert pda input for proposal_account_info")?;
+
+    let mut governance_data = Box::new(GovernanceData::parse(proposal_account_info, program_id)?);
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    if !governance_data.is_still_ongoing {
+        Err(InglError::TooLate.utilize("This proposal is currently Closed"))?
+    }
+    if governance_data.expiration_time < clock_data.unix_timestamp as u32 {
+        Err(InglError::TooLate.utilize("This proposal is currently Expired"))?
+    }
+
+    for _ in 0..cnt {
+        let nft_account_data_info = next_account_info(account_info_iter)?;
+        let mint_account_info = next_account_info(account_info_iter)?;
+        let associated_token_account_info = next_account_info(account_info_iter)?;
+
+        verify_voting_authority(
+            payer_account_info,
+            mint_account_info,
+            nft_account_data_info,
+            associated_token_account_info,
+            account_info_iter,
+            program_id,
+        )?;
+
+        let mut nft_data = Box::new(NftData::parse(nft_account_data_info, program_id)?);
+        match nft_data.funds_location {
+            FundsLocation::Delegated => (),
+            _ => Err(InglError::InvalidFundsLocation.utilize("Funds location is not delegated"))?,
+        }
+
+        *governance_data
+            .votes
+            .get_mut(&nft_data.numeration)
+            .error_log("This NFT has not voted on the proposal")? = vote;
+        *nft_data
+            .all_votes
+            .get_mut(&numeration)
+            .error_log("vote to change not found in nft data")? = vote;
+
+        nft_data
+            .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+            .error_log("failed to serialize into nft_account_info")?;
+        GovernanceEvent::VoteChanged {
+            proposal: numeration,
+            nft: nft_data.numeration,
+            vote,
+        }
+        .emit()?;
+    }
+
+    governance_data
+        .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into proposal_account_info")?;
+    log!(log_level, 4, "Done with Change vote !!!");
+    Ok(())
+}
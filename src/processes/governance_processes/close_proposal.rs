@@ -0,0 +1,72 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::init_processes::reset_uris_page::refund_lamports,
+    state::{constants::*, GovernanceData},
+    utils::{get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Closes an executed, vetoed or failed proposal once its retention period is over, returning its rent to the proposer.
+///The votes of closed proposals can then be pruned from the NFTs with PruneVotes.
+pub fn close_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proposal_numeration: u32,
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Close proposal ...");
+    let account_info_iter = &mut accounts.iter();
+    let _payer_account_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+    let proposer_account_info = next_account_info(account_info_iter)?;
+
+    proposal_account_info
+        .assert_seed(
+            program_id,
+            &[INGL_PROPOSAL_KEY, &proposal_numeration.to_be_bytes()],
+        )
+        .error_log("failed to assert_pda_input for proposal_account_info")?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let governance_data = Box::new(GovernanceData::parse(proposal_account_info, program_id)?);
+
+    proposer_account_info
+        .assert_key_match(&governance_data.proposer)
+        .error_log("Error @ Proposer address verification")?;
+    if governance_data.is_still_ongoing {
+        Err(InglError::TooEarly.utilize("This proposal is currently still ongoing."))?
+    }
+    if governance_data.did_proposal_pass == Some(true)
+        && !governance_data.is_proposal_executed
+        && !governance_data.is_execution_failed
+        && !governance_data.is_vetoed
+    {
+        Err(InglError::TooEarly
+            .utilize("Passed proposals can only be closed once executed, failed or vetoed."))?
+    }
+    if (clock_data.unix_timestamp as u32)
+        < governance_data
+            .date_finalized
+            .error_log("Proposal must be finalized")?
+            + PROPOSAL_RETENTION_PERIOD
+    {
+        Err(InglError::TooEarly.utilize("The proposal's retention period is not over yet."))?
+    }
+
+    log!(log_level, 2, "Closing the proposal account ...");
+    refund_lamports(
+        proposal_account_info,
+        proposer_account_info,
+        proposal_account_info.lamports(),
+    )?;
+    proposal_account_info.data.borrow_mut().fill(0);
+    log!(log_level, 4, "Proposal closed !!!");
+    Ok(())
+}
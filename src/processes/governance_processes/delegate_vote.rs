@@ -0,0 +1,77 @@
+use crate::{
+    log,
+    state::{constants::*, VoteDelegation},
+    utils::{
+        create_pda_account, get_rent_data, verify_nft_ownership, AccountInfoHelpers, ResultExt,
+    },
+};
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Delegates the governance voting power of an NFT to another wallet, replacing any previous delegation.
+pub fn delegate_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegate: Pubkey,
+    log_level: u8,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Delegate vote ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let vote_delegation_account_info = next_account_info(account_info_iter)?;
+
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+    let (_vote_delegation_key, vote_delegation_bump) = vote_delegation_account_info
+        .assert_seed(
+            program_id,
+            &[VOTE_DELEGATION_KEY, mint_account_info.key.as_ref()],
+        )
+        .error_log("failed to assert pda input for vote_delegation_account_info")?;
+
+    if vote_delegation_account_info.data_is_empty() {
+        log!(log_level, 2, "Creating the vote delegation account ...");
+        create_pda_account(
+            program_id,
+            payer_account_info,
+            None,
+            vote_delegation_account_info,
+            &[
+                VOTE_DELEGATION_KEY,
+                mint_account_info.key.as_ref(),
+                &[vote_delegation_bump],
+            ],
+            VoteDelegation::get_space(),
+            &rent_data,
+        )
+        .error_log("failed to create vote_delegation_account_info")?;
+    } else {
+        VoteDelegation::parse(vote_delegation_account_info, program_id)?;
+    }
+
+    VoteDelegation {
+        validation_phrase: VOTE_DELEGATION_VAL_PHRASE,
+        mint: *mint_account_info.key,
+        owner: *payer_account_info.key,
+        delegate,
+    }
+    .serialize(&mut &mut vote_delegation_account_info.data.borrow_mut()[..])
+    .error_log("failed to serialize into vote_delegation_account_info")?;
+    log!(log_level, 4, "Votes delegated to {} !!!", delegate);
+    Ok(())
+}
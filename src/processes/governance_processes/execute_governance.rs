@@ -3,9 +3,14 @@ use std::slice::Iter;
 use crate::{
     error::InglError,
     log,
+    processes::init_processes::{
+        add_uploader::{load_uploaders_account, save_uploaders_account},
+        upload_uris_page::grow_account,
+    },
     state::{
         constants::*, ConfigAccountType, GeneralData, GovernanceData, GovernanceType,
-        ValidatorConfig, VoteAccountGovernance,
+        UploaderGovernance, UrisAccount, UrisGovernance, UrisPage, ValidatorConfig,
+        VoteAccountGovernance,
     },
     utils::{AccountInfoHelpers, OptionExt, ResultExt},
 };
@@ -19,6 +24,8 @@ use solana_program::{
     entrypoint::ProgramResult,
     program::invoke_signed,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
     sysvar::{self, Sysvar},
     vote::{instruction::{authorize, update_commission, update_validator_identity}, state::VoteAuthorize},
 };
@@ -27,15 +34,12 @@ pub fn execute_governance(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     proposal_numeration: u32,
+    max_actions: Option<u8>,
     log_level: u8,
 ) -> ProgramResult {
-    log!(
-        log_level,
-        4,
-        "Initiating Finalize program upgrade proposal ..."
-    );
+    log!(log_level, 4, "Initiating Execute governance ...");
     let account_info_iter = &mut accounts.iter();
-    let _payer_account_info = next_account_info(account_info_iter)?;
+    let payer_account_info = next_account_info(account_info_iter)?;
     let sysvar_clock_info = next_account_info(account_info_iter)?;
     let proposal_account_info = next_account_info(account_info_iter)?;
     let ingl_config_account = next_account_info(account_info_iter)?;
@@ -78,6 +82,12 @@ pub fn execute_governance(
     if governance_data.is_proposal_executed == true {
         Err(InglError::TooLate.utilize("This proposal has already been executed."))?
     }
+    if governance_data.is_vetoed {
+        Err(InglError::TooLate.utilize("This proposal was vetoed by the guardian."))?
+    }
+    if governance_data.is_execution_failed {
+        Err(InglError::TooLate.utilize("The execution of this proposal failed."))?
+    }
     if (clock_data.unix_timestamp as u32)
         < (governance_data
             .clone()
@@ -85,20 +95,35 @@ pub fn execute_governance(
             .error_log("Proposal must be finalized")?
             + GOVERNANCE_SAFETY_LEEWAY)
     {
-        match governance_data.clone().governance_type {
-            GovernanceType::ProgramUpgrade { .. } => {
-                Err(InglError::TooEarly.utilize("This proposal is not ready to be executed yet"))?
-            }
-            GovernanceType::ConfigAccount(config_governance_type) => {
-                match config_governance_type {
-                    ConfigAccountType::InitialRedemptionFee(_) => Err(InglError::TooEarly
-                        .utilize("This proposal is not ready to be executed yet"))?,
-                    ConfigAccountType::RedemptionFeeDuration(_) => Err(InglError::TooEarly
-                        .utilize("This proposal is not ready to be executed yet"))?,
-                    _ => (),
+        // Proposals can be vetoed until the leeway elapses, so none takes effect before then once a guardian is set.
+        if config_data.guardian.is_some() {
+            Err(InglError::TooEarly.utilize("This proposal can still be vetoed by the guardian"))?
+        }
+        for action in governance_data.actions.iter() {
+            match action {
+                GovernanceType::ProgramUpgrade { .. } => {
+                    Err(InglError::TooEarly
+                        .utilize("This proposal is not ready to be executed yet"))?
+                }
+                GovernanceType::ConfigAccount(config_governance_type) => {
+                    match config_governance_type {
+                        ConfigAccountType::InitialRedemptionFee(_) => Err(InglError::TooEarly
+                            .utilize("This proposal is not ready to be executed yet"))?,
+                        ConfigAccountType::RedemptionFeeDuration(_) => Err(InglError::TooEarly
+                            .utilize("This proposal is not ready to be executed yet"))?,
+                        ConfigAccountType::Guardian(_) | ConfigAccountType::IsPaused(_) => {
+                            Err(InglError::TooEarly
+                                .utilize("This proposal is not ready to be executed yet"))?
+                        }
+                        _ => (),
+                    }
+                }
+                GovernanceType::TreasurySpend { .. } => {
+                    Err(InglError::TooEarly
+                        .utilize("This proposal is not ready to be executed yet"))?
                 }
+                _ => (),
             }
-            _ => (),
         }
     }
 
@@ -111,61 +136,115 @@ pub fn execute_governance(
         None => Err(InglError::TooEarly.utilize("This proposal is currently still ongoing."))?,
     }
 
-    match governance_data.clone().governance_type {
-        GovernanceType::ConfigAccount(config_governance_type) => {
-            match config_governance_type {
-                ConfigAccountType::MaxPrimaryStake(x) => {
-                    config_data.max_primary_stake = x;
-                }
-                ConfigAccountType::NftHolderShare(x) => {
-                    config_data.nft_holders_share = x;
-                }
-                ConfigAccountType::InitialRedemptionFee(x) => {
-                    config_data.initial_redemption_fee = x;
-                }
-                ConfigAccountType::RedemptionFeeDuration(x) => {
-                    config_data.redemption_fee_duration = x;
-                }
-                ConfigAccountType::ValidatorName(x) => {
-                    config_data.validator_name = x;
-                }
-                ConfigAccountType::TwitterHandle(x) => {
-                    config_data.twitter_handle = x;
-                }
-                ConfigAccountType::DiscordInvite(x) => {
-                    config_data.discord_invite = x;
-                }
+    // All actions are executed at once, unless max_actions splits those needing more accounts than fit in a transaction.
+    let first_action = governance_data.executed_actions as usize;
+    let last_action = match max_actions {
+        None => governance_data.actions.len(),
+        Some(0) => Err(InglError::InvalidData.utilize("At least one action must be executed"))?,
+        Some(x) => {
+            if !governance_data
+                .actions
+                .iter()
+                .any(|action| action.needs_extra_accounts())
+            {
+                Err(InglError::InvalidData
+                    .utilize("Only actions needing extra accounts can be executed in chunks"))?
             }
-            config_data
-                .validate_data()
-                .error_log("Invalid data in config account")?;
+            governance_data.actions.len().min(first_action + x as usize)
         }
-        GovernanceType::ProgramUpgrade {
-            buffer_account,
-            code_link: _,
-        } => handle_program_upgrade(program_id, account_info_iter, buffer_account, log_level)?,
-        GovernanceType::VoteAccountGovernance(vote_account_governance_type) => {
-            handle_vote_account_governance_change(
-                program_id,
-                account_info_iter,
-                vote_account_governance_type.clone(),
-                log_level,
-            )?;
-            match vote_account_governance_type {
-                VoteAccountGovernance::Commission(x) => {
-                    config_data.commission = x;
-                }
-                VoteAccountGovernance::ValidatorID(x) => {
-                    config_data.validator_id = x;
+    };
+
+    // Config actions all take effect with the last action, so the config resulting from the whole batch
+    // is validated before its first chunk runs, and an invalid one fails the execution before any action takes effect.
+    // Later chunks validate it again, as the config may have changed since, failing the execution of the remaining actions.
+    let resulting_config =
+        get_resulting_config(&config_data, &governance_data.actions, first_action);
+    if let Err(error) = resulting_config.validate_data() {
+        log!(
+            log_level,
+            4,
+            "Invalid resulting config data: {:?}. Marking the proposal's execution as failed ...",
+            error
+        );
+        governance_data.is_execution_failed = true;
+        governance_data
+            .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
+            .error_log("failed to serialize into proposal_account_info")?;
+        return Ok(());
+    }
+
+    log!(
+        log_level,
+        2,
+        "Executing actions {} to {} of {} ...",
+        first_action,
+        last_action,
+        governance_data.actions.len()
+    );
+    for action in governance_data.actions[first_action..last_action]
+        .iter()
+        .cloned()
+    {
+        match action {
+            GovernanceType::ConfigAccount(_) => (),
+            GovernanceType::ProgramUpgrade {
+                buffer_account,
+                code_link: _,
+            } => {
+                config_data.assert_not_paused()?;
+                handle_program_upgrade(program_id, account_info_iter, buffer_account, log_level)?
+            }
+            GovernanceType::VoteAccountGovernance(vote_account_governance_type) => {
+                handle_vote_account_governance_change(
+                    program_id,
+                    account_info_iter,
+                    vote_account_governance_type.clone(),
+                    log_level,
+                )?;
+                if let VoteAccountGovernance::ValidatorID(_) = vote_account_governance_type {
                     if general_data.last_validated_validator_id_proposal > proposal_numeration {
                         Err(InglError::TooLate.utilize("The Time to Execute Proposal has passed."))?
                     }
                 }
+                vote_account_governance_type.apply(&mut config_data);
+            }
+            GovernanceType::UploaderGovernance(uploader_governance_type) => {
+                handle_uploader_governance_change(
+                    program_id,
+                    payer_account_info,
+                    account_info_iter,
+                    uploader_governance_type,
+                    log_level,
+                )?;
+            }
+            GovernanceType::UrisGovernance(uris_governance_type) => {
+                handle_uris_governance_change(
+                    program_id,
+                    payer_account_info,
+                    account_info_iter,
+                    uris_governance_type,
+                    log_level,
+                )?;
             }
+            GovernanceType::TreasurySpend {
+                recipient,
+                lamports,
+                memo,
+            } => handle_treasury_spend(
+                program_id,
+                account_info_iter,
+                recipient,
+                lamports,
+                memo,
+                log_level,
+            )?,
         }
     }
-
-    governance_data.is_proposal_executed = true;
+    governance_data.executed_actions = last_action as u8;
+    governance_data.is_proposal_executed = last_action == governance_data.actions.len();
+    if governance_data.is_proposal_executed {
+        *config_data = resulting_config;
+    }
     log!(log_level, 0, "serialization only left");
     governance_data
         .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
@@ -173,10 +252,30 @@ pub fn execute_governance(
     config_data
         .serialize(&mut &mut ingl_config_account.data.borrow_mut()[..])
         .error_log("failed to serialize into ingl_config_account")?;
-    log!(log_level, 4, "Done with upgrade proposal finalization !!!");
+    log!(log_level, 4, "Done executing governance !!!");
     Ok(())
 }
 
+/// Returns the config once every action from first_action on is executed.
+/// Config actions are included whatever first_action is, as they only take effect with the last action.
+pub fn get_resulting_config(
+    config_data: &ValidatorConfig,
+    actions: &[GovernanceType],
+    first_action: usize,
+) -> ValidatorConfig {
+    let mut resulting_config = config_data.clone();
+    for (i, action) in actions.iter().cloned().enumerate() {
+        match action {
+            GovernanceType::ConfigAccount(x) => x.apply(&mut resulting_config),
+            GovernanceType::VoteAccountGovernance(x) if i >= first_action => {
+                x.apply(&mut resulting_config)
+            }
+            _ => (),
+        }
+    }
+    resulting_config
+}
+
 pub fn handle_program_upgrade(
     program_id: &Pubkey,
     account_info_iter: &mut Iter<AccountInfo>,
@@ -349,3 +448,186 @@ pub fn handle_vote_account_governance_change(
     }
     Ok(())
 }
+
+pub fn handle_uploader_governance_change<'a>(
+    program_id: &Pubkey,
+    payer_account_info: &AccountInfo<'a>,
+    account_info_iter: &mut Iter<AccountInfo<'a>>,
+    governance_type: UploaderGovernance,
+    log_level: u8,
+) -> ProgramResult {
+    let uploaders_account_info = next_account_info(account_info_iter)?;
+
+    let mut uploaders_data = load_uploaders_account(program_id, uploaders_account_info)?;
+    match governance_type {
+        UploaderGovernance::AddUploader(uploader) => {
+            log!(log_level, 2, "Authorizing uploader {} ...", uploader.key);
+            uploaders_data.add_uploader(uploader)?;
+        }
+        UploaderGovernance::RemoveUploader(uploader) => {
+            log!(log_level, 2, "Removing uploader {} ...", uploader);
+            uploaders_data.remove_uploader(&uploader)?;
+        }
+    }
+    save_uploaders_account(
+        program_id,
+        payer_account_info,
+        uploaders_account_info,
+        &uploaders_data,
+    )?;
+    log!(log_level, 2, "Uploaders updated !!!");
+    Ok(())
+}
+
+pub fn handle_uris_governance_change<'a>(
+    program_id: &Pubkey,
+    payer_account_info: &AccountInfo<'a>,
+    account_info_iter: &mut Iter<AccountInfo<'a>>,
+    governance_type: UrisGovernance,
+    log_level: u8,
+) -> ProgramResult {
+    let uris_account_info = next_account_info(account_info_iter)?;
+
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("failed to assert_pda_input for uris_account_info")?;
+    let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    let rarity_count = uris_account_data.rarities.len();
+    match governance_type {
+        UrisGovernance::ReplaceUris { rarity, uris } => {
+            log!(log_level, 2, "Replacing the uris of rarity {} ...", rarity);
+            *uris_account_data
+                .uris
+                .get_mut(rarity as usize)
+                .error_log("Error: rarity has no uris")? = uris;
+        }
+        UrisGovernance::SetBaseUri(base_uri) => {
+            log!(log_level, 2, "Setting the base uri to {} ...", base_uri);
+            uris_account_data.base_uri = base_uri;
+        }
+        UrisGovernance::CommitUrisRoot { rarity, uris_root } => {
+            if rarity as usize >= rarity_count {
+                Err(InglError::InvalidUrisAccountData.utilize("Rarity is out of bounds"))?
+            }
+            log!(
+                log_level,
+                2,
+                "Committing the uris root of rarity {} ...",
+                rarity
+            );
+            uris_account_data.uri_roots.resize(rarity_count, None);
+            uris_account_data.uri_roots[rarity as usize] = uris_root;
+        }
+        UrisGovernance::ReplaceUrisPage { rarity, page, uris } => {
+            if page >= uris_account_data.get_page_count(rarity) {
+                Err(InglError::InvalidUrisAccountData.utilize("Page is out of bounds"))?
+            }
+            log!(
+                log_level,
+                2,
+                "Replacing page {} of rarity {} ...",
+                page,
+                rarity
+            );
+            let uris_page_account_info = next_account_info(account_info_iter)?;
+            uris_page_account_info
+                .assert_seed(program_id, &[URIS_PAGE_KEY, &[rarity], &page.to_be_bytes()])
+                .error_log("failed to assert_pda_input for uris_page_account_info")?;
+            let mut uris_page = UrisPage::parse(uris_page_account_info, program_id)?;
+            uris_page.uris = uris;
+            let space = uris_page.get_space();
+            if space > MAX_URIS_PAGE_SPACE {
+                Err(InglError::UrisAccountTooBig.utilize("Too many images for a single page"))?
+            }
+            if space > uris_page_account_info.data_len() {
+                grow_account(payer_account_info, uris_page_account_info, space)?;
+            }
+            uris_page
+                .serialize(&mut &mut uris_page_account_info.data.borrow_mut()[..])
+                .error_log("failed to serialize into uris_page_account_info")?;
+        }
+        UrisGovernance::SetRarities {
+            rarities,
+            rarity_names,
+        } => {
+            if rarities.len() != rarity_count {
+                Err(InglError::InvalidUrisAccountData
+                    .utilize("The number of rarities can't change once frozen"))?
+            }
+            log!(log_level, 2, "Setting the rarities to {:?} ...", rarities);
+            uris_account_data.rarities = UrisAccount::get_cumulated_rarities(&rarities)?;
+            uris_account_data.rarity_names = rarity_names;
+        }
+        UrisGovernance::SetRewardWeights(reward_weights) => {
+            log!(
+                log_level,
+                2,
+                "Setting the reward weights to {:?} ...",
+                reward_weights
+            );
+            uris_account_data.reward_weights = reward_weights;
+        }
+    }
+    uris_account_data
+        .validate_data()
+        .error_log("Invalid data in uris account")?;
+
+    let space = uris_account_data.get_space();
+    if space > uris_account_info.data_len() {
+        grow_account(payer_account_info, uris_account_info, space)?;
+    }
+    uris_account_data
+        .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into uris_account_info")?;
+    log!(log_level, 2, "Uris updated !!!");
+    Ok(())
+}
+
+pub fn handle_treasury_spend(
+    program_id: &Pubkey,
+    account_info_iter: &mut Iter<AccountInfo>,
+    recipient: Pubkey,
+    lamports: u64,
+    memo: String,
+    log_level: u8,
+) -> ProgramResult {
+    let treasury_account_info = next_account_info(account_info_iter)?;
+    let recipient_account_info = next_account_info(account_info_iter)?;
+
+    let (_treasury_id, treasury_bump) = treasury_account_info
+        .assert_seed(program_id, &[TREASURY_KEY])
+        .error_log("failed to assert_pda_input for treasury_account_info")?;
+    recipient_account_info
+        .assert_key_match(&recipient)
+        .error_log("Error @ Recipient address verification")?;
+
+    // The treasury keeps its rent exempt minimum, and a single proposal can only spend a share of the rest.
+    let rent_data = Rent::get()?;
+    let available_lamports = treasury_account_info
+        .lamports()
+        .saturating_sub(rent_data.minimum_balance(0));
+    let spend_cap = (available_lamports as u128 * MAX_TREASURY_SPEND_SHARE as u128 / 100) as u64;
+    if lamports > spend_cap {
+        Err(InglError::BeyondBounds
+            .utilize("Treasury spend exceeds MAX_TREASURY_SPEND_SHARE of the treasury"))?
+    }
+
+    log!(
+        log_level,
+        2,
+        "Spending {} lamports from the treasury: {}",
+        lamports,
+        memo
+    );
+    invoke_signed(
+        &system_instruction::transfer(treasury_account_info.key, &recipient, lamports),
+        &[
+            treasury_account_info.clone(),
+            recipient_account_info.clone(),
+        ],
+        &[&[TREASURY_KEY, &[treasury_bump]]],
+    )
+    .error_log("failed to transfer funds from treasury_account_info to recipient_account_info")?;
+    log!(log_level, 2, "Treasury spend transferred !!!");
+    Ok(())
+}
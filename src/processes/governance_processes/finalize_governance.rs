@@ -1,11 +1,12 @@
 use crate::{
     error::InglError,
     log,
+    processes::init_processes::reset_uris_page::refund_lamports,
     state::{
         constants::*, GeneralData, GovernanceData, GovernanceType, ValidatorConfig,
         VoteAccountGovernance,
     },
-    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+    utils::{AccountInfoHelpers, ResultExt},
 };
 
 use borsh::BorshSerialize;
@@ -37,6 +38,8 @@ pub fn finalize_governance(
     let proposal_account_info = next_account_info(account_info_iter)?;
     let ingl_config_account = next_account_info(account_info_iter)?;
     let general_account_info = next_account_info(account_info_iter)?;
+    let proposer_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
 
     log!(log_level, 0, "Done with account collection");
 
@@ -70,6 +73,12 @@ pub fn finalize_governance(
 
     let mut governance_data = Box::new(GovernanceData::parse(proposal_account_info, program_id)?);
     let config_data = Box::new(ValidatorConfig::parse(ingl_config_account, program_id)?);
+    proposer_account_info
+        .assert_key_match(&governance_data.proposer)
+        .error_log("Error @ Proposer address verification")?;
+    vote_account_info
+        .assert_key_match(&config_data.vote_account)
+        .error_log("Error @ Vote account address verification")?;
     let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
     if !general_data
         .unfinalized_proposals
@@ -83,54 +92,53 @@ pub fn finalize_governance(
         Err(InglError::TooLate.utilize("This proposal is currently Closed"))?
     }
     log!(log_level, 0, "Done with account validations ...");
-    let total_votes_expected = config_data
-        .max_primary_stake
-        .checked_div(config_data.unit_backing)
-        .error_log("failed to calculate total_votes_expected")?
-        as u32;
-    let mut number_of_votes: u32 = 0;
-    let mut total_no_votes: u32 = 0;
-    for vote in governance_data.votes.values() {
-        if !*vote {
-            total_no_votes += 1;
-        }
-        number_of_votes += 1;
-    }
-
-    if number_of_votes
-        <= (config_data.proposal_quorum as u32)
-            .checked_mul(total_votes_expected)
-            .error_log("Error at quorum mult")?
-            .checked_div(100)
-            .error_log("Error Calculating quorum percentage")?
-    {
+    let has_reached_quorum = governance_data.has_reached_quorum();
+    let is_expired = governance_data.expiration_time < clock_data.unix_timestamp as u32;
+    if !has_reached_quorum && !is_expired {
         Err(InglError::NotEnoughVotes.utilize(""))?
     }
 
-    if (total_no_votes * 100)
-        .checked_div(number_of_votes)
-        .error_log("Error Calculating Dissaproval Percentage")?
-        > 20
-        || (governance_data.expiration_time < clock_data.unix_timestamp as u32)
-    {
+    // The deposit is refunded to the proposer if the proposal reached quorum, and paid to the vote account otherwise.
+    let deposit_recipient_info = if has_reached_quorum {
+        proposer_account_info
+    } else {
+        vote_account_info
+    };
+    log!(
+        log_level,
+        2,
+        "Settling the {} lamports deposit to {}",
+        governance_data.deposit,
+        deposit_recipient_info.key
+    );
+    refund_lamports(
+        proposal_account_info,
+        deposit_recipient_info,
+        governance_data.deposit,
+    )?;
+    governance_data.deposit = 0;
+
+    if !has_reached_quorum || !governance_data.has_reached_approval() || is_expired {
         governance_data.did_proposal_pass = Some(false);
     } else {
         governance_data.did_proposal_pass = Some(true);
-        match governance_data.clone().governance_type {
-            GovernanceType::ProgramUpgrade {
-                buffer_account: _,
-                code_link: _,
-            } => {
-                general_data.last_feeless_redemption_date =
-                    clock_data.unix_timestamp as u32 + FEELESS_REDEMPTION_PERIOD;
-            }
-            GovernanceType::VoteAccountGovernance(x) => match x {
-                VoteAccountGovernance::ValidatorID(_) => {
-                    general_data.last_validated_validator_id_proposal = proposal_numeration
+        for action in governance_data.actions.iter() {
+            match action {
+                GovernanceType::ProgramUpgrade {
+                    buffer_account: _,
+                    code_link: _,
+                } => {
+                    general_data.last_feeless_redemption_date =
+                        clock_data.unix_timestamp as u32 + FEELESS_REDEMPTION_PERIOD;
                 }
+                GovernanceType::VoteAccountGovernance(x) => match x {
+                    VoteAccountGovernance::ValidatorID(_) => {
+                        general_data.last_validated_validator_id_proposal = proposal_numeration
+                    }
+                    _ => (),
+                },
                 _ => (),
-            },
-            _ => (),
+            }
         }
     }
     governance_data.is_still_ongoing = false;
@@ -4,8 +4,8 @@ use crate::{
     error::InglError,
     log,
     state::{
-        constants::*, GeneralData, GovernanceData, GovernanceType, ValidatorConfig,
-        VoteAccountGovernance,
+        constants::*, GeneralData, GovernanceData, GovernanceType, ProposalThreshold,
+        ValidatorConfig, VoteAccountGovernance,
     },
     utils::{
         get_clock_data, get_rent_data, verify_nft_ownership, AccountInfoHelpers, OptionExt,
@@ -15,7 +15,6 @@ use crate::{
 
 use borsh::BorshSerialize;
 
-use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     bpf_loader_upgradeable,
@@ -29,7 +28,7 @@ use solana_program::{
 pub fn create_governance(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    governance_type: GovernanceType,
+    actions: Vec<GovernanceType>,
     title: String,
     description: String,
     log_level: u8,
@@ -80,49 +79,56 @@ pub fn create_governance(
 
     log!(log_level, 0, "Done with account collection");
 
-    let buffer_address_info;
-
-    match &governance_type {
-        GovernanceType::ProgramUpgrade {
-            buffer_account,
-            code_link: _,
-        } => {
-            buffer_address_info = next_account_info(account_info_iter)?;
-            buffer_address_info
-                .assert_key_match(&buffer_account)
-                .error_log("Error @ Buffer account must match the account info")?;
-            buffer_address_info
-                .assert_owner(&bpf_loader_upgradeable::id())
-                .error_log("buffer_address_info is not owned by bpf_loader_upgradeable")?;
-            let buffer_data: UpgradeableLoaderState =
-                bincode::deserialize(&buffer_address_info.data.borrow())
-                    .expect("failed to deserialize buffer_address_info data");
-            match buffer_data {
-                UpgradeableLoaderState::Buffer { authority_address } => {
-                    let (expected_authority_address, _epda_bump) = Pubkey::find_program_address(
-                        &[INGL_PROGRAM_AUTHORITY_KEY.as_ref()],
-                        program_id,
-                    );
-                    authority_address
-                        .error_log("Program must have an authority address")?
-                        .assert_match(&expected_authority_address)
-                        .error_log("Error @ Authority must the correct program's PDA")?;
+    // Actions are checked in order against the config as the previous actions leave it.
+    let mut proposed_config = config_data.clone();
+    for action in actions.iter() {
+        match action {
+            GovernanceType::ProgramUpgrade {
+                buffer_account,
+                code_link: _,
+            } => {
+                let buffer_address_info = next_account_info(account_info_iter)?;
+                buffer_address_info
+                    .assert_key_match(&buffer_account)
+                    .error_log("Error @ Buffer account must match the account info")?;
+                buffer_address_info
+                    .assert_owner(&bpf_loader_upgradeable::id())
+                    .error_log("buffer_address_info is not owned by bpf_loader_upgradeable")?;
+                let buffer_data: UpgradeableLoaderState =
+                    bincode::deserialize(&buffer_address_info.data.borrow())
+                        .expect("failed to deserialize buffer_address_info data");
+                match buffer_data {
+                    UpgradeableLoaderState::Buffer { authority_address } => {
+                        let (expected_authority_address, _epda_bump) = Pubkey::find_program_address(
+                            &[INGL_PROGRAM_AUTHORITY_KEY.as_ref()],
+                            program_id,
+                        );
+                        authority_address
+                            .error_log("Program must have an authority address")?
+                            .assert_match(&expected_authority_address)
+                            .error_log("Error @ Authority must the correct program's PDA")?;
+                    }
+                    _ => return Err(InglError::ExpectedBufferAccount.utilize("")),
                 }
-                _ => return Err(InglError::ExpectedBufferAccount.utilize("")),
             }
-        }
 
-        GovernanceType::VoteAccountGovernance(x) => match x {
-            VoteAccountGovernance::ValidatorID(_) => {
-                if config_data.is_validator_id_switchable == false {
-                    return Err(InglError::InvalidData
-                        .utilize("Validator Id for this Validator Instance is not switchable"));
+            GovernanceType::VoteAccountGovernance(x) => match x {
+                VoteAccountGovernance::ValidatorID(_) => {
+                    if proposed_config.is_validator_id_switchable == false {
+                        return Err(InglError::InvalidData.utilize(
+                            "Validator Id for this Validator Instance is not switchable",
+                        ));
+                    }
                 }
-            }
+                _ => (),
+            },
+            GovernanceType::ConfigAccount(x) => x.clone().apply(&mut proposed_config),
             _ => (),
-        },
-        _ => (),
+        }
     }
+    proposed_config
+        .validate_data()
+        .error_log("Error @ the proposed config changes are invalid")?;
 
     let mut general_account_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
     let (_proposal_id, proposal_bump) = proposal_account_info
@@ -137,6 +143,11 @@ pub fn create_governance(
 
     log!(log_level, 0, "Done with account assertions");
 
+    let threshold = ProposalThreshold::strictest(
+        actions
+            .iter()
+            .map(|action| action.get_threshold(&config_data.governance_thresholds)),
+    );
     let governance_data = GovernanceData {
         validation_phrase: GOVERNANCE_DATA_VAL_PHRASE,
         expiration_time: clock_data.unix_timestamp as u32 + config_data.governance_expiration_time,
@@ -147,14 +158,32 @@ pub fn create_governance(
         title: title,
         description: description,
         votes: BTreeMap::new(),
-        governance_type: governance_type,
+        actions,
+        executed_actions: 0,
+        expected_votes: config_data
+            .quorum_basis
+            .get_expected_votes(
+                config_data.max_primary_stake,
+                general_account_data.total_delegated,
+                config_data.unit_backing,
+            )
+            .error_log("failed to calculate expected_votes")?,
+        threshold,
+        proposer: *payer_account_info.key,
+        deposit: config_data.proposal_deposit,
+        is_cancelled: false,
+        is_vetoed: false,
+        is_execution_failed: false,
     };
     governance_data
         .verify()
         .error_log("governance_data is invalid")?;
 
     let space = governance_data.get_space();
-    let lamports = rent_data.minimum_balance(space);
+    let lamports = rent_data
+        .minimum_balance(space)
+        .checked_add(governance_data.deposit)
+        .error_log("Error @ proposal lamports calculation")?;
 
     log!(log_level, 2, "Creating proposal account ...");
     invoke_signed(
@@ -173,19 +202,12 @@ pub fn create_governance(
         ]],
     )
     .error_log("failed to create proposal account")?;
-    log!(log_level, 2, "Created proposal account !!!");
-
-    log!(log_level, 2, "Transfering Spam prevention Sol ...");
-    invoke(
-        &system_instruction::transfer(
-            payer_account_info.key,
-            vote_account_info.key,
-            LAMPORTS_PER_SOL.checked_mul(2).unwrap(),
-        ),
-        &[payer_account_info.clone(), vote_account_info.clone()],
-    )
-    .error_log("failed to transfer spam prevention sol")?;
-    log!(log_level, 2, "Transferred Spam prevention Sol !!!");
+    log!(
+        log_level,
+        2,
+        "Created proposal account, escrowing a {} lamports deposit !!!",
+        governance_data.deposit
+    );
 
     general_account_data
         .unfinalized_proposals
@@ -1,4 +1,12 @@
 pub mod init_governance;
 pub mod vote_governance;
 pub mod finalize_governance;
-pub mod execute_governance;
\ No newline at end of file
+pub mod execute_governance;
+pub mod change_vote;
+pub mod withdraw_vote;
+pub mod delegate_vote;
+pub mod revoke_vote_delegation;
+pub mod cancel_proposal;
+pub mod close_proposal;
+pub mod prune_votes;
+pub mod veto_proposal;
\ No newline at end of file
@@ -0,0 +1,67 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::governance_processes::withdraw_vote::shrink_and_refund,
+    state::{constants::*, NftData},
+    utils::{get_rent_data, verify_nft_ownership, AccountInfoHelpers, OptionExt, ResultExt},
+};
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Removes the votes of an NFT on closed proposals, shrinking its account and refunding the freed rent to its holder.
+///Each proposal numeration is followed by its, now closed, proposal account.
+pub fn prune_votes(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    numerations: Vec<u32>,
+    log_level: u8,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Prune votes ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+    let mut nft_data = Box::new(NftData::parse(nft_account_data_info, program_id)?);
+
+    for numeration in numerations.iter() {
+        let proposal_account_info = next_account_info(account_info_iter)?;
+        proposal_account_info
+            .assert_seed(program_id, &[INGL_PROPOSAL_KEY, &numeration.to_be_bytes()])
+            .error_log("failed to assert pda input for proposal_account_info")?;
+        if !proposal_account_info.data_is_empty() && proposal_account_info.lamports() != 0 {
+            Err(InglError::TooEarly.utilize("Only votes on closed proposals can be pruned"))?
+        }
+        nft_data
+            .all_votes
+            .remove(numeration)
+            .error_log("vote to prune not found in nft data")?;
+    }
+
+    log!(log_level, 2, "Pruned {} votes !!!", numerations.len());
+    shrink_and_refund(
+        nft_account_data_info,
+        payer_account_info,
+        nft_data.get_space(),
+        &rent_data,
+    )?;
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into nft_account_info")?;
+    log!(log_level, 4, "Done with Prune votes !!!");
+    Ok(())
+}
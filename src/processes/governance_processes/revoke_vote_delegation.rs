@@ -0,0 +1,51 @@
+use crate::{
+    log,
+    processes::init_processes::reset_uris_page::refund_lamports,
+    state::{constants::*, VoteDelegation},
+    utils::{verify_nft_ownership, AccountInfoHelpers, ResultExt},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Revokes the vote delegation of an NFT, closing its account and refunding its rent to the holder.
+pub fn revoke_vote_delegation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Revoke vote delegation ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let vote_delegation_account_info = next_account_info(account_info_iter)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+    vote_delegation_account_info
+        .assert_seed(
+            program_id,
+            &[VOTE_DELEGATION_KEY, mint_account_info.key.as_ref()],
+        )
+        .error_log("failed to assert pda input for vote_delegation_account_info")?;
+    VoteDelegation::parse(vote_delegation_account_info, program_id)?;
+
+    log!(log_level, 2, "Closing the vote delegation account ...");
+    refund_lamports(
+        vote_delegation_account_info,
+        payer_account_info,
+        vote_delegation_account_info.lamports(),
+    )?;
+    vote_delegation_account_info.data.borrow_mut().fill(0);
+    log!(log_level, 4, "Vote delegation revoked !!!");
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::{
+    error::InglError,
+    log,
+    state::{constants::*, GovernanceData, GovernanceEvent, ValidatorConfig},
+    utils::{get_clock_data, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Lets the guardian veto a passed proposal before the governance safety leeway elapses.
+pub fn veto_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proposal_numeration: u32,
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Veto proposal ...");
+    let account_info_iter = &mut accounts.iter();
+    let guardian_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+
+    guardian_account_info
+        .assert_signer()
+        .error_log("Error: Guardian account is not a signer")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("failed to assert_pda_input for config_account_info")?;
+    proposal_account_info
+        .assert_seed(
+            program_id,
+            &[INGL_PROPOSAL_KEY, &proposal_numeration.to_be_bytes()],
+        )
+        .error_log("failed to assert_pda_input for proposal_account_info")?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    let mut governance_data = Box::new(GovernanceData::parse(proposal_account_info, program_id)?);
+
+    guardian_account_info
+        .assert_key_match(
+            &config_data
+                .guardian
+                .error_log("This validator instance has no guardian")?,
+        )
+        .error_log("Error: Guardian account is not the config guardian")?;
+    if governance_data.did_proposal_pass != Some(true) {
+        Err(InglError::InvalidData.utilize("Only passed proposals can be vetoed"))?
+    }
+    if governance_data.is_proposal_executed {
+        Err(InglError::TooLate.utilize("This proposal has already been executed."))?
+    }
+    if governance_data.is_vetoed {
+        Err(InglError::TooLate.utilize("This proposal has already been vetoed."))?
+    }
+    let date_finalized = governance_data
+        .date_finalized
+        .error_log("Proposal must be finalized")?;
+    if clock_data.unix_timestamp as u32 >= date_finalized + GOVERNANCE_SAFETY_LEEWAY {
+        Err(InglError::TooLate.utilize("The governance safety leeway has elapsed"))?
+    }
+
+    governance_data.is_vetoed = true;
+    governance_data
+        .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into proposal_account_info")?;
+    GovernanceEvent::ProposalVetoed {
+        proposal: proposal_numeration,
+    }
+    .emit()?;
+    log!(log_level, 4, "Proposal vetoed !!!");
+    Ok(())
+}
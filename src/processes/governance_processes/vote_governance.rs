@@ -1,8 +1,10 @@
 use crate::{
     error::InglError,
     log,
-    state::{constants::*, GovernanceData, NftData, FundsLocation},
-    utils::{get_clock_data, get_rent_data, verify_nft_ownership, AccountInfoHelpers, ResultExt},
+    state::{constants::*, FundsLocation, GovernanceData, GovernanceEvent, NftData},
+    utils::{
+        get_clock_data, get_rent_data, verify_voting_authority, AccountInfoHelpers, ResultExt,
+    },
 };
 use borsh::BorshSerialize;
 use solana_program::{
@@ -61,11 +63,12 @@ pub fn vote_governance(
         let mint_account_info = next_account_info(account_info_iter)?;
         let associated_token_account_info = next_account_info(account_info_iter)?;
 
-        verify_nft_ownership(
+        verify_voting_authority(
             payer_account_info,
             mint_account_info,
             nft_account_data_info,
             associated_token_account_info,
+            account_info_iter,
             program_id,
         )?;
 
@@ -76,6 +79,10 @@ pub fn vote_governance(
             _ => Err(InglError::InvalidFundsLocation.utilize("Funds location is not delegated"))?,
         }
 
+        if governance_data.votes.contains_key(&nft_data.numeration) {
+            Err(InglError::InvalidData
+                .utilize("This NFT already voted. Use ChangeVote to change its vote"))?
+        }
         log!(log_level, 0, "about to insert vote");
         governance_data.votes.insert(nft_data.numeration, vote);
         nft_data.all_votes.insert(numeration, vote);
@@ -104,6 +111,12 @@ pub fn vote_governance(
         nft_data
             .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
             .error_log("failed to serialize into nft_account_info")?;
+        GovernanceEvent::VoteCast {
+            proposal: numeration,
+            nft: nft_data.numeration,
+            vote,
+        }
+        .emit()?;
     }
 
     let new_space = proposal_account_info.data.borrow().len() + incremented_space;
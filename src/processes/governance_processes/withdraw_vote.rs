@@ -0,0 +1,125 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::init_processes::reset_uris_page::refund_lamports,
+    state::{constants::*, GovernanceData, GovernanceEvent, NftData},
+    utils::{
+        get_clock_data, get_rent_data, verify_voting_authority, AccountInfoHelpers, OptionExt,
+        ResultExt,
+    },
+};
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+///Withdraws the votes of NFTs from an ongoing proposal.
+///Both vote maps shrink, and the rent they free is refunded to the payer.
+pub fn withdraw_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    numeration: u32,
+    cnt: u8,
+    log_level: u8,
+    clock_is_from_account: bool,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Withdraw vote ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let proposal_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    proposal_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Proposal account is not owned by the program")?;
+    proposal_account_info
+        .assert_seed(program_id, &[INGL_PROPOSAL_KEY, &numeration.to_be_bytes()])
+        .error_log("failed to assert pda input for proposal_account_info")?;
+
+    let mut governance_data = Box::new(GovernanceData::parse(proposal_account_info, program_id)?);
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    if !governance_data.is_still_ongoing {
+        Err(InglError::TooLate.utilize("This proposal is currently Closed"))?
+    }
+    if governance_data.expiration_time < clock_data.unix_timestamp as u32 {
+        Err(InglError::TooLate.utilize("This proposal is currently Expired"))?
+    }
+
+    let mut decremented_space = 0;
+    for _ in 0..cnt {
+        let nft_account_data_info = next_account_info(account_info_iter)?;
+        let mint_account_info = next_account_info(account_info_iter)?;
+        let associated_token_account_info = next_account_info(account_info_iter)?;
+
+        verify_voting_authority(
+            payer_account_info,
+            mint_account_info,
+            nft_account_data_info,
+            associated_token_account_info,
+            account_info_iter,
+            program_id,
+        )?;
+
+        let mut nft_data = Box::new(NftData::parse(nft_account_data_info, program_id)?);
+        governance_data
+            .votes
+            .remove(&nft_data.numeration)
+            .error_log("This NFT has not voted on the proposal")?;
+        nft_data
+            .all_votes
+            .remove(&numeration)
+            .error_log("vote to withdraw not found in nft data")?;
+        decremented_space += 5;
+
+        shrink_and_refund(
+            nft_account_data_info,
+            payer_account_info,
+            nft_account_data_info.data_len() - 5,
+            &rent_data,
+        )?;
+        nft_data
+            .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+            .error_log("failed to serialize into nft_account_info")?;
+        GovernanceEvent::VoteWithdrawn {
+            proposal: numeration,
+            nft: nft_data.numeration,
+        }
+        .emit()?;
+    }
+
+    shrink_and_refund(
+        proposal_account_info,
+        payer_account_info,
+        proposal_account_info.data_len() - decremented_space,
+        &rent_data,
+    )?;
+    governance_data
+        .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
+        .error_log("failed to serialize into proposal_account_info")?;
+    log!(log_level, 4, "Done with Withdraw vote !!!");
+    Ok(())
+}
+
+///Reallocates a program owned account to a smaller space, refunding the rent it frees.
+pub fn shrink_and_refund(
+    account_info: &AccountInfo,
+    refund_account_info: &AccountInfo,
+    space: usize,
+    rent_data: &Rent,
+) -> ProgramResult {
+    let lamports = rent_data
+        .minimum_balance(account_info.data_len())
+        .saturating_sub(rent_data.minimum_balance(space));
+    account_info
+        .realloc(space, false)
+        .error_log("failed to realloc account size")?;
+    refund_lamports(account_info, refund_account_info, lamports)
+}
@@ -0,0 +1,106 @@
+use crate::{
+    log,
+    processes::init_processes::upload_uris_page::grow_account,
+    state::{constants::*, Uploader, UploadersAccount, ValidatorConfig},
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+///Authorizes an uploader, or replaces the rarities it may upload to.
+pub fn add_uploader(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    uploader: Uploader,
+    log_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uploaders_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+
+    let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    payer_account_info
+        .assert_key_match(&config.validator_id)
+        .error_log("Error: Payer account is not the validator_id")?;
+
+    let mut uploaders_data = load_uploaders_account(program_id, uploaders_account_info)?;
+    log!(log_level, 2, "Authorizing uploader {} ...", uploader.key);
+    uploaders_data.add_uploader(uploader)?;
+    save_uploaders_account(
+        program_id,
+        payer_account_info,
+        uploaders_account_info,
+        &uploaders_data,
+    )?;
+    log!(log_level, 2, "Uploader authorized !!!");
+    Ok(())
+}
+
+///Parses the uploaders account, or returns an empty list if it has not been created yet.
+pub fn load_uploaders_account(
+    program_id: &Pubkey,
+    uploaders_account_info: &AccountInfo,
+) -> Result<UploadersAccount, ProgramError> {
+    uploaders_account_info
+        .assert_seed(program_id, &[UPLOADERS_SEED])
+        .error_log("Error: uploaders_account is not the uploaders account")?;
+    if uploaders_account_info.data_is_empty() {
+        return Ok(UploadersAccount::default());
+    }
+    UploadersAccount::parse(uploaders_account_info, program_id)
+}
+
+///Serializes the uploaders account, creating it or topping up its rent from the payer as needed.
+pub fn save_uploaders_account<'a>(
+    program_id: &Pubkey,
+    payer_account_info: &AccountInfo<'a>,
+    uploaders_account_info: &AccountInfo<'a>,
+    uploaders_data: &UploadersAccount,
+) -> ProgramResult {
+    let space = uploaders_data.get_space();
+    if uploaders_account_info.data_is_empty() {
+        let (_uploaders_key, uploaders_bump) = uploaders_account_info
+            .assert_seed(program_id, &[UPLOADERS_SEED])
+            .error_log("Error: uploaders_account is not the uploaders account")?;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account_info.key,
+                uploaders_account_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer_account_info.clone(), uploaders_account_info.clone()],
+            &[&[UPLOADERS_SEED, &[uploaders_bump]]],
+        )
+        .error_log("Error: Failed to create uploaders_account")?;
+    } else if space > uploaders_account_info.data_len() {
+        grow_account(payer_account_info, uploaders_account_info, space)?;
+    }
+    uploaders_data
+        .serialize(&mut &mut uploaders_account_info.data.borrow_mut()[..])
+        .error_log("Error: Failed to serialize uploaders account")?;
+    Ok(())
+}
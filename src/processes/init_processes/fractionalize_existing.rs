@@ -27,23 +27,12 @@ pub fn fractionalize(
     let InitArgs {
         log_level,
         init_commission,
-        max_primary_stake,
-        nft_holders_share,
-        initial_redemption_fee,
-        is_validator_id_switchable,
-        unit_backing,
-        redemption_fee_duration,
-        creator_royalties,
-        rarities,
-        rarity_names,
-        governance_expiration_time,
-        twitter_handle,
-        discord_invite,
-        validator_name,
-        collection_uri,
-        website,
-        default_uri,
-        proposal_quorum,
+        ref rarities,
+        ref rarity_names,
+        ref rarity_reward_weights,
+        ref validator_name,
+        ref collection_uri,
+        ..
     } = init_args;
 
     log!(log_level, 4, "Init Process Started");
@@ -178,12 +167,13 @@ pub fn fractionalize(
     )
     .error_log("Error: @mint_collection creation")?;
 
-    let mut rarity_name_space = 0;
-    for i in rarity_names.iter() {
-        rarity_name_space += i.len() + 4;
-    }
+    let uri_data = UrisAccount::new(
+        rarities.clone(),
+        rarity_names.clone(),
+        rarity_reward_weights.clone(),
+    )?;
 
-    let uris_account_creation_size = 16 + rarities.len() * 2 + rarity_name_space;
+    let uris_account_creation_size = uri_data.get_space();
     let uris_account_creation_lamports = rent_data.minimum_balance(uris_account_creation_size);
     log!(log_level, 2, "Creating Uris Account ... ");
     invoke_signed(
@@ -200,23 +190,9 @@ pub fn fractionalize(
     log!(log_level, 2, "Created Uris Account !!!");
 
     let config_data = ValidatorConfig::new(
-        is_validator_id_switchable,
-        max_primary_stake,
-        nft_holders_share,
-        initial_redemption_fee,
-        unit_backing,
-        redemption_fee_duration,
-        proposal_quorum,
-        creator_royalties,
-        init_commission,
+        &init_args,
         *validator_account_info.key,
         *vote_account_info.key,
-        governance_expiration_time,
-        default_uri,
-        validator_name.clone(),
-        twitter_handle,
-        discord_invite,
-        website,
     )?;
 
     let general_data = GeneralData::default();
@@ -254,8 +230,6 @@ pub fn fractionalize(
     )?;
     log!(log_level, 2, "Created General Account ... ");
 
-    let uri_data = UrisAccount::new(rarities, rarity_names)?;
-
     log!(log_level, 0, "Created Main Data succesfully ... ");
 
     config_data
@@ -280,7 +254,7 @@ pub fn fractionalize(
 
     log!(log_level, 2, "Initing Program Registration ... ");
     invoke(
-        &register_program_instruction(*payer_account_info.key, *program_id, validator_name),
+        &register_program_instruction(*payer_account_info.key, *program_id, validator_name.clone()),
         &registry_program_accounts,
     )?;
 
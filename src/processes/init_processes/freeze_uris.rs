@@ -0,0 +1,52 @@
+use crate::{
+    log,
+    state::{constants::*, UrisAccount, ValidatorConfig},
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Permanently locks the uris and rarities. Afterwards they can only be changed through governance.
+pub fn freeze_uris(program_id: &Pubkey, accounts: &[AccountInfo], log_level: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    uris_account_info
+        .assert_owner(program_id)
+        .error_log("Error: uris_account is not owned by the program")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: uris_account is not the uris account")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+
+    let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    payer_account_info
+        .assert_key_match(&config.validator_id)
+        .error_log("Error: Payer account is not the validator_id")?;
+
+    let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    uris_account_data.assert_not_frozen()?;
+    log!(log_level, 2, "Freezing the uris ...");
+    uris_account_data.is_frozen = true;
+    uris_account_data
+        .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
+        .error_log("Error: Failed to serialize uris account")?;
+    log!(log_level, 2, "Uris frozen !!!");
+    Ok(())
+}
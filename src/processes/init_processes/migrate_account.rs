@@ -0,0 +1,400 @@
+use crate::{
+    error::InglError,
+    log,
+    state::{
+        constants::*, FundsLocation, GeneralData, GovernanceData, GovernanceThresholds,
+        GovernanceType, NftData, ProposalThreshold, QuorumBasis, RebalancingData, UrisAccount,
+        ValidatorConfig, VoteReward,
+    },
+    utils::{get_rent_data, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+///Converts a config, uris, general, nft or proposal account of an instance initialized by an earlier version of the program to its current layout.
+///Anyone can migrate an account, the payer tops up the rent of the grown account. The config account must be migrated first.
+pub fn migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Account Migration ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let target_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    system_program_account_info
+        .assert_key_match(&system_program::id())
+        .error_log("Error: System program account is not the system program")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+    target_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Target account is not owned by the program")?;
+
+    let validation_phrase = u32::deserialize(&mut &target_account_info.data.borrow()[..])
+        .error_log("Error: Failed to read the validation phrase of the target account")?;
+    let target = MigrationTarget {
+        payer_account_info,
+        account_info: target_account_info,
+        rent_data: &rent_data,
+    };
+
+    if validation_phrase == LEGACY_INGL_CONFIG_VAL_PHRASE {
+        target_account_info
+            .assert_key_match(config_account_info.key)
+            .error_log("Error: Target account is not the config account")?;
+        log!(log_level, 2, "Migrating the config account ...");
+        let config_data = migrate_config(target.read()?)?;
+        return target.write(&config_data, config_data.get_space());
+    }
+
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    match validation_phrase {
+        LEGACY_URIS_ACCOUNT_VAL_PHRASE => {
+            target_account_info
+                .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+                .error_log("Error: Target account is not the uris account")?;
+            log!(log_level, 2, "Migrating the uris account ...");
+            let uris_data = migrate_uris(target.read()?);
+            target.write(&uris_data, uris_data.get_space())
+        }
+        LEGACY_GENERAL_ACCOUNT_VAL_PHRASE => {
+            target_account_info
+                .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+                .error_log("Error: Target account is not the general account")?;
+            log!(log_level, 2, "Migrating the general account ...");
+            let general_data = migrate_general(target.read()?);
+            target.write(&general_data, general_data.get_space())
+        }
+        LEGACY_NFT_DATA_VAL_PHRASE => {
+            log!(log_level, 2, "Migrating the nft account ...");
+            let nft_data = migrate_nft(target.read()?, &config_data);
+            target.write(&nft_data, nft_data.get_space())
+        }
+        LEGACY_GOVERNANCE_DATA_VAL_PHRASE => {
+            log!(log_level, 2, "Migrating the proposal account ...");
+            let governance_data = migrate_proposal(target.read()?, &config_data)?;
+            target.write(&governance_data, governance_data.get_space())
+        }
+        _ => Err(InglError::InvalidValPhrase.utilize("Target account has no legacy layout")),
+    }?;
+    log!(log_level, 2, "Account migrated !!!");
+    Ok(())
+}
+
+struct MigrationTarget<'a, 'b> {
+    payer_account_info: &'b AccountInfo<'a>,
+    account_info: &'b AccountInfo<'a>,
+    rent_data: &'b Rent,
+}
+impl<'a, 'b> MigrationTarget<'a, 'b> {
+    fn read<T: BorshDeserialize>(&self) -> Result<T, ProgramError> {
+        Ok(try_from_slice_unchecked(&self.account_info.data.borrow())
+            .error_log("Error: Failed to deserialize the legacy layout of the target account")?)
+    }
+
+    /// Grows the account to the space of its current layout if needed, then stores its data.
+    fn write<T: BorshSerialize>(&self, data: &T, space: usize) -> ProgramResult {
+        if self.account_info.data_len() < space {
+            let lamports = self
+                .rent_data
+                .minimum_balance(space)
+                .saturating_sub(self.account_info.lamports());
+            if lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        self.payer_account_info.key,
+                        self.account_info.key,
+                        lamports,
+                    ),
+                    &[self.payer_account_info.clone(), self.account_info.clone()],
+                )
+                .error_log("Error: Failed to transfer rent lamports")?;
+            }
+            self.account_info
+                .realloc(space, false)
+                .error_log("Error: Failed to realloc account")?;
+        }
+        data.serialize(&mut &mut self.account_info.data.borrow_mut()[..])
+            .error_log("Error: Failed to serialize the migrated account")?;
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyValidatorConfig {
+    pub validation_phrase: u32,
+    pub is_validator_id_switchable: bool,
+    pub max_primary_stake: u64,
+    pub nft_holders_share: u8,
+    pub initial_redemption_fee: u8,
+    pub unit_backing: u64,
+    pub redemption_fee_duration: u32,
+    pub proposal_quorum: u8,
+    pub creator_royalties: u16,
+    pub commission: u8,
+    pub validator_id: Pubkey,
+    pub vote_account: Pubkey,
+    pub governance_expiration_time: u32,
+    pub default_uri: String,
+    pub validator_name: String,
+    pub twitter_handle: String,
+    pub discord_invite: String,
+    pub website: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyUrisAccount {
+    pub validation_phrase: u32,
+    pub rarities: Vec<u16>,
+    pub rarity_names: Vec<String>,
+    pub uris: Vec<Vec<String>>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyVoteReward {
+    pub epoch_number: u64,
+    pub total_reward: u64,
+    pub total_stake: u64,
+    pub nft_holders_reward: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyGeneralData {
+    pub validation_phrase: u32,
+    pub mint_numeration: u32,
+    pub pending_delegation_total: u64,
+    pub dealloced: u64,
+    pub total_delegated: u64,
+    pub last_withdraw_epoch: u64,
+    pub last_total_staked: u64,
+    pub is_t_stake_initialized: bool,
+    pub proposal_numeration: u32,
+    pub last_feeless_redemption_date: u32,
+    pub last_validated_validator_id_proposal: u32,
+    pub rebalancing_data: RebalancingData,
+    pub unfinalized_proposals: BTreeSet<u32>,
+    pub vote_rewards: Vec<LegacyVoteReward>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyNftData {
+    pub validation_phrase: u32,
+    pub rarity: Option<u8>,
+    pub rarity_seed_slot: Option<u64>,
+    pub funds_location: FundsLocation,
+    pub numeration: u32,
+    pub date_created: u32,
+    pub last_withdrawal_epoch: Option<u64>,
+    pub last_delegation_epoch: Option<u64>,
+    pub all_withdraws: Vec<u64>,
+    pub all_votes: BTreeMap<u32, bool>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyGovernanceData {
+    pub validation_phrase: u32,
+    pub expiration_time: u32,
+    pub is_still_ongoing: bool,
+    pub date_finalized: Option<u32>,
+    pub did_proposal_pass: Option<bool>,
+    pub is_proposal_executed: bool,
+    pub title: String,
+    pub description: String,
+    pub votes: BTreeMap<u32, bool>,
+    pub governance_type: GovernanceType,
+}
+
+/// Every kind of proposal keeps the single quorum of the legacy config. Legacy proposals failed
+/// if more than 20% of their votes were against, which is an 80% approval threshold.
+fn migrate_config(legacy: LegacyValidatorConfig) -> Result<ValidatorConfig, ProgramError> {
+    let critical = ProposalThreshold {
+        quorum: legacy.proposal_quorum.max(MIN_CRITICAL_PROPOSAL_QUORUM),
+        approval: GOVERNANCE_EXECUTION_THRESHOLD,
+    };
+    let other = ProposalThreshold {
+        quorum: legacy.proposal_quorum.max(MIN_PROPOSAL_QUORUM),
+        approval: GOVERNANCE_EXECUTION_THRESHOLD,
+    };
+    let config_data = ValidatorConfig {
+        validation_phrase: INGL_CONFIG_VAL_PHRASE,
+        is_validator_id_switchable: legacy.is_validator_id_switchable,
+        max_primary_stake: legacy.max_primary_stake,
+        nft_holders_share: legacy.nft_holders_share,
+        initial_redemption_fee: legacy.initial_redemption_fee,
+        unit_backing: legacy.unit_backing,
+        redemption_fee_duration: legacy.redemption_fee_duration,
+        governance_thresholds: GovernanceThresholds {
+            program_upgrade: critical,
+            validator_id: critical,
+            commission: other,
+            economics: other,
+            profile: other,
+            uploaders: other,
+            uris: other,
+            treasury: other,
+            thresholds: critical,
+        },
+        creator_royalties: legacy.creator_royalties,
+        commission: legacy.commission,
+        validator_id: legacy.validator_id,
+        vote_account: legacy.vote_account,
+        governance_expiration_time: legacy.governance_expiration_time,
+        rarity_reroll_fee: None,
+        rarity_reroll_cooldown: 0,
+        mint_fee: 0,
+        referral_fee_bps: 0,
+        programmable_rule_set: None,
+        uses_token_2022: false,
+        lock_boost_tiers: Vec::new(),
+        quorum_basis: QuorumBasis::MaxPrimaryStake,
+        proposal_deposit: MIN_PROPOSAL_DEPOSIT,
+        treasury_share: 0,
+        guardian: None,
+        is_paused: false,
+        default_uri: legacy.default_uri,
+        validator_name: legacy.validator_name,
+        twitter_handle: legacy.twitter_handle,
+        discord_invite: legacy.discord_invite,
+        website: legacy.website,
+    };
+    config_data
+        .validate_data()
+        .error_log("Error @ Migrated Config Data Validation")?;
+    Ok(config_data)
+}
+
+/// Legacy uris were already in use, so they come out frozen and unweighted.
+fn migrate_uris(legacy: LegacyUrisAccount) -> UrisAccount {
+    UrisAccount {
+        validation_phrase: URIS_ACCOUNT_VAL_PHRASE,
+        rarities: legacy.rarities,
+        rarity_names: legacy.rarity_names,
+        reward_weights: Vec::new(),
+        uris: legacy.uris,
+        uri_pages: Vec::new(),
+        base_uri: String::new(),
+        uri_roots: Vec::new(),
+        is_frozen: true,
+    }
+}
+
+/// Legacy rewards weren't weighted, so every delegated nft weighs its unit backing.
+fn migrate_general(legacy: LegacyGeneralData) -> GeneralData {
+    GeneralData {
+        validation_phrase: GENERAL_ACCOUNT_VAL_PHRASE,
+        mint_numeration: legacy.mint_numeration,
+        pending_delegation_total: legacy.pending_delegation_total,
+        dealloced: legacy.dealloced,
+        total_delegated: legacy.total_delegated,
+        total_weighted_delegated: legacy.total_delegated,
+        total_boost_weight: 0,
+        last_withdraw_epoch: legacy.last_withdraw_epoch,
+        last_total_staked: legacy.last_total_staked,
+        is_t_stake_initialized: legacy.is_t_stake_initialized,
+        proposal_numeration: legacy.proposal_numeration,
+        last_feeless_redemption_date: legacy.last_feeless_redemption_date,
+        last_validated_validator_id_proposal: legacy.last_validated_validator_id_proposal,
+        rebalancing_data: legacy.rebalancing_data,
+        unfinalized_proposals: legacy.unfinalized_proposals,
+        vote_rewards: legacy
+            .vote_rewards
+            .into_iter()
+            .map(|reward| VoteReward {
+                epoch_number: reward.epoch_number,
+                total_reward: reward.total_reward,
+                total_stake: reward.total_stake,
+                total_weighted_stake: reward.total_stake,
+                nft_holders_reward: reward.nft_holders_reward,
+                total_boost_weight: 0,
+                boost_reward: 0,
+            })
+            .collect(),
+    }
+}
+
+fn migrate_nft(legacy: LegacyNftData, config_data: &ValidatorConfig) -> NftData {
+    let weighted_backing = match legacy.funds_location {
+        FundsLocation::Delegated => config_data.unit_backing,
+        FundsLocation::Undelegated => 0,
+    };
+    NftData {
+        validation_phrase: NFT_DATA_VAL_PHRASE,
+        rarity: legacy.rarity,
+        rarity_seed_slot: legacy.rarity_seed_slot,
+        funds_location: legacy.funds_location,
+        numeration: legacy.numeration,
+        date_created: legacy.date_created,
+        last_withdrawal_epoch: legacy.last_withdrawal_epoch,
+        last_delegation_epoch: legacy.last_delegation_epoch,
+        all_withdraws: legacy.all_withdraws,
+        all_votes: legacy.all_votes,
+        rarity_commitment: None,
+        reroll_count: 0,
+        last_reroll_date: None,
+        is_programmable: false,
+        last_known_owner: None,
+        lock_until: None,
+        lock_boost_tier: None,
+        last_owner_seen_epoch: None,
+        weighted_backing,
+        boost_weight: 0,
+    }
+}
+
+/// Legacy proposals carried no deposit and didn't record their proposer, so their rent is
+/// refunded to the validator id once they're closed.
+fn migrate_proposal(
+    legacy: LegacyGovernanceData,
+    config_data: &ValidatorConfig,
+) -> Result<GovernanceData, ProgramError> {
+    Ok(GovernanceData {
+        validation_phrase: GOVERNANCE_DATA_VAL_PHRASE,
+        expiration_time: legacy.expiration_time,
+        is_still_ongoing: legacy.is_still_ongoing,
+        date_finalized: legacy.date_finalized,
+        did_proposal_pass: legacy.did_proposal_pass,
+        is_proposal_executed: legacy.is_proposal_executed,
+        title: legacy.title,
+        description: legacy.description,
+        votes: legacy.votes,
+        expected_votes: QuorumBasis::MaxPrimaryStake
+            .get_expected_votes(config_data.max_primary_stake, 0, config_data.unit_backing)
+            .error_log("failed to calculate expected_votes")?,
+        threshold: legacy
+            .governance_type
+            .get_threshold(&config_data.governance_thresholds),
+        actions: vec![legacy.governance_type],
+        executed_actions: legacy.is_proposal_executed as u8,
+        proposer: config_data.validator_id,
+        deposit: 0,
+        is_cancelled: false,
+        is_vetoed: false,
+        is_execution_failed: false,
+    })
+}
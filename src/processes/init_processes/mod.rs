@@ -1,4 +1,12 @@
+pub mod add_uploader;
+pub mod commit_uris_root;
 pub mod fractionalize_existing;
+pub mod freeze_uris;
 pub mod init;
+pub mod migrate_account;
+pub mod remove_uploader;
 pub mod reset_uris;
+pub mod reset_uris_page;
+pub mod set_base_uri;
 pub mod upload_uris;
+pub mod upload_uris_page;
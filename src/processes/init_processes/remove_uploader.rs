@@ -0,0 +1,51 @@
+use crate::{
+    log,
+    processes::init_processes::add_uploader::load_uploaders_account,
+    state::{constants::*, ValidatorConfig},
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Revokes the upload authorization of an uploader.
+pub fn remove_uploader(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    uploader: Pubkey,
+    log_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uploaders_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+
+    let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    payer_account_info
+        .assert_key_match(&config.validator_id)
+        .error_log("Error: Payer account is not the validator_id")?;
+
+    let mut uploaders_data = load_uploaders_account(program_id, uploaders_account_info)?;
+    log!(log_level, 2, "Removing uploader {} ...", uploader);
+    uploaders_data.remove_uploader(&uploader)?;
+    uploaders_data
+        .serialize(&mut &mut uploaders_account_info.data.borrow_mut()[..])
+        .error_log("Error: Failed to serialize uploaders account")?;
+    log!(log_level, 2, "Uploader removed !!!");
+    Ok(())
+}
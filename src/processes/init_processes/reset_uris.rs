@@ -1,5 +1,6 @@
 use crate::{
     log,
+    processes::init_processes::reset_uris_page::refund_lamports,
     state::{constants::*, UrisAccount, ValidatorConfig},
     utils::{AccountInfoHelpers, ResultExt},
 };
@@ -8,11 +9,9 @@ use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    // program::invoke_signed,
     pubkey::Pubkey,
     rent::Rent,
     sysvar::Sysvar,
-    // system_instruction,
 };
 
 pub fn reset_uris(program_id: &Pubkey, accounts: &[AccountInfo], log_level: u8) -> ProgramResult {
@@ -42,14 +41,10 @@ pub fn reset_uris(program_id: &Pubkey, accounts: &[AccountInfo], log_level: u8)
         .assert_key_match(&config.validator_id)
         .error_log("Error: Payer account is not the validator_id")?;
     let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    uris_account_data.assert_not_frozen()?;
     uris_account_data.uris = Vec::new();
 
-    let mut rarity_name_space = 0;
-    for i in uris_account_data.rarity_names.iter() {
-        rarity_name_space += i.len() + 4;
-    }
-
-    let uri_account_size = 16 + uris_account_data.rarities.len() * 2 + rarity_name_space;
+    let uri_account_size = uris_account_data.get_space();
 
     let lamports = Rent::get()?.minimum_balance(uris_account_info.data_len())
         - Rent::get()?.minimum_balance(uri_account_size);
@@ -63,12 +58,8 @@ pub fn reset_uris(program_id: &Pubkey, accounts: &[AccountInfo], log_level: u8)
         .realloc(uri_account_size, true)
         .error_log("Error: Failed to realloc uris account")?;
 
-    // invoke_signed(
-    //     &system_instruction::transfer(uris_account_info.key, payer_account_info.key, lamports),
-    //     &[uris_account_info.clone(), payer_account_info.clone()],
-    //     &[&[URIS_ACCOUNT_SEED.as_ref(), &[uris_account_bump]]],
-    // )
-    // .error_log("Error: Failed to transfer lamports to payer account")?;
+    // The uris account is owned by the program, so its lamports are moved directly rather than through the system program.
+    refund_lamports(uris_account_info, payer_account_info, lamports)?;
     uris_account_data
         .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
         .error_log("Error: Failed to serialize into uris account")?;
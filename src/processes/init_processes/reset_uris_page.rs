@@ -0,0 +1,143 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::init_processes::upload_uris_page::grow_account,
+    state::{constants::*, UrisAccount, UrisPage, ValidatorConfig},
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+///Replaces the uris of a page of a rarity. Emptying the last page of a rarity closes it.
+///Rent freed by the shrink is refunded to the payer.
+pub fn reset_uris_page(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rarity: u8,
+    page: u16,
+    uris: Vec<String>,
+    log_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+    let uris_page_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer account is not a signer")?;
+    uris_account_info
+        .assert_owner(program_id)
+        .error_log("Error: uris_account is not owned by the program")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: uris_account is not the uris account")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+    uris_page_account_info
+        .assert_seed(program_id, &[URIS_PAGE_KEY, &[rarity], &page.to_be_bytes()])
+        .error_log("Error: uris_page_account_info pda assertion")?;
+
+    let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    payer_account_info
+        .assert_key_match(&config.validator_id)
+        .error_log("Error: Payer account is not the validator_id")?;
+    let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    uris_account_data.assert_not_frozen()?;
+    let mut uris_page = UrisPage::parse(uris_page_account_info, program_id)?;
+
+    if uris.is_empty() {
+        let page_count = uris_account_data.get_page_count(rarity);
+        if page + 1 != page_count {
+            Err(InglError::InvalidData.utilize("Only the last page of a rarity can be emptied"))?
+        }
+        log!(
+            log_level,
+            2,
+            "Closing page {} of rarity {} ...",
+            page,
+            rarity
+        );
+        *uris_account_data
+            .uri_pages
+            .get_mut(rarity as usize)
+            .error_log("Error: rarity has no pages")? = page;
+        uris_account_data
+            .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
+            .error_log("Error: Failed to serialize into uris account")?;
+
+        refund_lamports(
+            uris_page_account_info,
+            payer_account_info,
+            uris_page_account_info.lamports(),
+        )?;
+        uris_page_account_info.data.borrow_mut().fill(0);
+        log!(log_level, 2, "Closed page {} !!", page);
+        return Ok(());
+    }
+
+    log!(
+        log_level,
+        0,
+        "Replacing page {} of rarity {} with {} URIs",
+        page,
+        rarity,
+        uris.len()
+    );
+    uris_page.uris = uris;
+    let space = uris_page.get_space();
+    if space > MAX_URIS_PAGE_SPACE {
+        Err(InglError::UrisAccountTooBig.utilize("Too many images for a single page"))?
+    }
+    if space > uris_page_account_info.data_len() {
+        grow_account(payer_account_info, uris_page_account_info, space)?;
+    } else {
+        uris_page_account_info
+            .realloc(space, false)
+            .error_log("Error: Failed to realloc uris page")?;
+        let lamports = uris_page_account_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(space));
+        log!(
+            log_level,
+            2,
+            "Refunding {} lamports to payer account",
+            lamports
+        );
+        refund_lamports(uris_page_account_info, payer_account_info, lamports)?;
+    }
+    uris_page
+        .serialize(&mut &mut uris_page_account_info.data.borrow_mut()[..])
+        .error_log("Error: Failed to serialize into uris page")?;
+    Ok(())
+}
+
+///Moves lamports out of a program owned account.
+pub fn refund_lamports(
+    from_account_info: &AccountInfo,
+    to_account_info: &AccountInfo,
+    lamports: u64,
+) -> ProgramResult {
+    **from_account_info.lamports.borrow_mut() = from_account_info
+        .lamports()
+        .checked_sub(lamports)
+        .error_log("Error: refunded account lamports decrease")?;
+    **to_account_info.lamports.borrow_mut() = to_account_info
+        .lamports()
+        .checked_add(lamports)
+        .error_log("Error: payer lamports increase")?;
+    Ok(())
+}
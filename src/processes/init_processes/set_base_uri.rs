@@ -0,0 +1,75 @@
+use crate::{
+    log,
+    processes::init_processes::{
+        upload_uris::assert_upload_authority, upload_uris_page::grow_account,
+    },
+    state::{constants::*, UrisAccount, ValidatorConfig},
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Sets the prefix prepended to every stored uri, so uploads only carry content identifiers.
+pub fn set_base_uri(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    base_uri: String,
+    log_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+    let upload_authority_account_info = next_account_info(account_info_iter)?;
+    let uploaders_account_info = next_account_info(account_info_iter)?;
+
+    upload_authority_account_info
+        .assert_signer()
+        .error_log("Error: Upload authority account is not a signer")?;
+    uris_account_info
+        .assert_owner(program_id)
+        .error_log("Error: uris_account is not owned by the program")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: uris_account is not the uris account")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+
+    let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    assert_upload_authority(
+        program_id,
+        &config,
+        uploaders_account_info,
+        upload_authority_account_info,
+        None,
+    )?;
+
+    let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    uris_account_data.assert_not_frozen()?;
+    log!(log_level, 2, "Setting the base uri to {} ...", base_uri);
+    uris_account_data.base_uri = base_uri;
+    uris_account_data
+        .validate_data()
+        .error_log("Error: invalid base uri")?;
+
+    grow_account(
+        payer_account_info,
+        uris_account_info,
+        uris_account_data.get_space(),
+    )?;
+    uris_account_data
+        .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
+        .error_log("Error: Failed to serialize uris account")?;
+    log!(log_level, 2, "Base uri set !!!");
+    Ok(())
+}
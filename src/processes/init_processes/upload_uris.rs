@@ -1,10 +1,7 @@
 use crate::{
     error::InglError,
     log,
-    state::{
-        constants::{team::UPLOADERS, *},
-        UrisAccount, ValidatorConfig,
-    },
+    state::{constants::*, UploadersAccount, UrisAccount, ValidatorConfig},
     utils::{AccountInfoHelpers, ResultExt},
 };
 
@@ -32,6 +29,7 @@ pub fn upload_uris(
     let config_account_info = next_account_info(account_info_iter)?;
     let uris_account_info = next_account_info(account_info_iter)?;
     let upload_authority_account_info = next_account_info(account_info_iter)?;
+    let uploaders_account_info = next_account_info(account_info_iter)?;
 
     upload_authority_account_info
         .assert_signer()
@@ -50,16 +48,16 @@ pub fn upload_uris(
         .error_log("Error: Config account is not the config account")?;
 
     let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
-    match upload_authority_account_info.assert_key_match(&config.validator_id) {
-        Ok(_) => (),
-        Err(_) => {
-            if !UPLOADERS.contains(upload_authority_account_info.key) {
-                Err(InglError::AddressMismatch.utilize("Error: Upload authority account is not the validator_id, or temporarily authorized uploader"))?
-            }
-        }
-    }
+    assert_upload_authority(
+        program_id,
+        &config,
+        uploaders_account_info,
+        upload_authority_account_info,
+        Some(rarity),
+    )?;
 
     let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    uris_account_data.assert_not_frozen()?;
     log!(
         log_level,
         0,
@@ -111,3 +109,34 @@ pub fn upload_uris(
         .error_log("Error: Failed to serialize config")?;
     Ok(())
 }
+
+///Asserts that the upload authority is the validator_id, or an authorized uploader whose scope covers the rarity.
+///A None rarity is for changes spanning all rarities, which only unscoped uploaders may make.
+pub fn assert_upload_authority(
+    program_id: &Pubkey,
+    config: &ValidatorConfig,
+    uploaders_account_info: &AccountInfo,
+    upload_authority_account_info: &AccountInfo,
+    rarity: Option<u8>,
+) -> ProgramResult {
+    if *upload_authority_account_info.key == config.validator_id {
+        return Ok(());
+    }
+    uploaders_account_info
+        .assert_seed(program_id, &[UPLOADERS_SEED])
+        .error_log("Error: uploaders_account is not the uploaders account")?;
+    let uploaders_data = UploadersAccount::parse(uploaders_account_info, program_id).error_log(
+        "Error: Upload authority account is not the validator_id, and no uploaders are authorized",
+    )?;
+    let uploader = match uploaders_data.get_uploader(upload_authority_account_info.key) {
+        Some(uploader) => uploader,
+        None => Err(InglError::AddressMismatch.utilize(
+            "Error: Upload authority account is not the validator_id, or an authorized uploader",
+        ))?,
+    };
+    if !uploader.is_in_scope(rarity) {
+        Err(InglError::AddressMismatch
+            .utilize("Error: Upload authority is not authorized for this rarity"))?
+    }
+    Ok(())
+}
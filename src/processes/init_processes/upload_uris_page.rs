@@ -0,0 +1,168 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::init_processes::upload_uris::assert_upload_authority,
+    state::{constants::*, UrisAccount, UrisPage, ValidatorConfig},
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+///Appends uris to a page of a rarity, creating the page if it is the next one.
+pub fn upload_uris_page(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rarity: u8,
+    page: u16,
+    uris: Vec<String>,
+    log_level: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+    let upload_authority_account_info = next_account_info(account_info_iter)?;
+    let uploaders_account_info = next_account_info(account_info_iter)?;
+    let uris_page_account_info = next_account_info(account_info_iter)?;
+
+    upload_authority_account_info
+        .assert_signer()
+        .error_log("Error: Upload authority account is not a signer")?;
+    uris_account_info
+        .assert_owner(program_id)
+        .error_log("Error: uris_account is not owned by the program")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: uris_account is not the uris account")?;
+    config_account_info
+        .assert_owner(program_id)
+        .error_log("Error: Config account is not owned by the program")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: Config account is not the config account")?;
+    let (uris_page_key, uris_page_bump) = uris_page_account_info
+        .assert_seed(program_id, &[URIS_PAGE_KEY, &[rarity], &page.to_be_bytes()])
+        .error_log("Error: uris_page_account_info pda assertion")?;
+
+    let config = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    assert_upload_authority(
+        program_id,
+        &config,
+        uploaders_account_info,
+        upload_authority_account_info,
+        Some(rarity),
+    )?;
+
+    let mut uris_account_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    uris_account_data.assert_not_frozen()?;
+    if rarity as usize >= uris_account_data.rarities.len() {
+        Err(InglError::InvalidUrisAccountData.utilize("Rarity is out of bounds"))?
+    }
+    if uris.is_empty() {
+        Err(InglError::InvalidData.utilize("No uris to upload"))?
+    }
+    let page_count = uris_account_data.get_page_count(rarity);
+    if page > page_count {
+        Err(InglError::InvalidData.utilize("Pages must be uploaded in order"))?
+    }
+
+    log!(
+        log_level,
+        0,
+        "Uploading {} URIs to page {} of rarity {}",
+        uris.len(),
+        page,
+        rarity
+    );
+    let mut uris_page = if page == page_count {
+        log!(log_level, 2, "Creating page {} ...", page);
+        let uris_page = UrisPage {
+            validation_phrase: URIS_PAGE_VAL_PHRASE,
+            rarity,
+            page,
+            uris: Vec::new(),
+        };
+        let space = uris_page.get_space();
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account_info.key,
+                &uris_page_key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer_account_info.clone(), uris_page_account_info.clone()],
+            &[&[
+                URIS_PAGE_KEY,
+                &[rarity],
+                &page.to_be_bytes(),
+                &[uris_page_bump],
+            ]],
+        )
+        .error_log("Error: Failed to create uris_page_account_info")?;
+
+        let rarity_count = uris_account_data.rarities.len();
+        uris_account_data.uri_pages.resize(rarity_count, 0);
+        uris_account_data.uri_pages[rarity as usize] = page_count + 1;
+        grow_account(
+            payer_account_info,
+            uris_account_info,
+            uris_account_data.get_space(),
+        )?;
+        uris_account_data
+            .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
+            .error_log("Error: Failed to serialize uris account")?;
+        uris_page
+    } else {
+        UrisPage::parse(uris_page_account_info, program_id)?
+    };
+    uris_page.uris.extend(uris);
+
+    let space = uris_page.get_space();
+    if space > MAX_URIS_PAGE_SPACE {
+        Err(InglError::UrisAccountTooBig.utilize(
+            "Uploaded too many images for this page. Consider uploading to the next page",
+        ))?
+    }
+    grow_account(payer_account_info, uris_page_account_info, space)?;
+    uris_page
+        .serialize(&mut &mut uris_page_account_info.data.borrow_mut()[..])
+        .error_log("Error: Failed to serialize uris page")?;
+    log!(log_level, 2, "Uploaded URIs to page {} !!", page);
+    Ok(())
+}
+
+///Tops up the rent of an account from the payer, then reallocates it to the given space.
+pub fn grow_account<'a>(
+    payer_account_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    space: usize,
+) -> ProgramResult {
+    let lamports: i128 =
+        Rent::get()?.minimum_balance(space) as i128 - account_info.lamports() as i128;
+    if lamports > 0 {
+        invoke(
+            &system_instruction::transfer(
+                payer_account_info.key,
+                account_info.key,
+                lamports as u64,
+            ),
+            &[payer_account_info.clone(), account_info.clone()],
+        )
+        .error_log("Error: Failed to transfer rent lamports")?;
+    }
+    account_info
+        .realloc(space, false)
+        .error_log("Error: Failed to realloc account")?;
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::{
+        marketplace_processes::delist_nft::{close_listing, refund_bid, release_escrow},
+        rewards_processes::nft_withdraw::{
+            has_unclaimed_rewards, settle_rewards, RewardSettlement,
+        },
+    },
+    state::{constants::*, FundsLocation, GeneralData, Listing, NftData, ValidatorConfig},
+    utils::{get_clock_data, get_rent_data, AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+    system_instruction,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+///Sells a listed gem, either to the payer at the listing's price or, when accept_bid is set, to the highest bidder at their bid.
+///The seller is paid the sale price minus the creator royalties, which go to the vote account,
+///and the rewards accrued by a delegated gem up to the sale are paid to the seller,
+///or credited to the claim of a previous holder the seller got the gem from without settling it.
+pub fn buy_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    accept_bid: bool,
+    log_level: u8,
+    clock_is_from_account: bool,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Buy nft ... accept_bid: {}", accept_bid);
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let buyer_account_info = next_account_info(account_info_iter)?;
+    let seller_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let listing_account_info = next_account_info(account_info_iter)?;
+    let escrow_token_account_info = next_account_info(account_info_iter)?;
+    let buyer_token_account_info = next_account_info(account_info_iter)?;
+    let reward_claim_account_info = next_account_info(account_info_iter)?;
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_account_info = next_account_info(account_info_iter)?;
+    let spl_ata_program_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error @ general_account_info pda assertion")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error @ config_account_info pda assertion")?;
+    nft_account_data_info
+        .assert_seed(
+            program_id,
+            &[NFT_ACCOUNT_CONST, mint_account_info.key.as_ref()],
+        )
+        .error_log("Error @ nft_account_data_info pda assertion")?;
+    let (_authorized_withdrawer, authorized_withdrawer_bump) = authorized_withdrawer_info
+        .assert_seed(program_id, &[AUTHORIZED_WITHDRAWER_KEY])
+        .error_log("Error @ authorized_withdrawer_info pda assertion")?;
+    let (_listing_key, listing_bump) = listing_account_info
+        .assert_seed(program_id, &[LISTING_KEY, mint_account_info.key.as_ref()])
+        .error_log("Error @ listing_account_info pda assertion")?;
+    buyer_token_account_info
+        .assert_key_match(&get_associated_token_address(
+            buyer_account_info.key,
+            mint_account_info.key,
+        ))
+        .error_log("Error @ buyer_token_account_info key assertion")?;
+    spl_ata_program_account_info
+        .assert_key_match(&spl_associated_token_account::id())
+        .error_log("Error @ spl_ata_program_account_info key assertion")?;
+
+    let general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error @ nft_account_data_info deserialization")?;
+    let listing = Listing::parse(listing_account_info, program_id)
+        .error_log("Error @ listing_account_info deserialization")?;
+    vote_account_info
+        .assert_key_match(&config_data.vote_account)
+        .error_log("Error @ vote_account_info key assertion")?;
+    seller_account_info
+        .assert_key_match(&listing.seller)
+        .error_log("Error @ seller_account_info key assertion")?;
+
+    let price = if accept_bid {
+        payer_account_info
+            .assert_key_match(&listing.seller)
+            .error_log("Error: Only the seller can accept a bid")?;
+        let bid = listing
+            .highest_bid
+            .error_log("Error: The listing has no bid to accept")?;
+        buyer_account_info
+            .assert_key_match(&bid.bidder)
+            .error_log("Error @ buyer_account_info must be the highest bidder")?;
+        bid.amount
+    } else {
+        buyer_account_info
+            .assert_key_match(payer_account_info.key)
+            .error_log("Error @ buyer_account_info must be the payer")?;
+        listing.price
+    };
+    if *buyer_account_info.key == listing.seller {
+        Err(InglError::InvalidData.utilize("The seller can't buy their own gem"))?
+    }
+    let royalty = listing.get_royalty(price, &config_data);
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    log!(
+        log_level,
+        2,
+        "Paying the sale of {} lamports, royalty: {}",
+        price,
+        royalty
+    );
+    if accept_bid {
+        // The bid is held by the listing account, the seller's proceeds are sent with its closure below.
+        **listing_account_info.lamports.borrow_mut() = listing_account_info
+            .lamports()
+            .checked_sub(royalty)
+            .error_log("Error @ listing lamports decrease")?;
+        **vote_account_info.lamports.borrow_mut() = vote_account_info
+            .lamports()
+            .checked_add(royalty)
+            .error_log("Error @ vote account lamports increase")?;
+    } else {
+        invoke(
+            &system_instruction::transfer(payer_account_info.key, vote_account_info.key, royalty),
+            &[payer_account_info.clone(), vote_account_info.clone()],
+        )
+        .error_log("Error @ royalty transfer to the vote account")?;
+        invoke(
+            &system_instruction::transfer(
+                payer_account_info.key,
+                seller_account_info.key,
+                price - royalty,
+            ),
+            &[payer_account_info.clone(), seller_account_info.clone()],
+        )
+        .error_log("Error @ proceeds transfer to the seller")?;
+        if let Some(bid) = listing.highest_bid {
+            let bidder_account_info = next_account_info(account_info_iter)?;
+            refund_bid(listing_account_info, bidder_account_info, &bid)?;
+        }
+    }
+
+    if let FundsLocation::Delegated = nft_data.funds_location {
+        if has_unclaimed_rewards(&nft_data, &general_data)? {
+            log!(log_level, 2, "Settling the gem's accrued rewards ...");
+            let new_space = nft_account_data_info.data.borrow().len() + 8;
+            let lamports = rent_data
+                .minimum_balance(new_space)
+                .checked_sub(rent_data.minimum_balance(nft_account_data_info.data.borrow().len()))
+                .unwrap();
+            invoke(
+                &system_instruction::transfer(
+                    payer_account_info.key,
+                    nft_account_data_info.key,
+                    lamports,
+                ),
+                &[payer_account_info.clone(), nft_account_data_info.clone()],
+            )
+            .error_log("Error @ transfer for reallocating nft_account_data_info")?;
+            nft_account_data_info
+                .realloc(new_space, false)
+                .error_log("Error: @realloc of nft_account_data_info")?;
+
+            let settlement = RewardSettlement {
+                mint: mint_account_info.key,
+                reward_claim: reward_claim_account_info,
+                authorized_withdrawer: authorized_withdrawer_info,
+                authorized_withdrawer_bump,
+                epoch: clock_data.epoch,
+                rent_data: &rent_data,
+            };
+            let seller_reward = settle_rewards(
+                program_id,
+                &mut nft_data,
+                &general_data,
+                &listing.seller,
+                payer_account_info,
+                &settlement,
+                log_level,
+            )
+            .error_log("Error @ settling the gem's rewards")?;
+            invoke_signed(
+                &system_instruction::transfer(
+                    authorized_withdrawer_info.key,
+                    seller_account_info.key,
+                    seller_reward,
+                ),
+                &[
+                    authorized_withdrawer_info.clone(),
+                    seller_account_info.clone(),
+                ],
+                &[&[AUTHORIZED_WITHDRAWER_KEY, &[authorized_withdrawer_bump]]],
+            )
+            .error_log("Error @ rewards transfer to the seller")?;
+            log!(log_level, 2, "Settled {} to the seller", seller_reward);
+        }
+    }
+    nft_data.last_known_owner = Some(*buyer_account_info.key);
+    nft_data.last_owner_seen_epoch = Some(clock_data.epoch);
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("Error @ nft_data serialization")?;
+
+    log!(log_level, 2, "Releasing the gem to the buyer ...");
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            payer_account_info.key,
+            buyer_account_info.key,
+            mint_account_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            payer_account_info.clone(),
+            buyer_token_account_info.clone(),
+            buyer_account_info.clone(),
+            mint_account_info.clone(),
+            system_program_account_info.clone(),
+            spl_token_program_account_info.clone(),
+        ],
+    )
+    .error_log("Error @ buyer_token_account_info creation")?;
+    release_escrow(
+        listing_account_info,
+        listing_bump,
+        mint_account_info,
+        escrow_token_account_info,
+        buyer_token_account_info,
+        seller_account_info,
+        spl_token_program_account_info,
+    )?;
+    close_listing(listing_account_info, seller_account_info)?;
+
+    log!(log_level, 4, "Sold nft for {} lamports!!!", price);
+    Ok(())
+}
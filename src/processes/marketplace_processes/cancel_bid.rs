@@ -0,0 +1,48 @@
+use crate::{
+    log,
+    processes::marketplace_processes::delist_nft::refund_bid,
+    state::{constants::*, Listing},
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Withdraws the highest bid of a listing, refunding its escrowed lamports to the bidder.
+pub fn cancel_bid(program_id: &Pubkey, accounts: &[AccountInfo], log_level: u8) -> ProgramResult {
+    log!(log_level, 4, "Cancel bid ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let listing_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    listing_account_info
+        .assert_seed(program_id, &[LISTING_KEY, mint_account_info.key.as_ref()])
+        .error_log("Error @ listing_account_info pda assertion")?;
+    let mut listing = Listing::parse(listing_account_info, program_id)
+        .error_log("Error @ listing_account_info deserialization")?;
+    let bid = listing
+        .highest_bid
+        .error_log("Error: The listing has no bid to cancel")?;
+    payer_account_info
+        .assert_key_match(&bid.bidder)
+        .error_log("Error: Only the highest bidder can cancel their bid")?;
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    refund_bid(listing_account_info, payer_account_info, &bid)?;
+    listing.highest_bid = None;
+    listing
+        .serialize(&mut &mut listing_account_info.data.borrow_mut()[..])
+        .error_log("Error @ listing serialization")?;
+
+    log!(log_level, 4, "Cancelled bid of {} lamports!!!", bid.amount);
+    Ok(())
+}
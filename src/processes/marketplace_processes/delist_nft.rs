@@ -0,0 +1,172 @@
+use crate::{
+    log,
+    state::{constants::*, Bid, Listing},
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+///Takes a gem off the marketplace, returning it to its seller and refunding the highest bidder if any.
+pub fn delist_nft(program_id: &Pubkey, accounts: &[AccountInfo], log_level: u8) -> ProgramResult {
+    log!(log_level, 4, "Delist nft ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let listing_account_info = next_account_info(account_info_iter)?;
+    let escrow_token_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_account_info = next_account_info(account_info_iter)?;
+    let spl_ata_program_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    spl_token_program_account_info
+        .assert_key_match(&spl_token::id())
+        .error_log("Error @ spl_token_program_account_info key assertion")?;
+    spl_ata_program_account_info
+        .assert_key_match(&spl_associated_token_account::id())
+        .error_log("Error @ spl_ata_program_account_info key assertion")?;
+    let (_listing_key, listing_bump) = listing_account_info
+        .assert_seed(program_id, &[LISTING_KEY, mint_account_info.key.as_ref()])
+        .error_log("Error @ listing_account_info pda assertion")?;
+    let listing = Listing::parse(listing_account_info, program_id)
+        .error_log("Error @ listing_account_info deserialization")?;
+    payer_account_info
+        .assert_key_match(&listing.seller)
+        .error_log("Error: Only the seller can delist the gem")?;
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            payer_account_info.key,
+            payer_account_info.key,
+            mint_account_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            payer_account_info.clone(),
+            associated_token_account_info.clone(),
+            payer_account_info.clone(),
+            mint_account_info.clone(),
+            system_program_account_info.clone(),
+            spl_token_program_account_info.clone(),
+        ],
+    )
+    .error_log("Error @ associated_token_account_info creation")?;
+    release_escrow(
+        listing_account_info,
+        listing_bump,
+        mint_account_info,
+        escrow_token_account_info,
+        associated_token_account_info,
+        payer_account_info,
+        spl_token_program_account_info,
+    )?;
+
+    if let Some(bid) = listing.highest_bid {
+        let bidder_account_info = next_account_info(account_info_iter)?;
+        refund_bid(listing_account_info, bidder_account_info, &bid)?;
+    }
+    close_listing(listing_account_info, payer_account_info)?;
+
+    log!(log_level, 4, "Delisted nft!!!");
+    Ok(())
+}
+
+///Moves the escrowed gem to the destination token account, then closes the escrow, sending its rent to the seller.
+pub fn release_escrow<'a>(
+    listing_account_info: &AccountInfo<'a>,
+    listing_bump: u8,
+    mint_account_info: &AccountInfo<'a>,
+    escrow_token_account_info: &AccountInfo<'a>,
+    destination_token_account_info: &AccountInfo<'a>,
+    seller_account_info: &AccountInfo<'a>,
+    spl_token_program_account_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    escrow_token_account_info
+        .assert_key_match(&get_associated_token_address(
+            listing_account_info.key,
+            mint_account_info.key,
+        ))
+        .error_log("Error @ escrow_token_account_info key assertion")?;
+    let listing_seeds: &[&[u8]] = &[LISTING_KEY, mint_account_info.key.as_ref(), &[listing_bump]];
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            escrow_token_account_info.key,
+            destination_token_account_info.key,
+            listing_account_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            escrow_token_account_info.clone(),
+            destination_token_account_info.clone(),
+            listing_account_info.clone(),
+            spl_token_program_account_info.clone(),
+        ],
+        &[listing_seeds],
+    )
+    .error_log("Error @ gem transfer out of escrow")?;
+    invoke_signed(
+        &spl_token::instruction::close_account(
+            &spl_token::id(),
+            escrow_token_account_info.key,
+            seller_account_info.key,
+            listing_account_info.key,
+            &[],
+        )?,
+        &[
+            escrow_token_account_info.clone(),
+            seller_account_info.clone(),
+            listing_account_info.clone(),
+            spl_token_program_account_info.clone(),
+        ],
+        &[listing_seeds],
+    )
+    .error_log("Error @ escrow_token_account_info closing")?;
+    Ok(())
+}
+
+///Returns the lamports of an outbid or cancelled bid, held by the listing account, to its bidder.
+pub fn refund_bid(
+    listing_account_info: &AccountInfo,
+    bidder_account_info: &AccountInfo,
+    bid: &Bid,
+) -> ProgramResult {
+    bidder_account_info
+        .assert_key_match(&bid.bidder)
+        .error_log("Error @ bidder_account_info key assertion")?;
+    **listing_account_info.lamports.borrow_mut() = listing_account_info
+        .lamports()
+        .checked_sub(bid.amount)
+        .error_log("Error @ listing lamports decrease")?;
+    **bidder_account_info.lamports.borrow_mut() = bidder_account_info
+        .lamports()
+        .checked_add(bid.amount)
+        .error_log("Error @ bidder lamports increase")?;
+    Ok(())
+}
+
+///Closes the listing account, sending its remaining lamports to the seller.
+pub fn close_listing(
+    listing_account_info: &AccountInfo,
+    seller_account_info: &AccountInfo,
+) -> ProgramResult {
+    let dest_starting_lamports = seller_account_info.lamports();
+    **seller_account_info.lamports.borrow_mut() = dest_starting_lamports
+        .checked_add(listing_account_info.lamports())
+        .error_log("Error @ seller lamports increase")?;
+    **listing_account_info.lamports.borrow_mut() = 0;
+    listing_account_info.data.borrow_mut().fill(0);
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use crate::{
+    error::InglError,
+    log,
+    state::{constants::*, Listing, NftData},
+    utils::{
+        create_pda_account, get_clock_data, get_rent_data, is_token_2022_gem, verify_nft_ownership,
+        AccountInfoHelpers, ResultExt,
+    },
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::{error::TokenError, state::Account};
+
+///Escrows a gem in its listing's associated token account and puts it on sale at the given price.
+///The gem's funds are left untouched, so a delegated gem keeps earning rewards while listed.
+///Programmable gems can't be moved by a plain token transfer, so they are rejected with ProgrammableNftNotListable.
+///Token-2022 gems are rejected with Token2022GemNotListable, as any marketplace can trade them without losing their rewards.
+pub fn list_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+    log_level: u8,
+    clock_is_from_account: bool,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "List nft ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let listing_account_info = next_account_info(account_info_iter)?;
+    let escrow_token_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_account_info = next_account_info(account_info_iter)?;
+    let spl_ata_program_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )
+    .error_log("Error @ nft ownership verification")?;
+    if is_token_2022_gem(mint_account_info) {
+        Err(InglError::Token2022GemNotListable.utilize("mint_account_info.owner"))?
+    }
+    system_program_account_info
+        .assert_key_match(&solana_program::system_program::id())
+        .error_log("Error @ system_program_account_info key assertion")?;
+    spl_token_program_account_info
+        .assert_key_match(&spl_token::id())
+        .error_log("Error @ spl_token_program_account_info key assertion")?;
+    spl_ata_program_account_info
+        .assert_key_match(&spl_associated_token_account::id())
+        .error_log("Error @ spl_ata_program_account_info key assertion")?;
+    let (listing_key, listing_bump) = listing_account_info
+        .assert_seed(program_id, &[LISTING_KEY, mint_account_info.key.as_ref()])
+        .error_log("Error @ listing_account_info pda assertion")?;
+    escrow_token_account_info
+        .assert_key_match(&get_associated_token_address(
+            &listing_key,
+            mint_account_info.key,
+        ))
+        .error_log("Error @ escrow_token_account_info key assertion")?;
+
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error @ nft_account_data_info deserialization")?;
+    if nft_data.is_programmable {
+        Err(InglError::ProgrammableNftNotListable.utilize("nft_data.is_programmable"))?
+    }
+    if nft_data.lock_until.is_some() {
+        Err(InglError::NftLocked.utilize("Unlock the gem before listing it"))?
+    }
+    if Account::unpack(&associated_token_account_info.data.borrow())
+        .error_log("Error @ associated_token_account_info unpacking")?
+        .is_frozen()
+    {
+        Err(TokenError::AccountFrozen).error_log("Gem's token account is frozen")?
+    }
+    if price == 0 {
+        Err(InglError::InvalidData.utilize("Listing price must be greater than 0"))?
+    }
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    create_pda_account(
+        program_id,
+        payer_account_info,
+        None,
+        listing_account_info,
+        &[LISTING_KEY, mint_account_info.key.as_ref(), &[listing_bump]],
+        Listing::get_space(),
+        &rent_data,
+    )
+    .error_log("Error @ listing_account_info creation")?;
+
+    log!(log_level, 2, "Escrowing the gem ...");
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            payer_account_info.key,
+            &listing_key,
+            mint_account_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            payer_account_info.clone(),
+            escrow_token_account_info.clone(),
+            listing_account_info.clone(),
+            mint_account_info.clone(),
+            system_program_account_info.clone(),
+            spl_token_program_account_info.clone(),
+        ],
+    )
+    .error_log("Error @ escrow_token_account_info creation")?;
+    invoke(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            associated_token_account_info.key,
+            escrow_token_account_info.key,
+            payer_account_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            associated_token_account_info.clone(),
+            escrow_token_account_info.clone(),
+            payer_account_info.clone(),
+        ],
+    )
+    .error_log("Error @ gem transfer to escrow")?;
+    log!(log_level, 2, "Gem escrowed!!!");
+
+    // Rewards accrued while listed are owed to the seller, unless a previous holder is still owed theirs.
+    if nft_data
+        .last_known_owner
+        .map_or(true, |owner| owner == *payer_account_info.key)
+    {
+        nft_data.last_known_owner = Some(*payer_account_info.key);
+        nft_data.last_owner_seen_epoch = Some(clock_data.epoch);
+        nft_data
+            .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+            .error_log("Error @ nft_data serialization")?;
+    }
+
+    Listing {
+        validation_phrase: LISTING_VAL_PHRASE,
+        seller: *payer_account_info.key,
+        mint: *mint_account_info.key,
+        price,
+        highest_bid: None,
+    }
+    .serialize(&mut &mut listing_account_info.data.borrow_mut()[..])
+    .error_log("Error @ listing serialization")?;
+
+    log!(log_level, 4, "Listed nft at {} lamports!!!", price);
+    Ok(())
+}
@@ -0,0 +1,6 @@
+pub mod buy_nft;
+pub mod cancel_bid;
+pub mod delist_nft;
+pub mod list_nft;
+pub mod place_bid;
+pub mod quote_listing;
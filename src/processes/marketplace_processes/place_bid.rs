@@ -0,0 +1,74 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::marketplace_processes::delist_nft::refund_bid,
+    state::{constants::*, Bid, Listing},
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+///Escrows a bid below the listing's price in the listing account, refunding the bid it outbids.
+pub fn place_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    log_level: u8,
+) -> ProgramResult {
+    log!(log_level, 4, "Place bid ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let listing_account_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    listing_account_info
+        .assert_seed(program_id, &[LISTING_KEY, mint_account_info.key.as_ref()])
+        .error_log("Error @ listing_account_info pda assertion")?;
+    let mut listing = Listing::parse(listing_account_info, program_id)
+        .error_log("Error @ listing_account_info deserialization")?;
+    if *payer_account_info.key == listing.seller {
+        Err(InglError::InvalidData.utilize("The seller can't bid on their own gem"))?
+    }
+    if amount >= listing.price
+        || amount
+            <= listing
+                .highest_bid
+                .map(|bid| bid.amount)
+                .unwrap_or_default()
+    {
+        Err(InglError::InvalidBid.utilize("bid amount"))?
+    }
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    invoke(
+        &system_instruction::transfer(payer_account_info.key, listing_account_info.key, amount),
+        &[payer_account_info.clone(), listing_account_info.clone()],
+    )
+    .error_log("Error @ bid transfer to listing_account_info")?;
+    if let Some(outbid) = listing.highest_bid {
+        let outbid_account_info = next_account_info(account_info_iter)?;
+        refund_bid(listing_account_info, outbid_account_info, &outbid)?;
+    }
+
+    listing.highest_bid = Some(Bid {
+        bidder: *payer_account_info.key,
+        amount,
+    });
+    listing
+        .serialize(&mut &mut listing_account_info.data.borrow_mut()[..])
+        .error_log("Error @ listing serialization")?;
+
+    log!(log_level, 4, "Placed bid of {} lamports!!!", amount);
+    Ok(())
+}
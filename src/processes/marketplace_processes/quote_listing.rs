@@ -0,0 +1,75 @@
+use crate::{
+    log,
+    processes::rewards_processes::nft_withdraw::{calculate_total_reward, has_unclaimed_rewards},
+    state::{
+        constants::*, FundsLocation, GeneralData, Listing, ListingQuote, NftData, ValidatorConfig,
+    },
+    utils::{AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::set_return_data,
+    pubkey::Pubkey,
+};
+
+///Sets the return data to a ListingQuote, comparing a listing's price to the rewards its gem will settle to the seller.
+pub fn quote_listing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+) -> ProgramResult {
+    log!(log_level, 4, "Quote listing ...");
+    let account_info_iter = &mut accounts.iter();
+    let general_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let listing_account_info = next_account_info(account_info_iter)?;
+
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error @ general_account_info pda assertion")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error @ config_account_info pda assertion")?;
+    nft_account_data_info
+        .assert_seed(
+            program_id,
+            &[NFT_ACCOUNT_CONST, mint_account_info.key.as_ref()],
+        )
+        .error_log("Error @ nft_account_data_info pda assertion")?;
+    listing_account_info
+        .assert_seed(program_id, &[LISTING_KEY, mint_account_info.key.as_ref()])
+        .error_log("Error @ listing_account_info pda assertion")?;
+
+    let general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    let nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error @ nft_account_data_info deserialization")?;
+    let listing = Listing::parse(listing_account_info, program_id)
+        .error_log("Error @ listing_account_info deserialization")?;
+
+    let claimable_rewards = match nft_data.funds_location {
+        FundsLocation::Delegated if has_unclaimed_rewards(&nft_data, &general_data)? => {
+            calculate_total_reward(&nft_data, &general_data, None, log_level)
+                .error_log("Error: @calculate_total_reward")? as u64
+        }
+        _ => 0,
+    };
+    let quote = ListingQuote {
+        price: listing.price,
+        royalty: listing.get_royalty(listing.price, &config_data),
+        highest_bid: listing.highest_bid,
+        claimable_rewards,
+    };
+    log!(log_level, 4, "Listing quote: {:?}", quote);
+    set_return_data(
+        &quote
+            .try_to_vec()
+            .error_log("Error @ listing quote serialization")?,
+    );
+    Ok(())
+}
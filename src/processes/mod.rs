@@ -2,4 +2,5 @@ pub mod init_processes;
 pub mod validator_processes;
 pub mod nft_processes;
 pub mod governance_processes;
-pub mod rewards_processes;
\ No newline at end of file
+pub mod rewards_processes;
+pub mod marketplace_processes;
\ No newline at end of file
@@ -0,0 +1,72 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::init_processes::reset_uris_page::refund_lamports,
+    state::{constants::*, MintPhases, MinterRecord},
+    utils::{get_clock_data, AccountInfoHelpers, ResultExt},
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Closes a minter record once its mint phase is over or its schedule was replaced, returning its rent to the minter.
+pub fn close_minter_record(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Initiating Close minter record ...");
+    let account_info_iter = &mut accounts.iter();
+    let minter_account_info = next_account_info(account_info_iter)?;
+    let mint_phases_account_info = next_account_info(account_info_iter)?;
+    let minter_record_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    minter_account_info
+        .assert_signer()
+        .error_log("Error: Minter account is not a signer")?;
+    mint_phases_account_info
+        .assert_seed(program_id, &[MINT_PHASES_SEED])
+        .error_log("Error @ mint_phases_account_info pda assertion")?;
+
+    let minter_record = MinterRecord::parse(minter_record_account_info, program_id)?;
+    minter_account_info
+        .assert_key_match(&minter_record.minter)
+        .error_log("Error: Minter account is not the minter of the record")?;
+    minter_record_account_info
+        .assert_seed(
+            program_id,
+            &[
+                MINTER_RECORD_KEY,
+                minter_account_info.key.as_ref(),
+                &minter_record.schedule_version.to_be_bytes(),
+                &[minter_record.phase_index],
+            ],
+        )
+        .error_log("Error @ minter_record_account_info pda assertion")?;
+
+    if mint_phases_account_info.owner == program_id {
+        let mint_phases = Box::new(MintPhases::parse(mint_phases_account_info, program_id)?);
+        if mint_phases.schedule_version == minter_record.schedule_version
+            && !mint_phases
+                .is_phase_over(minter_record.phase_index, clock_data.unix_timestamp as u32)
+        {
+            Err(InglError::TooEarly.utilize("The mint phase of this record is not over yet."))?
+        }
+    }
+
+    log!(log_level, 2, "Closing the minter record account ...");
+    refund_lamports(
+        minter_record_account_info,
+        minter_account_info,
+        minter_record_account_info.lamports(),
+    )?;
+    minter_record_account_info.data.borrow_mut().fill(0);
+    log!(log_level, 4, "Minter record closed !!!");
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::{
+    error::InglError,
+    log,
+    state::{constants::*, NftData, ValidatorConfig},
+    utils::{
+        get_clock_data, get_rent_data, verify_nft_ownership, AccountInfoHelpers, OptionExt,
+        ResultExt,
+    },
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    slot_hashes, system_instruction,
+};
+
+///Commits the hash of a holder secret that will be mixed into the NFT's rarity seed at imprint time.
+/// Must be called before the rarity seed slot is reached, i.e. within RARITY_IMPRINT_WAIT_SLOTS slots of the mint.
+/// Once the rarity seed slot has aged out of the SlotHashes sysvar, a new commitment sets a fresh rarity seed slot.
+/// Such a recommit counts as a reroll, so it waits out the rarity reroll cooldown.
+pub fn commit_rarity_seed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commitment: [u8; 32],
+    log_level: u8,
+    clock_is_from_account: bool,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Committing rarity seed ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error: @nft_account_data_info deserialization")?;
+
+    if nft_data.rarity.is_some() {
+        Err(ProgramError::InvalidAccountData).error_log("Rarity has already been imprinted")?
+    }
+    let rarity_seed_slot = nft_data
+        .rarity_seed_slot
+        .error_log("Error: Rarity seed slot can't be None")?;
+    if clock_data.slot > rarity_seed_slot + slot_hashes::MAX_ENTRIES as u64 {
+        config_account_info
+            .assert_seed(program_id, &[INGL_CONFIG_SEED])
+            .error_log("Error: @config_account_info pda assertion")?;
+        let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+        let now = clock_data.unix_timestamp as u32;
+        if let Some(last_reroll_date) = nft_data.last_reroll_date {
+            if now < last_reroll_date + config_data.rarity_reroll_cooldown {
+                Err(InglError::TooEarly.utilize("Rarity reroll cooldown hasn't elapsed yet"))?
+            }
+        }
+        log!(
+            log_level,
+            2,
+            "Rarity seed slot has aged out, setting a fresh one ..."
+        );
+        nft_data.rarity_seed_slot = Some(clock_data.slot + RARITY_IMPRINT_WAIT_SLOTS);
+        nft_data.reroll_count = nft_data
+            .reroll_count
+            .checked_add(1)
+            .error_log("Error @ reroll_count increment")?;
+        nft_data.last_reroll_date = Some(now);
+    } else if nft_data.rarity_commitment.is_some() {
+        Err(ProgramError::InvalidAccountData).error_log("Rarity seed has already been committed")?
+    } else if clock_data.slot >= rarity_seed_slot {
+        Err(InglError::TooLate.utilize("Rarity seed slot has already been reached"))?
+    }
+    nft_data.rarity_commitment = Some(commitment);
+
+    let new_space = nft_data.get_space();
+    if nft_account_data_info.data_len() < new_space {
+        let lamports = rent_data
+            .minimum_balance(new_space)
+            .saturating_sub(nft_account_data_info.lamports());
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    payer_account_info.key,
+                    nft_account_data_info.key,
+                    lamports,
+                ),
+                &[payer_account_info.clone(), nft_account_data_info.clone()],
+            )
+            .error_log(
+                "failed to transfer for reallaocating_nft_account_data_size @system_program invoke",
+            )?;
+        }
+        nft_account_data_info
+            .realloc(new_space, false)
+            .error_log("Error: @realloc of nft_account_data_info")?;
+    }
+
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("Error: @nft_account_data_info serialization")?;
+
+    log!(log_level, 4, "Rarity seed committed !!!");
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::{
     error::InglError,
     log,
-    state::{constants::*, FundsLocation, GeneralData, NftData, ValidatorConfig},
+    state::{constants::*, FundsLocation, GeneralData, NftData, UrisAccount, ValidatorConfig},
     utils::{get_clock_data, verify_nft_ownership, AccountInfoHelpers, OptionExt, ResultExt},
 };
 
@@ -27,6 +27,7 @@ pub fn delegate_gem(
     let nft_account_data_info = next_account_info(account_info_iter)?;
     let associated_token_account_info = next_account_info(account_info_iter)?;
     let general_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
 
@@ -44,6 +45,9 @@ pub fn delegate_gem(
     config_account_info
         .assert_seed(program_id, &[INGL_CONFIG_SEED.as_ref()])
         .error_log("Error: @config_account_info seed assertion")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: @uris_account_info seed assertion")?;
 
     general_account_info
         .assert_owner(program_id)
@@ -57,16 +61,27 @@ pub fn delegate_gem(
 
     let mut general_account_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
     let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    config_data.assert_not_paused()?;
+    let uris_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
 
     general_account_data.total_delegated = general_account_data
         .total_delegated
         .checked_add(config_data.unit_backing)
         .error_log("Error @ general Account Data Delegated Total recalc")?;
+    let weighted_backing =
+        config_data.get_weighted_backing(uris_data.get_reward_weight(nft_account_data.rarity));
+    general_account_data.total_weighted_delegated = general_account_data
+        .total_weighted_delegated
+        .checked_add(weighted_backing)
+        .error_log("Error @ general Account Data Weighted Delegated Total recalc")?;
 
     match nft_account_data.funds_location {
         FundsLocation::Undelegated => {
             nft_account_data.funds_location = FundsLocation::Delegated;
             nft_account_data.last_delegation_epoch = Some(clock_data.epoch);
+            nft_account_data.last_known_owner = Some(*payer_account_info.key);
+            nft_account_data.last_owner_seen_epoch = Some(clock_data.epoch);
+            nft_account_data.weighted_backing = weighted_backing;
         }
         _ => Err(InglError::InvalidFundsLocation.utilize("gem's funds location."))?,
     }
@@ -1,27 +1,47 @@
+use std::slice::Iter;
+
 use crate::{
     error::InglError,
     log,
-    state::{constants::INGL_MINT_AUTHORITY_KEY, NftData, UrisAccount},
-    utils::{get_clock_data, verify_nft_ownership, AccountInfoHelpers, OptionExt, ResultExt},
+    processes::rewards_processes::nft_withdraw::has_unclaimed_rewards,
+    state::{
+        constants::*, get_seed_part, FundsLocation, GeneralData, NftData, UriProof, UrisAccount,
+        UrisPage, UrisRoot, ValidatorConfig, VrfResult, VrfState,
+    },
+    token_2022::{update_token_metadata_field, TokenMetadataField},
+    utils::{
+        get_clock_data, get_slot_hash, is_token_2022_gem, set_programmable_lock,
+        set_token_2022_freeze, verify_merkle_proof, verify_nft_ownership, AccountInfoHelpers,
+        GemAccounts, OptionExt, ProgrammableAccounts, PubkeyHelpers, ResultExt,
+    },
 };
 
-use arrayref::array_ref;
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use mpl_token_metadata::state::{DataV2, Metadata, PREFIX};
+use mpl_token_metadata::{
+    instruction::{
+        builders::UpdateBuilder, CollectionDetailsToggle, CollectionToggle, InstructionBuilder,
+        RuleSetToggle, UpdateArgs, UsesToggle,
+    },
+    state::{Data, DataV2, Metadata, PREFIX},
+};
 
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    clock::Slot,
     entrypoint::ProgramResult,
+    hash::hashv,
     program::invoke_signed,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar,
 };
 
 pub fn process_imprint_rarity(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    reveal: Option<[u8; 32]>,
+    uri_proof: Option<UriProof>,
     log_level: u8,
     clock_is_from_account: bool,
 ) -> ProgramResult {
@@ -37,7 +57,17 @@ pub fn process_imprint_rarity(
     let ingl_config_account_info = next_account_info(account_info_iter)?;
     let uris_account_info = next_account_info(account_info_iter)?;
     let token_program_account_info = next_account_info(account_info_iter)?;
-    let recent_blockhashes_account_info = next_account_info(account_info_iter)?;
+    let slot_hashes_account_info = next_account_info(account_info_iter)?;
+    let vrf_state_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_data = NftData::validate(
+        NftData::deserialize(&mut &nft_account_info.data.borrow()[..])
+            .error_log("Error: Error desirializing NFT account data")?,
+    )
+    .error_log("Error: Invalid NFT Account")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
 
     log!(log_level, 0, "Done retrieving accounts infos");
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
@@ -56,42 +86,47 @@ pub fn process_imprint_rarity(
     nft_account_info
         .assert_owner(&program_id)
         .error_log("Error: @gem_account_info ownership")?;
-    mint_account_info
-        .assert_owner(&spl_token::id())
-        .error_log("Error: @mint_account_info ownership")?;
-    nft_edition_account_info
-        .assert_owner(&mpl_token_metadata::id())
-        .error_log("Error: @nft_edition_account_info ownership")?;
-    metadata_account_info
-        .assert_owner(&mpl_token_metadata::id())
-        .error_log("Error: @metadata_account_info ownership")?;
-    recent_blockhashes_account_info
-        .assert_owner(&solana_program::sysvar::id())
-        .error_log("Error: @recent_blockhashes_account_info ownership")?;
-    recent_blockhashes_account_info
-        .assert_key_match(&sysvar::slot_hashes::ID)
-        .error_log("Error: @recent_blockhashes_account_info key")?;
+    let is_token_2022 = is_token_2022_gem(mint_account_info);
+    if !is_token_2022 {
+        mint_account_info
+            .assert_owner(&spl_token::id())
+            .error_log("Error: @mint_account_info ownership")?;
+        nft_edition_account_info
+            .assert_owner(&mpl_token_metadata::id())
+            .error_log("Error: @nft_edition_account_info ownership")?;
+        metadata_account_info
+            .assert_owner(&mpl_token_metadata::id())
+            .error_log("Error: @metadata_account_info ownership")?;
+    }
     ingl_config_account_info
         .assert_owner(&program_id)
         .error_log("Error: Ingl config account is not owned by the program")?;
+    ingl_config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: @ingl_config_account_info pda assertion")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: @uris_account_info pda assertion")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error: @general_account_info pda assertion")?;
 
-    let mut nft_data = NftData::validate(
-        NftData::deserialize(&mut &nft_account_info.data.borrow()[..])
-            .error_log("Error: Error desirializing NFT account data")?,
-    )
-    .error_log("Error: Invalid NFT Account")?;
+    let config_data = Box::new(ValidatorConfig::parse(
+        ingl_config_account_info,
+        program_id,
+    )?);
+    let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
 
     log!(log_level, 0, "Checking deserialized data...");
-    if (clock_data.slot as u64)
-        < nft_data
-            .rarity_seed_slot
-            .error_log("Error: Rarity seed slot can't be None")?
-    {
-        Err(InglError::TooEarly.utilize("imprint_rarity"))?
-    }
     if let Some(_) = nft_data.rarity {
         Err(ProgramError::InvalidAccountData).error_log("Rarity has already been imprinted")?
     }
+    let is_delegated = matches!(nft_data.funds_location, FundsLocation::Delegated);
+    // The reward weight changes with the rarity, so rewards accrued under the base weight must be withdrawn first.
+    if is_delegated && has_unclaimed_rewards(&nft_data, &general_data)? {
+        Err(InglError::InvalidData
+            .utilize("Withdraw the accrued rewards before imprinting the rarity"))?
+    }
     log!(log_level, 2, "Done Checking deserialized data !!!");
 
     let (mint_authority_key, mint_authority_bump) = mint_authority_account_info
@@ -107,9 +142,11 @@ pub fn process_imprint_rarity(
         ],
         &mpl_token_metadata_id,
     );
-    nft_edition_account_info
-        .assert_key_match(&nft_edition_key)
-        .error_log("Error: @edition_account_info")?;
+    if !is_token_2022 {
+        nft_edition_account_info
+            .assert_key_match(&nft_edition_key)
+            .error_log("Error: @edition_account_info")?;
+    }
 
     let mpl_token_metadata_id = mpl_token_metadata::id();
     let metadata_seeds = &[
@@ -120,76 +157,379 @@ pub fn process_imprint_rarity(
     let (nft_metadata_key, _nft_metadata_bump) =
         Pubkey::find_program_address(metadata_seeds, &mpl_token_metadata::id());
 
-    metadata_account_info
-        .assert_key_match(&nft_metadata_key)
-        .error_log("Error: @meta_data_account_info")?;
-
-    log!(log_level, 2, "Thawing the token account ...");
-    invoke_signed(
-        &mpl_token_metadata::instruction::thaw_delegated_account(
-            mpl_token_metadata_id,
-            mint_authority_key,
-            *associated_token_account_info.key,
-            nft_edition_key,
-            *mint_account_info.key,
-        ),
-        &[
-            mint_authority_account_info.clone(),
-            associated_token_account_info.clone(),
-            nft_edition_account_info.clone(),
-            mint_account_info.clone(),
-            token_program_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error: @ thawing the token account")?;
-    log!(log_level, 2, "Token account thawed !!!");
-
-    let gem_metadata = Metadata::deserialize(&mut &metadata_account_info.data.borrow()[..])
-        .error_log("Error: @ deserialize metadata")?;
-
-    let recent_blockhashes_data = recent_blockhashes_account_info.data.borrow(); //TODO: ensure to only use the blockhash for the rarity seed slot, and fail use the common rarity if the slot is too old.
-    let most_recent = array_ref![recent_blockhashes_data, 12, 8];
+    if !is_token_2022 {
+        metadata_account_info
+            .assert_key_match(&nft_metadata_key)
+            .error_log("Error: @meta_data_account_info")?;
+    }
 
-    let seed = ((u64::from_le_bytes(*most_recent).saturating_sub(clock_data.unix_timestamp as u64))
-        % 10000) as u16;
+    if is_token_2022 {
+        log!(log_level, 2, "Thawing the Token-2022 token account ...");
+        set_token_2022_freeze(
+            false,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: mint_authority_account_info,
+                mint_authority_bump,
+                mint: mint_account_info,
+                token: associated_token_account_info,
+                metadata: metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+        )?;
+        log!(log_level, 2, "Token-2022 token account thawed !!!");
+    } else if let Some(programmable_accounts) = &programmable_accounts {
+        log!(log_level, 2, "Unlocking the programmable nft ...");
+        programmable_accounts
+            .verify(mint_account_info.key, associated_token_account_info.key)
+            .error_log("Error: @programmable accounts assertion")?;
+        set_programmable_lock(
+            false,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: mint_authority_account_info,
+                mint_authority_bump,
+                mint: mint_account_info,
+                token: associated_token_account_info,
+                metadata: metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+            programmable_accounts,
+        )?;
+        log!(log_level, 2, "Programmable nft unlocked !!!");
+    } else {
+        log!(log_level, 2, "Thawing the token account ...");
+        invoke_signed(
+            &mpl_token_metadata::instruction::thaw_delegated_account(
+                mpl_token_metadata_id,
+                mint_authority_key,
+                *associated_token_account_info.key,
+                nft_edition_key,
+                *mint_account_info.key,
+            ),
+            &[
+                mint_authority_account_info.clone(),
+                associated_token_account_info.clone(),
+                nft_edition_account_info.clone(),
+                mint_account_info.clone(),
+                token_program_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+        )
+        .error_log("Error: @ thawing the token account")?;
+        log!(log_level, 2, "Token account thawed !!!");
+    }
 
-    let uris_data = Box::new(UrisAccount::parse(&uris_account_info, program_id)?);
-    let (nft_rarity_uri, rarity) = uris_data.get_uri(seed);
+    let mut uris_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    let (seed, seed_hash) = get_rarity_seed(
+        program_id,
+        &RaritySeedRequest {
+            nft_data: &nft_data,
+            mint_key: mint_account_info.key,
+            current_slot: clock_data.slot,
+            reveal,
+        },
+        slot_hashes_account_info,
+        vrf_state_account_info,
+        account_info_iter,
+    )?;
+    let rarity = uris_data.get_rarity(seed);
+    let nft_rarity_uri = get_rarity_uri(
+        program_id,
+        &uris_data,
+        rarity,
+        seed,
+        &seed_hash,
+        uri_proof,
+        account_info_iter,
+    )?;
 
     log!(log_level, 2, "Updating metadata account ...");
-    invoke_signed(
-        &mpl_token_metadata::instruction::update_metadata_accounts_v2(
-            mpl_token_metadata_id,
-            *metadata_account_info.key,
-            *mint_authority_account_info.key,
-            Some(*mint_authority_account_info.key),
-            Some(DataV2 {
-                uri: nft_rarity_uri,
-                uses: gem_metadata.uses,
-                name: gem_metadata.data.name,
-                symbol: gem_metadata.data.symbol,
-                collection: gem_metadata.collection,
-                creators: gem_metadata.data.creators,
-                seller_fee_basis_points: gem_metadata.data.seller_fee_basis_points,
-            }),
-            Some(gem_metadata.primary_sale_happened),
-            Some(gem_metadata.is_mutable),
-        ),
-        &[
-            metadata_account_info.clone(),
-            mint_authority_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error: @ updating metadata account")?;
+    update_gem_uri(
+        &GemAccounts {
+            payer: payer_account_info,
+            mint_authority: mint_authority_account_info,
+            mint_authority_bump,
+            mint: mint_account_info,
+            token: associated_token_account_info,
+            metadata: metadata_account_info,
+            edition: nft_edition_account_info,
+        },
+        programmable_accounts.as_ref(),
+        nft_rarity_uri,
+    )?;
     log!(log_level, 2, "Metadata account updated!!!");
 
+    if is_delegated {
+        let weighted_backing =
+            config_data.get_weighted_backing(uris_data.get_reward_weight(Some(rarity)));
+        general_data.total_weighted_delegated = general_data
+            .total_weighted_delegated
+            .checked_sub(nft_data.weighted_backing)
+            .error_log("Error @ general_data.total_weighted_delegated recalc")?
+            .checked_add(weighted_backing)
+            .error_log("Error @ general_data.total_weighted_delegated recalc")?;
+        nft_data.weighted_backing = weighted_backing;
+    }
+
     nft_data.rarity = Some(rarity);
     nft_data
         .serialize(&mut &mut nft_account_info.data.borrow_mut()[..])
         .error_log("Failed to serialize @nft_account_info data")?;
+    general_data
+        .serialize(&mut &mut general_account_info.data.borrow_mut()[..])
+        .error_log("Failed to serialize @general_account_info data")?;
+    if !uris_data.is_frozen {
+        log!(
+            log_level,
+            2,
+            "Freezing the uris after the first imprint ..."
+        );
+        uris_data.is_frozen = true;
+        uris_data
+            .serialize(&mut &mut uris_account_info.data.borrow_mut()[..])
+            .error_log("Failed to serialize @uris_account_info data")?;
+    }
 
     log!(log_level, 4, "Imprint rarity !!!");
     Ok(())
 }
+
+/// Returns the uri of the rarity picked by the seed hash, from the proof of the rarity's committed uris,
+/// from its uris page account, which is then the next account, or from the uris account.
+pub fn get_rarity_uri(
+    program_id: &Pubkey,
+    uris_data: &UrisAccount,
+    rarity: u8,
+    seed: u16,
+    seed_hash: &[u8; 32],
+    uri_proof: Option<UriProof>,
+    account_info_iter: &mut Iter<AccountInfo>,
+) -> Result<String, ProgramError> {
+    Ok(match (uris_data.get_uris_root(rarity), uri_proof) {
+        (Some(uris_root), Some(uri_proof)) => {
+            let leaf = UrisRoot::get_leaf(uris_root.get_index(seed_hash), &uri_proof.uri);
+            if !verify_merkle_proof(&uri_proof.proof, &uris_root.root, leaf) {
+                Err(InglError::InvalidUriProof.utilize("uri proof verification"))?
+            }
+            uris_data.get_full_uri(&uri_proof.uri)
+        }
+        (Some(_), None) => Err(InglError::InvalidUriProof
+            .utilize("A uri proof is required for rarities with committed uris"))?,
+        _ => match uris_data.get_page(rarity, seed_hash) {
+            Some(page) => {
+                let uris_page_account_info = next_account_info(account_info_iter)?;
+                uris_page_account_info
+                    .assert_seed(program_id, &[URIS_PAGE_KEY, &[rarity], &page.to_be_bytes()])
+                    .error_log("Error: @uris_page_account_info pda assertion")?;
+                uris_data.get_full_uri(
+                    &UrisPage::parse(uris_page_account_info, program_id)?.get_uri(seed_hash),
+                )
+            }
+            None => uris_data.get_uri(seed, seed_hash).0,
+        },
+    })
+}
+
+/// Sets the metadata uri of the gem, signed by the mint authority as its update authority. The rest of its metadata is kept.
+/// The token metadata of Token-2022 gems is stored in their mint, which was funded for the longest uri at mint.
+pub fn update_gem_uri<'b>(
+    gem: &GemAccounts<'_, 'b>,
+    programmable_accounts: Option<&ProgrammableAccounts<'_, 'b>>,
+    uri: String,
+) -> ProgramResult {
+    let signer_seeds: &[&[u8]] = &[INGL_MINT_AUTHORITY_KEY, &[gem.mint_authority_bump]];
+    if is_token_2022_gem(gem.mint) {
+        return invoke_signed(
+            &update_token_metadata_field(
+                gem.mint.key,
+                gem.mint_authority.key,
+                TokenMetadataField::Uri,
+                uri,
+            )?,
+            &[gem.mint.clone(), gem.mint_authority.clone()],
+            &[signer_seeds],
+        )
+        .error_log("Error: @ updating the token metadata uri");
+    }
+    let gem_metadata = Metadata::deserialize(&mut &gem.metadata.data.borrow()[..])
+        .error_log("Error: @ deserialize metadata")?;
+    if let Some(programmable_accounts) = programmable_accounts {
+        let mut update_builder = UpdateBuilder::new();
+        update_builder
+            .authority(*gem.mint_authority.key)
+            .token(*gem.token.key)
+            .mint(*gem.mint.key)
+            .metadata(*gem.metadata.key)
+            .edition(*gem.edition.key)
+            .payer(*gem.payer.key);
+        if let Some(rule_set) = programmable_accounts.rule_set() {
+            update_builder.authorization_rules(rule_set);
+        }
+        let update_instruction = update_builder
+            .build(UpdateArgs::V1 {
+                new_update_authority: None,
+                data: Some(Data {
+                    uri,
+                    name: gem_metadata.data.name,
+                    symbol: gem_metadata.data.symbol,
+                    creators: gem_metadata.data.creators,
+                    seller_fee_basis_points: gem_metadata.data.seller_fee_basis_points,
+                }),
+                primary_sale_happened: None,
+                is_mutable: None,
+                collection: CollectionToggle::None,
+                collection_details: CollectionDetailsToggle::None,
+                uses: UsesToggle::None,
+                rule_set: RuleSetToggle::None,
+                authorization_data: None,
+            })
+            .map_err(|_| InglError::InvalidData.utilize("Update instruction"))?
+            .instruction();
+        let mut account_infos = vec![
+            gem.mint_authority.clone(),
+            gem.token.clone(),
+            gem.mint.clone(),
+            gem.metadata.clone(),
+            gem.edition.clone(),
+            gem.payer.clone(),
+        ];
+        account_infos.extend(programmable_accounts.infos());
+        invoke_signed(&update_instruction, &account_infos, &[signer_seeds])
+            .error_log("Error: @ updating programmable metadata account")?;
+    } else {
+        invoke_signed(
+            &mpl_token_metadata::instruction::update_metadata_accounts_v2(
+                mpl_token_metadata::id(),
+                *gem.metadata.key,
+                *gem.mint_authority.key,
+                Some(*gem.mint_authority.key),
+                Some(DataV2 {
+                    uri,
+                    uses: gem_metadata.uses,
+                    name: gem_metadata.data.name,
+                    symbol: gem_metadata.data.symbol,
+                    collection: gem_metadata.collection,
+                    creators: gem_metadata.data.creators,
+                    seller_fee_basis_points: gem_metadata.data.seller_fee_basis_points,
+                }),
+                Some(gem_metadata.primary_sale_happened),
+                Some(gem_metadata.is_mutable),
+            ),
+            &[gem.metadata.clone(), gem.mint_authority.clone()],
+            &[signer_seeds],
+        )
+        .error_log("Error: @ updating metadata account")?;
+    }
+    Ok(())
+}
+
+/// The NFT whose rarity seed is derived, at the slot it is imprinted at.
+pub struct RaritySeedRequest<'a> {
+    pub nft_data: &'a NftData,
+    pub mint_key: &'a Pubkey,
+    pub current_slot: Slot,
+    /// The holder's secret, required if a commitment was made.
+    pub reveal: Option<[u8; 32]>,
+}
+
+/// Derives the rarity seed of an NFT from the entropy of its rarity seed slot.
+/// The entropy is the VRF result when an oracle is configured, otherwise the hash of the rarity seed slot.
+/// The holder's revealed secret is mixed in when a commitment was made.
+/// If the seed slot has aged out of the SlotHashes sysvar, the holder must commit a new rarity seed with CommitRaritySeed.
+/// The seed hash it was taken from is returned along with it, for the independent picks of the NFT's uris page and uri.
+pub fn get_rarity_seed(
+    program_id: &Pubkey,
+    request: &RaritySeedRequest,
+    slot_hashes_account_info: &AccountInfo,
+    vrf_state_account_info: &AccountInfo,
+    account_info_iter: &mut Iter<AccountInfo>,
+) -> Result<(u16, [u8; 32]), ProgramError> {
+    let nft_data = request.nft_data;
+    let rarity_seed_slot = nft_data
+        .rarity_seed_slot
+        .error_log("Error: Rarity seed slot can't be None")?;
+    if request.current_slot <= rarity_seed_slot {
+        Err(InglError::TooEarly.utilize("imprint_rarity"))?
+    }
+
+    if let Some(commitment) = nft_data.rarity_commitment {
+        let revealed = request
+            .reveal
+            .error_log("Error: A reveal is required for this NFT's rarity commitment")?;
+        if hashv(&[&revealed]).to_bytes() != commitment {
+            Err(InglError::InvalidReveal.utilize("imprint_rarity"))?
+        }
+    }
+
+    let entropy = match get_vrf_result(
+        program_id,
+        vrf_state_account_info,
+        account_info_iter,
+        request.mint_key,
+        rarity_seed_slot,
+    )? {
+        Some(result) => result,
+        None => match get_slot_hash(slot_hashes_account_info, rarity_seed_slot)? {
+            Some(slot_hash) => slot_hash.to_bytes(),
+            None => Err(InglError::TooLate
+                .utilize("Rarity seed slot has aged out, commit a new rarity seed"))?,
+        },
+    };
+
+    let mut seed_parts: Vec<&[u8]> = vec![&entropy, request.mint_key.as_ref()];
+    if let (Some(revealed), Some(_)) = (request.reveal.as_ref(), nft_data.rarity_commitment) {
+        seed_parts.push(revealed);
+    }
+    let seed_hash = hashv(&seed_parts).to_bytes();
+    let seed = get_seed_part(&seed_hash, RARITY_SEED_OFFSET) % CUMMULATED_RARITY as u64;
+
+    Ok((seed as u16, seed_hash))
+}
+
+/// Reads the VRF result of an NFT mint if a VRF oracle is configured, otherwise returns None.
+pub fn get_vrf_result(
+    program_id: &Pubkey,
+    vrf_state_account_info: &AccountInfo,
+    account_info_iter: &mut Iter<AccountInfo>,
+    mint_key: &Pubkey,
+    rarity_seed_slot: Slot,
+) -> Result<Option<[u8; 32]>, ProgramError> {
+    vrf_state_account_info
+        .assert_seed(program_id, &[VRF_STATE_KEY])
+        .error_log("Error: @vrf_state_account_info pda assertion")?;
+    if vrf_state_account_info.owner != program_id {
+        return Ok(None);
+    }
+    let vrf_state = VrfState::parse(vrf_state_account_info, program_id)?;
+    let oracle_program = match vrf_state.oracle_program {
+        Some(oracle_program) => oracle_program,
+        None => return Ok(None),
+    };
+
+    let vrf_result_account_info = next_account_info(account_info_iter)?;
+    vrf_result_account_info
+        .assert_owner(&oracle_program)
+        .error_log("Error: @vrf_result_account_info ownership")?;
+    // The request of the mint, so that the result of another request can't be passed off as its own.
+    vrf_result_account_info
+        .assert_seed(&oracle_program, &[VRF_RESULT_KEY, mint_key.as_ref()])
+        .error_log("Error: @vrf_result_account_info request key assertion")?;
+    let vrf_result: VrfResult = try_from_slice_unchecked(&vrf_result_account_info.data.borrow())
+        .error_log("Error: @vrf_result_account_info deserialization")?;
+    if vrf_result.authority != vrf_state.oracle_authority {
+        Err(InglError::InvalidVrfAuthorityError.utilize("vrf_result_account_info"))?
+    }
+    vrf_result
+        .mint
+        .assert_match(mint_key)
+        .error_log("Error: VRF result was requested for a different mint")?;
+    if vrf_result.fulfilled_slot < rarity_seed_slot {
+        Err(InglError::TooEarly.utilize("VRF result was fulfilled before the rarity seed slot"))?
+    }
+    if vrf_result.result == [0; 32] {
+        Err(InglError::TooEarly.utilize("VRF result is not fulfilled yet"))?
+    }
+
+    Ok(Some(vrf_result.result))
+}
@@ -0,0 +1,246 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::rewards_processes::nft_withdraw::has_unclaimed_rewards,
+    state::{constants::*, FundsLocation, GeneralData, NftData, UrisAccount, ValidatorConfig},
+    token_2022::unpack_token_account,
+    utils::{
+        delegate_programmable_nft, get_clock_data, get_token_program_id, is_token_2022_gem,
+        set_programmable_lock, set_token_2022_freeze, verify_nft_ownership, AccountInfoHelpers,
+        GemAccounts, OptionExt, ProgrammableAccounts, ResultExt,
+    },
+};
+
+use borsh::BorshSerialize;
+use mpl_token_metadata::{pda::find_metadata_account, state::PREFIX};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+};
+use spl_token::error::TokenError;
+
+///Locks a delegated gem for the given duration, freezing its token account and preventing its undelegation and redemption.
+///The gem earns the boost of the best lock boost tier the duration qualifies for, until it is unlocked.
+pub fn lock_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    duration: u32,
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Lock nft ... duration: {}", duration);
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let mint_authority_account_info = next_account_info(account_info_iter)?;
+    let nft_edition_account_info = next_account_info(account_info_iter)?;
+    let nft_metadata_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error: @nft_account_data_info deserialization")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: @config_account_info pda assertion")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error: @general_account_info pda assertion")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: @uris_account_info pda assertion")?;
+    let (mint_authority_key, mint_authority_bump) = mint_authority_account_info
+        .assert_seed(program_id, &[INGL_MINT_AUTHORITY_KEY])
+        .error_log("Error: @mint_authority_account_info pda assertion")?;
+    let mpl_token_metadata_id = mpl_token_metadata::id();
+    let (nft_edition_key, _nft_edition_bump) = nft_edition_account_info
+        .assert_seed(
+            &mpl_token_metadata_id,
+            &[
+                PREFIX.as_bytes(),
+                mpl_token_metadata_id.as_ref(),
+                mint_account_info.key.as_ref(),
+                b"edition",
+            ],
+        )
+        .error_log("Error: @nft_edition_account_info pda assertion")?;
+    spl_token_program_account_info
+        .assert_key_match(&get_token_program_id(mint_account_info)?)
+        .error_log("Error: @spl_token_program_account_info")?;
+
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    let uris_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+
+    if let FundsLocation::Delegated = nft_data.funds_location {
+    } else {
+        Err(InglError::InvalidFundsLocation.utilize("Only delegated gems can be locked"))?
+    }
+    if nft_data.lock_until.is_some() {
+        Err(InglError::NftLocked.utilize("The gem is already locked"))?
+    }
+    let rarity = nft_data
+        .rarity
+        .error_log("Error: Rarity must be imprinted before locking the gem")?;
+    if duration == 0 || duration > MAX_LOCK_DURATION {
+        Err(InglError::BeyondBounds.utilize("Lock duration"))?
+    }
+    // The boost isn't retroactive, so rewards accrued before the lock must be withdrawn first.
+    if has_unclaimed_rewards(&nft_data, &general_data)? {
+        Err(InglError::InvalidData.utilize("Withdraw the accrued rewards before locking the gem"))?
+    }
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    let lock_boost_tier = config_data.get_lock_boost_tier(duration);
+    let boost_weight =
+        config_data.get_boost_weight(uris_data.get_reward_weight(Some(rarity)), lock_boost_tier);
+    general_data.total_boost_weight = general_data
+        .total_boost_weight
+        .checked_add(boost_weight)
+        .error_log("Error @ general_data.total_boost_weight recalc")?;
+    nft_data.lock_until = Some(
+        (clock_data.unix_timestamp as u32)
+            .checked_add(duration)
+            .error_log("Error @ lock_until calculation")?,
+    );
+    nft_data.lock_boost_tier = lock_boost_tier;
+    nft_data.boost_weight = boost_weight;
+
+    let associated_token_account_data = unpack_token_account(associated_token_account_info)
+        .error_log("Error: @associated_token_account_info data unpacking")?;
+    let mut lock_accounts = vec![
+        payer_account_info.clone(),
+        mint_account_info.clone(),
+        associated_token_account_info.clone(),
+        mint_authority_account_info.clone(),
+        nft_edition_account_info.clone(),
+        nft_metadata_account_info.clone(),
+    ];
+    lock_accounts.extend(programmable_accounts.iter().flat_map(|x| x.infos()));
+    if let Some(programmable_accounts) = &programmable_accounts {
+        log!(log_level, 2, "Locking the programmable nft ...");
+        nft_metadata_account_info
+            .assert_key_match(&find_metadata_account(mint_account_info.key).0)
+            .error_log("Error: @nft_metadata_account_info")?;
+        programmable_accounts
+            .verify(mint_account_info.key, associated_token_account_info.key)
+            .error_log("Error: @programmable accounts assertion")?;
+        // A programmable nft keeps its utility delegate until it is transferred.
+        if associated_token_account_data.delegate != Some(mint_authority_key).into() {
+            delegate_programmable_nft(
+                payer_account_info,
+                &mint_authority_key,
+                mint_account_info.key,
+                associated_token_account_info.key,
+                programmable_accounts,
+                &lock_accounts,
+            )?;
+        }
+        set_programmable_lock(
+            true,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: mint_authority_account_info,
+                mint_authority_bump,
+                mint: mint_account_info,
+                token: associated_token_account_info,
+                metadata: nft_metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+            programmable_accounts,
+        )?;
+        log!(log_level, 2, "Programmable nft locked!!!");
+    } else if is_token_2022_gem(mint_account_info) {
+        if associated_token_account_data.is_frozen() {
+            Err(TokenError::AccountFrozen)?
+        }
+        log!(log_level, 2, "Freezing the Token-2022 token account ...");
+        set_token_2022_freeze(
+            true,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: mint_authority_account_info,
+                mint_authority_bump,
+                mint: mint_account_info,
+                token: associated_token_account_info,
+                metadata: nft_metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+        )?;
+        log!(log_level, 2, "Token-2022 token account frozen!!!");
+    } else {
+        if associated_token_account_data.is_frozen() {
+            Err(TokenError::AccountFrozen)?
+        }
+        log!(log_level, 2, "Freezing the associated token account ...");
+        invoke(
+            &spl_token::instruction::approve(
+                &spl_token::id(),
+                associated_token_account_info.key,
+                &mint_authority_key,
+                payer_account_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                associated_token_account_info.clone(),
+                mint_authority_account_info.clone(),
+                payer_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ mint authority delegation")?;
+        invoke_signed(
+            &mpl_token_metadata::instruction::freeze_delegated_account(
+                mpl_token_metadata_id,
+                mint_authority_key,
+                *associated_token_account_info.key,
+                nft_edition_key,
+                *mint_account_info.key,
+            ),
+            &[
+                mint_authority_account_info.clone(),
+                associated_token_account_info.clone(),
+                nft_edition_account_info.clone(),
+                mint_account_info.clone(),
+                spl_token_program_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]]],
+        )
+        .error_log("Error while freezing associated_token_account")?;
+        log!(log_level, 2, "Associated token account frozen!!!");
+    }
+
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("Error: @nft_account_data_info serialization")?;
+    general_data
+        .serialize(&mut &mut general_account_info.data.borrow_mut()[..])
+        .error_log("Error: @general_account_info serialization")?;
+
+    log!(
+        log_level,
+        4,
+        "Locked nft until {}!!!",
+        nft_data.lock_until.unwrap_or_default()
+    );
+    Ok(())
+}
@@ -3,31 +3,51 @@ use std::collections::BTreeMap;
 use crate::{
     error::InglError,
     log,
-    state::{constants::*, FundsLocation, GeneralData, NftData, ValidatorConfig},
-    utils::{get_clock_data, get_rent_data_from_account, AccountInfoHelpers, OptionExt, ResultExt},
+    state::{
+        constants::*, FundsLocation, GeneralData, MintPhases, MinterRecord, NftData,
+        ReferrerRecord, ValidatorConfig,
+    },
+    token_2022::{
+        get_extra_account_metas_data, initialize_metadata_pointer, initialize_token_metadata,
+        initialize_transfer_hook, unpack_token_account, TokenMetadataFields,
+        EXTRA_ACCOUNT_METAS_SEED, GEM_MINT_SPACE,
+    },
+    utils::{
+        create_pda_account, delegate_programmable_nft, get_clock_data, get_rent_data_from_account,
+        is_token_2022_gem, set_programmable_lock, set_token_2022_freeze, verify_merkle_proof,
+        AccountInfoHelpers, GemAccounts, OptionExt, ProgrammableAccounts, ResultExt,
+    },
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use mpl_token_metadata::{
     self as metaplex,
-    state::{Collection, Creator, PREFIX},
+    instruction::{
+        builders::{CreateBuilder, MintBuilder, VerifyBuilder},
+        CreateArgs, InstructionBuilder, MintArgs, VerificationArgs,
+    },
+    pda::find_metadata_account,
+    state::{AssetData, Collection, Creator, PrintSupply, TokenStandard, PREFIX},
 };
 
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    hash::hashv,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
     system_instruction, system_program, sysvar,
 };
 
-use spl_associated_token_account::{get_associated_token_address, *};
-use spl_token::{self, error::TokenError, state::Account};
+use spl_associated_token_account::{get_associated_token_address_with_program_id, *};
+use spl_token::{self, error::TokenError};
+use spl_token_2022::instruction::AuthorityType;
 
 pub fn process_mint_nft(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    allowlist_proof: Vec<[u8; 32]>,
     log_level: u8,
     clock_is_from_account: bool,
 ) -> ProgramResult {
@@ -50,9 +70,27 @@ pub fn process_mint_nft(
     let ingl_config_account_info = next_account_info(account_info_iter)?;
     let uris_account_info = next_account_info(account_info_iter)?;
     let general_account_info = next_account_info(account_info_iter)?;
+    let mint_phases_account_info = next_account_info(account_info_iter)?;
+    let minter_record_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+
+    let config_data = Box::new(ValidatorConfig::parse(
+        ingl_config_account_info,
+        program_id,
+    )?);
+    let programmable_accounts = ProgrammableAccounts::collect(
+        account_info_iter,
+        config_data.programmable_rule_set.is_some(),
+    )?;
+    let extra_account_metas_info = if config_data.uses_token_2022 {
+        Some(next_account_info(account_info_iter)?)
+    } else {
+        None
+    };
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
     let rent_data = get_rent_data_from_account(sysvar_rent_account_info)?;
+    let referrer_account_info = account_info_iter.next();
 
     log!(log_level, 0, "Done with Main account Collection ...");
 
@@ -73,8 +111,13 @@ pub fn process_mint_nft(
     system_program_account_info
         .assert_key_match(&system_program::id())
         .error_log("Error: @system_program_account_info")?;
+    let token_program_id = if config_data.uses_token_2022 {
+        spl_token_2022::id()
+    } else {
+        spl_token::id()
+    };
     spl_token_program_account_info
-        .assert_key_match(&spl_token::id())
+        .assert_key_match(&token_program_id)
         .error_log("Error: @spl_token_program_account_info")?;
     sysvar_rent_account_info
         .assert_key_match(&sysvar::rent::id())
@@ -117,12 +160,12 @@ pub fn process_mint_nft(
         .assert_owner(program_id)
         .error_log("Error @ general_account_info ownership assertion")?;
 
-    let config_data = Box::new(ValidatorConfig::parse(
-        &ingl_config_account_info,
-        program_id,
-    )?);
+    config_data.assert_not_paused()?;
     // let uris_data = Box::new(UrisAccount::parse(&uris_account_info, program_id)?);
     let mut general_data = Box::new(GeneralData::parse(&general_account_info, program_id)?);
+    vote_account_info
+        .assert_key_match(&config_data.vote_account)
+        .error_log("Error @ Vote account address verification")?;
 
     let (vote_account_key, _va_bump) =
         Pubkey::find_program_address(&[VOTE_ACCOUNT_KEY.as_ref()], program_id);
@@ -138,9 +181,10 @@ pub fn process_mint_nft(
         .error_log("Error @ nft_metadata_account_info pda assertion")?;
 
     associated_token_account_info
-        .assert_key_match(&get_associated_token_address(
+        .assert_key_match(&get_associated_token_address_with_program_id(
             payer_account_info.key,
             nft_mint_account_info.key,
+            &token_program_id,
         ))
         .error_log("Error: @associated_token_account_info")?;
 
@@ -179,6 +223,70 @@ pub fn process_mint_nft(
     // Getting timestamp
     let current_timestamp = clock_data.unix_timestamp as u32;
 
+    let price_premium = enforce_mint_phase(
+        program_id,
+        payer_account_info,
+        mint_phases_account_info,
+        minter_record_account_info,
+        &allowlist_proof,
+        current_timestamp,
+        &rent_data,
+    )?;
+    if price_premium > 0 {
+        log!(
+            log_level,
+            2,
+            "transfer the mint phase premium to the vote account"
+        );
+        invoke(
+            &system_instruction::transfer(
+                payer_account_info.key,
+                vote_account_info.key,
+                price_premium,
+            ),
+            &[payer_account_info.clone(), vote_account_info.clone()],
+        )
+        .error_log("Error @ mint phase premium transfer")?;
+    }
+
+    let mut referral_fee = 0;
+    if let Some(referrer_account_info) = referrer_account_info {
+        let referrer_record_account_info = next_account_info(account_info_iter)?;
+        referral_fee = config_data.get_referral_fee();
+        record_referral(
+            program_id,
+            payer_account_info,
+            referrer_account_info,
+            referrer_record_account_info,
+            referral_fee,
+            &rent_data,
+        )?;
+        if referral_fee > 0 {
+            log!(log_level, 2, "transfer the referral fee to the referrer");
+            invoke(
+                &system_instruction::transfer(
+                    payer_account_info.key,
+                    referrer_account_info.key,
+                    referral_fee,
+                ),
+                &[payer_account_info.clone(), referrer_account_info.clone()],
+            )
+            .error_log("Error @ referral fee transfer")?;
+        }
+    }
+    if config_data.mint_fee > referral_fee {
+        log!(log_level, 2, "transfer the mint fee to the vote account");
+        invoke(
+            &system_instruction::transfer(
+                payer_account_info.key,
+                vote_account_info.key,
+                config_data.mint_fee - referral_fee,
+            ),
+            &[payer_account_info.clone(), vote_account_info.clone()],
+        )
+        .error_log("Error @ mint fee transfer")?;
+    }
+
     let space = 82;
     let rent_lamports = rent_data.minimum_balance(space);
 
@@ -186,6 +294,8 @@ pub fn process_mint_nft(
 
     general_data.mint_numeration += 1;
     general_data.total_delegated += mint_cost;
+    let weighted_backing = config_data.get_weighted_backing(REWARD_WEIGHT_BASE);
+    general_data.total_weighted_delegated += weighted_backing;
     if general_data.total_delegated > config_data.max_primary_stake {
         Err(InglError::TooLate.utilize("Max primary stake reached"))?
     }
@@ -204,71 +314,80 @@ pub fn process_mint_nft(
     )
     .error_log("Error @ minting_pool_account_info transfer")?;
 
-    log!(log_level, 2, "create the mint account");
-    invoke(
-        &system_instruction::create_account(
-            payer_account_info.key,
-            nft_mint_account_info.key,
-            rent_lamports,
-            space as u64,
-            spl_token_program_account_info.key,
-        ),
-        &[payer_account_info.clone(), nft_mint_account_info.clone()],
-    )
-    .error_log("Error @ mint_account_info creation")?;
-    log!(log_level, 2, "initialize the mint account");
-    invoke(
-        &spl_token::instruction::initialize_mint(
-            &spl_token::id(),
-            &nft_mint_account_info.key,
-            &mint_authority_key,
-            Some(&mint_authority_key),
-            0,
-        )?,
-        &[
-            nft_mint_account_info.clone(),
-            sysvar_rent_account_info.clone(),
-        ],
-    )
-    .error_log("Error @ mint_account_info initialization")?;
-    log!(log_level, 2, "create mint associated token account");
-    invoke(
-        &spl_associated_token_account::instruction::create_associated_token_account(
-            payer_account_info.key,
-            payer_account_info.key,
-            nft_mint_account_info.key,
-            &spl_token_program_account_info.key,
-        ),
-        &[
-            payer_account_info.clone(),
-            associated_token_account_info.clone(),
-            payer_account_info.clone(),
-            nft_mint_account_info.clone(),
-            system_program_account_info.clone(),
-            spl_token_program_account_info.clone(),
-        ],
-    )
-    .error_log("Error @ associated_token_account_info creation")?;
-
-    log!(log_level, 2, "Mint new token");
-    invoke_signed(
-        &spl_token::instruction::mint_to(
-            spl_token_program_account_info.key,
-            nft_mint_account_info.key,
-            associated_token_account_info.key,
-            &mint_authority_key,
-            &[],
-            1,
-        )?,
-        &[
-            nft_mint_account_info.clone(),
-            associated_token_account_info.clone(),
-            mint_authority_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error @ minting token")?;
-
+    if config_data.uses_token_2022 {
+        log!(log_level, 2, "create the Token-2022 mint account");
+        invoke(
+            &system_instruction::create_account(
+                payer_account_info.key,
+                nft_mint_account_info.key,
+                rent_data
+                    .minimum_balance(GEM_MINT_SPACE + 4 + TokenMetadataFields::get_max_space()),
+                GEM_MINT_SPACE as u64,
+                &spl_token_2022::id(),
+            ),
+            &[payer_account_info.clone(), nft_mint_account_info.clone()],
+        )
+        .error_log("Error @ mint_account_info creation")?;
+        log!(
+            log_level,
+            2,
+            "initialize the transfer hook and the metadata pointer"
+        );
+        invoke(
+            &initialize_transfer_hook(nft_mint_account_info.key, &mint_authority_key, program_id),
+            std::slice::from_ref(nft_mint_account_info),
+        )
+        .error_log("Error @ transfer hook initialization")?;
+        invoke(
+            &initialize_metadata_pointer(
+                nft_mint_account_info.key,
+                &mint_authority_key,
+                nft_mint_account_info.key,
+            ),
+            std::slice::from_ref(nft_mint_account_info),
+        )
+        .error_log("Error @ metadata pointer initialization")?;
+        log!(log_level, 2, "initialize the mint account");
+        invoke(
+            &spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::id(),
+                nft_mint_account_info.key,
+                &mint_authority_key,
+                Some(&mint_authority_key),
+                0,
+            )?,
+            std::slice::from_ref(nft_mint_account_info),
+        )
+        .error_log("Error @ mint_account_info initialization")?;
+    } else {
+        log!(log_level, 2, "create the mint account");
+        invoke(
+            &system_instruction::create_account(
+                payer_account_info.key,
+                nft_mint_account_info.key,
+                rent_lamports,
+                space as u64,
+                spl_token_program_account_info.key,
+            ),
+            &[payer_account_info.clone(), nft_mint_account_info.clone()],
+        )
+        .error_log("Error @ mint_account_info creation")?;
+        log!(log_level, 2, "initialize the mint account");
+        invoke(
+            &spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &nft_mint_account_info.key,
+                &mint_authority_key,
+                Some(&mint_authority_key),
+                0,
+            )?,
+            &[
+                nft_mint_account_info.clone(),
+                sysvar_rent_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ mint_account_info initialization")?;
+    }
     let mut creators = Vec::new();
     creators.push(Creator {
         address: mint_authority_key,
@@ -282,145 +401,399 @@ pub fn process_mint_nft(
         share: 100,
     });
 
-    log!(log_level, 2, "starting metadata creation");
-    invoke_signed(
-        &mpl_token_metadata::instruction::create_metadata_accounts_v3(
-            mpl_token_metadata_id,
-            nft_metadata_key,
-            *nft_mint_account_info.key,
-            *mint_authority_account_info.key,
-            *payer_account_info.key,
-            *mint_authority_account_info.key,
-            format!(
-                "{} #{}",
-                config_data.validator_name, &general_data.mint_numeration
-            ),
-            format!(
-                "{}#{}",
-                config_data
-                    .validator_name
-                    .get(
-                        0..(if config_data.validator_name.len() > 6 {
-                            6
-                        } else {
-                            config_data.validator_name.len()
-                        })
-                    )
-                    .error_log("error determining collection symbol")?,
-                &general_data.mint_numeration
-            ),
-            config_data.default_uri,
-            Some(creators),
-            300,
-            true,
-            true,
-            Some(Collection {
-                verified: false,
-                key: ingl_nft_collection_key,
-            }),
-            None,
-            None,
-        ),
-        &[
-            nft_metadata_account_info.clone(),
-            nft_mint_account_info.clone(),
-            mint_authority_account_info.clone(),
+    let nft_name = format!(
+        "{} #{}",
+        config_data.validator_name, &general_data.mint_numeration
+    );
+    let nft_symbol = format!(
+        "{}#{}",
+        config_data
+            .validator_name
+            .get(
+                0..(if config_data.validator_name.len() > 6 {
+                    6
+                } else {
+                    config_data.validator_name.len()
+                })
+            )
+            .error_log("error determining collection symbol")?,
+        &general_data.mint_numeration
+    );
+
+    if let (Some(rule_set), Some(programmable_accounts)) =
+        (config_data.programmable_rule_set, &programmable_accounts)
+    {
+        log!(log_level, 2, "minting a programmable nft");
+        programmable_accounts
+            .verify(nft_mint_account_info.key, associated_token_account_info.key)
+            .error_log("Error @ programmable accounts assertion")?;
+        let mint_authority_signer: &[&[u8]] = &[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]];
+        let mut account_infos = vec![
             payer_account_info.clone(),
+            nft_mint_account_info.clone(),
             mint_authority_account_info.clone(),
-            system_program_account_info.clone(),
-            sysvar_rent_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error @ nft_metadata_account_info creation")?;
-
-    log!(log_level, 2, "verifying collection");
-    invoke_signed(
-        &mpl_token_metadata::instruction::verify_collection(
-            mpl_token_metadata_id,
-            nft_metadata_key,
-            mint_authority_key,
-            *payer_account_info.key,
-            ingl_nft_collection_key,
-            collection_metadata_key,
-            ingl_collection_edition_key,
-            None,
-        ),
-        &[
+            associated_token_account_info.clone(),
             nft_metadata_account_info.clone(),
-            mint_authority_account_info.clone(),
-            payer_account_info.clone(),
+            nft_edition_account_info.clone(),
             ingl_collection_mint_info.clone(),
             ingl_collection_account_info.clone(),
             ingl_edition_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error @ collection verification")?;
-
-    log!(log_level, 2, "Creating master Edition account...");
-    invoke_signed(
-        &mpl_token_metadata::instruction::create_master_edition_v3(
-            mpl_token_metadata_id,
-            nft_edition_key,
-            *nft_mint_account_info.key,
-            mint_authority_key,
-            mint_authority_key,
-            nft_metadata_key,
-            *payer_account_info.key,
-            None,
-        ),
-        &[
-            nft_edition_account_info.clone(),
-            nft_mint_account_info.clone(),
-            mint_authority_account_info.clone(),
-            mint_authority_account_info.clone(),
-            payer_account_info.clone(),
-            nft_metadata_account_info.clone(),
-            spl_token_program_account_info.clone(),
             system_program_account_info.clone(),
-            sysvar_rent_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error @ master Edition creation")?;
-    log!(log_level, 2, "Delegate mint authority to metaplex...");
-    invoke(
-        &spl_token::instruction::approve(
-            &spl_token::id(),
-            associated_token_account_info.key,
+            spl_token_program_account_info.clone(),
+        ];
+        account_infos.extend(programmable_accounts.infos());
+
+        log!(log_level, 2, "starting programmable metadata creation");
+        let create_instruction = CreateBuilder::new()
+            .metadata(nft_metadata_key)
+            .master_edition(nft_edition_key)
+            .mint(*nft_mint_account_info.key)
+            .authority(mint_authority_key)
+            .payer(*payer_account_info.key)
+            .update_authority(mint_authority_key)
+            .update_authority_as_signer(true)
+            .initialize_mint(false)
+            .build(CreateArgs::V1 {
+                asset_data: get_programmable_asset_data(
+                    &config_data,
+                    rule_set,
+                    nft_name,
+                    nft_symbol,
+                    creators,
+                    ingl_nft_collection_key,
+                ),
+                decimals: Some(0),
+                print_supply: Some(PrintSupply::Zero),
+            })
+            .map_err(|_| InglError::InvalidData.utilize("Create instruction"))?
+            .instruction();
+        invoke_signed(
+            &create_instruction,
+            &account_infos,
+            &[mint_authority_signer],
+        )
+        .error_log("Error @ programmable nft_metadata_account_info creation")?;
+
+        log!(log_level, 2, "Mint new programmable token");
+        let mint_instruction = MintBuilder::new()
+            .token(*associated_token_account_info.key)
+            .token_owner(*payer_account_info.key)
+            .metadata(nft_metadata_key)
+            .master_edition(nft_edition_key)
+            .token_record(*programmable_accounts.token_record.key)
+            .mint(*nft_mint_account_info.key)
+            .authority(mint_authority_key)
+            .payer(*payer_account_info.key)
+            .authorization_rules(rule_set)
+            .build(MintArgs::V1 {
+                amount: 1,
+                authorization_data: None,
+            })
+            .map_err(|_| InglError::InvalidData.utilize("Mint instruction"))?
+            .instruction();
+        invoke_signed(&mint_instruction, &account_infos, &[mint_authority_signer])
+            .error_log("Error @ minting programmable token")?;
+
+        log!(log_level, 2, "verifying collection");
+        let verify_instruction = VerifyBuilder::new()
+            .authority(mint_authority_key)
+            .metadata(nft_metadata_key)
+            .collection_mint(ingl_nft_collection_key)
+            .collection_metadata(collection_metadata_key)
+            .collection_master_edition(ingl_collection_edition_key)
+            .build(VerificationArgs::CollectionV1)
+            .map_err(|_| InglError::InvalidData.utilize("Verify instruction"))?
+            .instruction();
+        invoke_signed(
+            &verify_instruction,
+            &account_infos,
+            &[mint_authority_signer],
+        )
+        .error_log("Error @ collection verification")?;
+
+        log!(
+            log_level,
+            2,
+            "Delegate the utility of the nft to the mint authority..."
+        );
+        delegate_programmable_nft(
+            payer_account_info,
             &mint_authority_key,
-            payer_account_info.key,
-            &[],
-            1,
-        )?,
-        &[
-            associated_token_account_info.clone(),
-            mint_authority_account_info.clone(),
-            payer_account_info.clone(),
-        ],
-    )
-    .error_log("Error @ mint authority delegation")?;
+            nft_mint_account_info.key,
+            associated_token_account_info.key,
+            programmable_accounts,
+            &account_infos,
+        )?;
+    } else if let Some(extra_account_metas_info) = extra_account_metas_info {
+        let mint_authority_signer: &[&[u8]] = &[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]];
+        log!(log_level, 2, "storing the token metadata in the mint");
+        invoke_signed(
+            &initialize_token_metadata(
+                nft_mint_account_info.key,
+                &mint_authority_key,
+                &mint_authority_key,
+                &TokenMetadataFields {
+                    name: nft_name,
+                    symbol: nft_symbol,
+                    uri: config_data.default_uri.clone(),
+                },
+            )?,
+            &[
+                nft_mint_account_info.clone(),
+                mint_authority_account_info.clone(),
+            ],
+            &[mint_authority_signer],
+        )
+        .error_log("Error @ token metadata initialization")?;
 
-    log!(
-        log_level,
-        2,
-        "updating update_primary_sale_happened_via_token"
-    );
-    invoke(
-        &mpl_token_metadata::instruction::update_primary_sale_happened_via_token(
-            mpl_token_metadata::id(),
-            nft_metadata_key,
-            *payer_account_info.key,
-            *associated_token_account_info.key,
-        ),
-        &[
-            nft_metadata_account_info.clone(),
-            payer_account_info.clone(),
-            associated_token_account_info.clone(),
-        ],
-    )
-    .error_log("Error @ update_primary_sale_happened_via_token")?;
+        log!(log_level, 2, "creating the extra account metas");
+        let (extra_account_metas_key, extra_account_metas_bump) = extra_account_metas_info
+            .assert_seed(
+                program_id,
+                &[EXTRA_ACCOUNT_METAS_SEED, nft_mint_account_info.key.as_ref()],
+            )
+            .error_log("Error @ extra_account_metas_info pda assertion")?;
+        let extra_account_metas_data = get_extra_account_metas_data();
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account_info.key,
+                &extra_account_metas_key,
+                rent_data.minimum_balance(extra_account_metas_data.len()),
+                extra_account_metas_data.len() as u64,
+                program_id,
+            ),
+            &[payer_account_info.clone(), extra_account_metas_info.clone()],
+            &[&[
+                EXTRA_ACCOUNT_METAS_SEED,
+                nft_mint_account_info.key.as_ref(),
+                &[extra_account_metas_bump],
+            ]],
+        )
+        .error_log("Error @ extra_account_metas_info creation")?;
+        extra_account_metas_info
+            .data
+            .borrow_mut()
+            .copy_from_slice(&extra_account_metas_data);
+
+        log!(log_level, 2, "create mint associated token account");
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account(
+                payer_account_info.key,
+                payer_account_info.key,
+                nft_mint_account_info.key,
+                spl_token_program_account_info.key,
+            ),
+            &[
+                payer_account_info.clone(),
+                associated_token_account_info.clone(),
+                payer_account_info.clone(),
+                nft_mint_account_info.clone(),
+                system_program_account_info.clone(),
+                spl_token_program_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ associated_token_account_info creation")?;
+
+        log!(log_level, 2, "Mint new Token-2022 token");
+        invoke_signed(
+            &spl_token_2022::instruction::mint_to(
+                &spl_token_2022::id(),
+                nft_mint_account_info.key,
+                associated_token_account_info.key,
+                &mint_authority_key,
+                &[],
+                1,
+            )?,
+            &[
+                nft_mint_account_info.clone(),
+                associated_token_account_info.clone(),
+                mint_authority_account_info.clone(),
+            ],
+            &[mint_authority_signer],
+        )
+        .error_log("Error @ minting token")?;
+
+        // Without an edition holding it, the mint authority is dropped to keep the supply at one.
+        log!(log_level, 2, "Removing the mint authority");
+        invoke_signed(
+            &spl_token_2022::instruction::set_authority(
+                &spl_token_2022::id(),
+                nft_mint_account_info.key,
+                None,
+                AuthorityType::MintTokens,
+                &mint_authority_key,
+                &[],
+            )?,
+            &[
+                nft_mint_account_info.clone(),
+                mint_authority_account_info.clone(),
+            ],
+            &[mint_authority_signer],
+        )
+        .error_log("Error @ mint authority removal")?;
+    } else {
+        log!(log_level, 2, "create mint associated token account");
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account(
+                payer_account_info.key,
+                payer_account_info.key,
+                nft_mint_account_info.key,
+                &spl_token_program_account_info.key,
+            ),
+            &[
+                payer_account_info.clone(),
+                associated_token_account_info.clone(),
+                payer_account_info.clone(),
+                nft_mint_account_info.clone(),
+                system_program_account_info.clone(),
+                spl_token_program_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ associated_token_account_info creation")?;
+
+        log!(log_level, 2, "Mint new token");
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                spl_token_program_account_info.key,
+                nft_mint_account_info.key,
+                associated_token_account_info.key,
+                &mint_authority_key,
+                &[],
+                1,
+            )?,
+            &[
+                nft_mint_account_info.clone(),
+                associated_token_account_info.clone(),
+                mint_authority_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+        )
+        .error_log("Error @ minting token")?;
+
+        log!(log_level, 2, "starting metadata creation");
+        invoke_signed(
+            &mpl_token_metadata::instruction::create_metadata_accounts_v3(
+                mpl_token_metadata_id,
+                nft_metadata_key,
+                *nft_mint_account_info.key,
+                *mint_authority_account_info.key,
+                *payer_account_info.key,
+                *mint_authority_account_info.key,
+                nft_name,
+                nft_symbol,
+                config_data.default_uri.clone(),
+                Some(creators),
+                config_data.creator_royalties,
+                true,
+                true,
+                Some(Collection {
+                    verified: false,
+                    key: ingl_nft_collection_key,
+                }),
+                None,
+                None,
+            ),
+            &[
+                nft_metadata_account_info.clone(),
+                nft_mint_account_info.clone(),
+                mint_authority_account_info.clone(),
+                payer_account_info.clone(),
+                mint_authority_account_info.clone(),
+                system_program_account_info.clone(),
+                sysvar_rent_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+        )
+        .error_log("Error @ nft_metadata_account_info creation")?;
+
+        log!(log_level, 2, "verifying collection");
+        invoke_signed(
+            &mpl_token_metadata::instruction::verify_collection(
+                mpl_token_metadata_id,
+                nft_metadata_key,
+                mint_authority_key,
+                *payer_account_info.key,
+                ingl_nft_collection_key,
+                collection_metadata_key,
+                ingl_collection_edition_key,
+                None,
+            ),
+            &[
+                nft_metadata_account_info.clone(),
+                mint_authority_account_info.clone(),
+                payer_account_info.clone(),
+                ingl_collection_mint_info.clone(),
+                ingl_collection_account_info.clone(),
+                ingl_edition_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+        )
+        .error_log("Error @ collection verification")?;
+
+        log!(log_level, 2, "Creating master Edition account...");
+        invoke_signed(
+            &mpl_token_metadata::instruction::create_master_edition_v3(
+                mpl_token_metadata_id,
+                nft_edition_key,
+                *nft_mint_account_info.key,
+                mint_authority_key,
+                mint_authority_key,
+                nft_metadata_key,
+                *payer_account_info.key,
+                None,
+            ),
+            &[
+                nft_edition_account_info.clone(),
+                nft_mint_account_info.clone(),
+                mint_authority_account_info.clone(),
+                mint_authority_account_info.clone(),
+                payer_account_info.clone(),
+                nft_metadata_account_info.clone(),
+                spl_token_program_account_info.clone(),
+                system_program_account_info.clone(),
+                sysvar_rent_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+        )
+        .error_log("Error @ master Edition creation")?;
+        log!(log_level, 2, "Delegate mint authority to metaplex...");
+        invoke(
+            &spl_token::instruction::approve(
+                &spl_token::id(),
+                associated_token_account_info.key,
+                &mint_authority_key,
+                payer_account_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                associated_token_account_info.clone(),
+                mint_authority_account_info.clone(),
+                payer_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ mint authority delegation")?;
+
+        log!(
+            log_level,
+            2,
+            "updating update_primary_sale_happened_via_token"
+        );
+        invoke(
+            &mpl_token_metadata::instruction::update_primary_sale_happened_via_token(
+                mpl_token_metadata::id(),
+                nft_metadata_key,
+                *payer_account_info.key,
+                *associated_token_account_info.key,
+            ),
+            &[
+                nft_metadata_account_info.clone(),
+                payer_account_info.clone(),
+                associated_token_account_info.clone(),
+            ],
+        )
+        .error_log("Error @ update_primary_sale_happened_via_token")?;
+    }
 
     let nft_account_data = NftData {
         validation_phrase: NFT_DATA_VAL_PHRASE,
@@ -431,8 +804,18 @@ pub fn process_mint_nft(
         funds_location: FundsLocation::Delegated,
         all_withdraws: Vec::new(),
         all_votes: BTreeMap::new(),
+        rarity_commitment: None,
+        reroll_count: 0,
+        last_reroll_date: None,
+        is_programmable: config_data.programmable_rule_set.is_some(),
+        last_known_owner: Some(*payer_account_info.key),
+        lock_until: None,
+        lock_boost_tier: None,
+        last_owner_seen_epoch: Some(clock_data.epoch),
         last_withdrawal_epoch: None,
         last_delegation_epoch: Some(clock_data.epoch),
+        weighted_backing,
+        boost_weight: 0,
     };
     let space = nft_account_data.get_space();
     let rent_lamports = rent_data.minimum_balance(space);
@@ -464,22 +847,191 @@ pub fn process_mint_nft(
         .serialize(&mut &mut general_account_info.data.borrow_mut()[..])
         .error_log("Error @ general_data serialization")?;
 
-    let freeze_nft_accounts = &[
+    let mut freeze_nft_accounts = vec![
         payer_account_info.clone(),
         nft_account_info.clone(),
         nft_mint_account_info.clone(),
         associated_token_account_info.clone(),
         mint_authority_account_info.clone(),
         nft_edition_account_info.clone(),
+        nft_metadata_account_info.clone(),
     ];
-    init_imprint_rarity(program_id, freeze_nft_accounts, log_level, false)
+    freeze_nft_accounts.extend(programmable_accounts.iter().flat_map(|x| x.infos()));
+    init_imprint_rarity(program_id, &freeze_nft_accounts, log_level, false)
         .error_log("error calling @freeze_nft_account")?;
 
     log!(log_level, 4, "nft account created!!!");
     Ok(())
 }
 
-fn init_imprint_rarity(
+///Enforces the rules of the active mint phase on the payer, and returns the price premium of the phase.
+/// Minting is unrestricted if no mint phases were set.
+fn enforce_mint_phase<'a>(
+    program_id: &Pubkey,
+    payer_account_info: &AccountInfo<'a>,
+    mint_phases_account_info: &AccountInfo<'a>,
+    minter_record_account_info: &AccountInfo<'a>,
+    allowlist_proof: &[[u8; 32]],
+    now: u32,
+    rent_data: &Rent,
+) -> Result<u64, ProgramError> {
+    mint_phases_account_info
+        .assert_seed(program_id, &[MINT_PHASES_SEED])
+        .error_log("Error @ mint_phases_account_info pda assertion")?;
+    if mint_phases_account_info.owner != program_id {
+        return Ok(0);
+    }
+    let mint_phases = Box::new(MintPhases::parse(mint_phases_account_info, program_id)?);
+    let (phase_index, phase) = match mint_phases.get_active_phase(now) {
+        Some(active_phase) => active_phase,
+        None => {
+            if mint_phases.phases.iter().any(|x| x.start_time > now) {
+                Err(InglError::TooEarly.utilize("No mint phase is currently open"))?
+            }
+            Err(InglError::TooLate.utilize("All mint phases have ended"))?
+        }
+    };
+
+    if let Some(allowlist_root) = phase.allowlist_root {
+        if !verify_merkle_proof(
+            allowlist_proof,
+            &allowlist_root,
+            hashv(&[payer_account_info.key.as_ref()]).to_bytes(),
+        ) {
+            Err(InglError::InvalidAllowlistProof.utilize("mint_nft"))?
+        }
+    }
+
+    if let Some(wallet_cap) = phase.wallet_cap {
+        let (_minter_record_key, minter_record_bump) = minter_record_account_info
+            .assert_seed(
+                program_id,
+                &[
+                    MINTER_RECORD_KEY,
+                    payer_account_info.key.as_ref(),
+                    &mint_phases.schedule_version.to_be_bytes(),
+                    &[phase_index],
+                ],
+            )
+            .error_log("Error @ minter_record_account_info pda assertion")?;
+        let mut minter_record = if minter_record_account_info.owner != program_id {
+            create_pda_account(
+                program_id,
+                payer_account_info,
+                None,
+                minter_record_account_info,
+                &[
+                    MINTER_RECORD_KEY,
+                    payer_account_info.key.as_ref(),
+                    &mint_phases.schedule_version.to_be_bytes(),
+                    &[phase_index],
+                    &[minter_record_bump],
+                ],
+                MinterRecord::get_space(),
+                rent_data,
+            )
+            .error_log("Error @ minter_record_account_info creation")?;
+            MinterRecord {
+                validation_phrase: MINTER_RECORD_VAL_PHRASE,
+                minted: 0,
+                minter: *payer_account_info.key,
+                schedule_version: mint_phases.schedule_version,
+                phase_index,
+            }
+        } else {
+            MinterRecord::parse(minter_record_account_info, program_id)?
+        };
+        if minter_record.minted >= wallet_cap {
+            Err(InglError::MintCapReached.utilize("mint_nft"))?
+        }
+        minter_record.minted += 1;
+        minter_record
+            .serialize(&mut &mut minter_record_account_info.data.borrow_mut()[..])
+            .error_log("Error @ minter_record serialization")?;
+    }
+
+    Ok(phase.price_premium)
+}
+
+///Adds a referral and its fee to the totals of the referrer, creating the referrer record if needed.
+fn record_referral<'a>(
+    program_id: &Pubkey,
+    payer_account_info: &AccountInfo<'a>,
+    referrer_account_info: &AccountInfo<'a>,
+    referrer_record_account_info: &AccountInfo<'a>,
+    referral_fee: u64,
+    rent_data: &Rent,
+) -> ProgramResult {
+    if referrer_account_info.key == payer_account_info.key {
+        Err(InglError::InvalidData.utilize("A minter can't refer themselves"))?
+    }
+    let (_referrer_record_key, referrer_record_bump) = referrer_record_account_info
+        .assert_seed(
+            program_id,
+            &[REFERRER_RECORD_KEY, referrer_account_info.key.as_ref()],
+        )
+        .error_log("Error @ referrer_record_account_info pda assertion")?;
+    let mut referrer_record = if referrer_record_account_info.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer_account_info,
+            None,
+            referrer_record_account_info,
+            &[
+                REFERRER_RECORD_KEY,
+                referrer_account_info.key.as_ref(),
+                &[referrer_record_bump],
+            ],
+            ReferrerRecord::get_space(),
+            rent_data,
+        )
+        .error_log("Error @ referrer_record_account_info creation")?;
+        ReferrerRecord {
+            validation_phrase: REFERRER_RECORD_VAL_PHRASE,
+            referrer: *referrer_account_info.key,
+            referrals: 0,
+            total_fees_earned: 0,
+        }
+    } else {
+        ReferrerRecord::parse(referrer_record_account_info, program_id)?
+    };
+    referrer_record.referrals += 1;
+    referrer_record.total_fees_earned += referral_fee;
+    referrer_record
+        .serialize(&mut &mut referrer_record_account_info.data.borrow_mut()[..])
+        .error_log("Error @ referrer_record serialization")?;
+    Ok(())
+}
+
+/// Metadata of a programmable gem, paying the creator royalties of the config on resales.
+pub fn get_programmable_asset_data(
+    config_data: &ValidatorConfig,
+    rule_set: Pubkey,
+    name: String,
+    symbol: String,
+    creators: Vec<Creator>,
+    collection_key: Pubkey,
+) -> AssetData {
+    AssetData {
+        name,
+        symbol,
+        uri: config_data.default_uri.clone(),
+        seller_fee_basis_points: config_data.creator_royalties,
+        creators: Some(creators),
+        primary_sale_happened: true,
+        is_mutable: true,
+        token_standard: TokenStandard::ProgrammableNonFungible,
+        collection: Some(Collection {
+            verified: false,
+            key: collection_key,
+        }),
+        uses: None,
+        collection_details: None,
+        rule_set: Some(rule_set),
+    }
+}
+
+pub fn init_imprint_rarity(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     log_level: u8,
@@ -493,6 +1045,15 @@ fn init_imprint_rarity(
     let associated_token_account_info = next_account_info(account_info_iter)?;
     let freeze_authority_account_info = next_account_info(account_info_iter)?;
     let nft_edition_account_info = next_account_info(account_info_iter)?;
+    let nft_metadata_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_data = NftData::validate(
+        NftData::deserialize(&mut &nft_account_info.data.borrow()[..])
+            .error_log("Error: Error desirializing NFT account data")?,
+    )
+    .error_log("Error: Invalid NFT Account")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
 
@@ -501,49 +1062,76 @@ fn init_imprint_rarity(
     payer_account_info
         .assert_signer()
         .error_log("@payer_authority_account_info")?;
+    let is_token_2022 = is_token_2022_gem(nft_mint_account_info);
+    let token_program_id = if is_token_2022 {
+        spl_token_2022::id()
+    } else {
+        spl_token::id()
+    };
     nft_mint_account_info
-        .assert_owner(&spl_token::id())
+        .assert_owner(&token_program_id)
         .error_log("Error: @mint_account_info ownership assertion")?;
     associated_token_account_info
-        .assert_owner(&spl_token::id())
+        .assert_owner(&token_program_id)
         .error_log("Error: @associated_token_account_info ownership assertion")?;
-    nft_edition_account_info
-        .assert_owner(&mpl_token_metadata::id())
-        .error_log("Error: @nft_edition_account_info ownership assertion")?;
+    if !is_token_2022 {
+        nft_edition_account_info
+            .assert_owner(&mpl_token_metadata::id())
+            .error_log("Error: @nft_edition_account_info ownership assertion")?;
+    }
 
     let (mint_authority_key, mint_authority_bump) = freeze_authority_account_info
         .assert_seed(&program_id, &[INGL_MINT_AUTHORITY_KEY.as_ref()])
         .error_log("Error: @freeze_authority_account_info pda assertion")?;
 
     associated_token_account_info
-        .assert_key_match(&get_associated_token_address(
+        .assert_key_match(&get_associated_token_address_with_program_id(
             payer_account_info.key,
             nft_mint_account_info.key,
+            &token_program_id,
         ))
         .error_log("Error: @associated_token_account_info assertion")?;
 
-    let associated_token_account_data =
-        Account::unpack(&associated_token_account_info.data.borrow())
-            .error_log("Error: @associated_token_account_info data unpacking")?;
+    let associated_token_account_data = unpack_token_account(associated_token_account_info)
+        .error_log("Error: @associated_token_account_info data unpacking")?;
     if associated_token_account_data.amount != 1 {
         Err(ProgramError::InsufficientFunds)?
     }
-    if associated_token_account_data.is_frozen() {
+
+    // The token account of a programmable nft is always frozen, its lock lives in the token record.
+    if !nft_data.is_programmable && associated_token_account_data.is_frozen() {
         Err(TokenError::AccountFrozen)?
     }
 
-    let mut nft_data = NftData::validate(
-        NftData::deserialize(&mut &nft_account_info.data.borrow()[..])
-            .error_log("Error: Error desirializing NFT account data")?,
-    )
-    .error_log("Error: Invalid NFT Account")?;
-
     if let Some(_) = nft_data.rarity_seed_slot {
         Err(ProgramError::InvalidAccountData).error_log("@nft_data rarity seed time already set")?
     }
 
     log!(log_level, 0, "Done with account assertions ...");
 
+    nft_data.rarity_seed_slot = Some(clock_data.slot + RARITY_IMPRINT_WAIT_SLOTS);
+
+    nft_data.serialize(&mut &mut nft_account_info.data.borrow_mut()[..])?;
+
+    if is_token_2022 {
+        log!(log_level, 2, "Freezing the Token-2022 token account ...");
+        set_token_2022_freeze(
+            true,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: freeze_authority_account_info,
+                mint_authority_bump,
+                mint: nft_mint_account_info,
+                token: associated_token_account_info,
+                metadata: nft_mint_account_info,
+                edition: nft_edition_account_info,
+            },
+        )?;
+        log!(log_level, 2, "Token-2022 token account frozen!!!");
+        log!(log_level, 4, "Freeze nft account !!!");
+        return Ok(());
+    }
+
     let mpl_token_metadata_program_id = mpl_token_metadata::id();
     let (nft_edition_key, _nft_edition_bump) = nft_edition_account_info
         .assert_seed(
@@ -557,29 +1145,49 @@ fn init_imprint_rarity(
         )
         .error_log("Error: @edition_account_info")?;
 
-    nft_data.rarity_seed_slot = Some(clock_data.slot + RARITY_IMPRINT_WAIT_SLOTS);
-
-    nft_data.serialize(&mut &mut nft_account_info.data.borrow_mut()[..])?;
-
-    log!(log_level, 2, "Freezing the associated token account ...");
-    invoke_signed(
-        &mpl_token_metadata::instruction::freeze_delegated_account(
-            mpl_token_metadata_program_id,
-            mint_authority_key,
-            *associated_token_account_info.key,
-            nft_edition_key,
-            *nft_mint_account_info.key,
-        ),
-        &[
-            freeze_authority_account_info.clone(),
-            associated_token_account_info.clone(),
-            nft_edition_account_info.clone(),
-            nft_mint_account_info.clone(),
-        ],
-        &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
-    )
-    .error_log("Error while freezing associated_token_account")?;
-    log!(log_level, 2, "Associated token account frozen!!!");
+    if let Some(programmable_accounts) = &programmable_accounts {
+        log!(log_level, 2, "Locking the programmable nft ...");
+        nft_metadata_account_info
+            .assert_key_match(&find_metadata_account(nft_mint_account_info.key).0)
+            .error_log("Error: @nft_metadata_account_info")?;
+        programmable_accounts
+            .verify(nft_mint_account_info.key, associated_token_account_info.key)
+            .error_log("Error: @programmable accounts assertion")?;
+        set_programmable_lock(
+            true,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: freeze_authority_account_info,
+                mint_authority_bump,
+                mint: nft_mint_account_info,
+                token: associated_token_account_info,
+                metadata: nft_metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+            programmable_accounts,
+        )?;
+        log!(log_level, 2, "Programmable nft locked!!!");
+    } else {
+        log!(log_level, 2, "Freezing the associated token account ...");
+        invoke_signed(
+            &mpl_token_metadata::instruction::freeze_delegated_account(
+                mpl_token_metadata_program_id,
+                mint_authority_key,
+                *associated_token_account_info.key,
+                nft_edition_key,
+                *nft_mint_account_info.key,
+            ),
+            &[
+                freeze_authority_account_info.clone(),
+                associated_token_account_info.clone(),
+                nft_edition_account_info.clone(),
+                nft_mint_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+        )
+        .error_log("Error while freezing associated_token_account")?;
+        log!(log_level, 2, "Associated token account frozen!!!");
+    }
 
     log!(log_level, 4, "Freeze nft account !!!");
     Ok(())
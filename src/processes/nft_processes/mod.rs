@@ -1,5 +1,11 @@
+pub mod close_minter_record;
+pub mod commit_rarity_seed;
 pub mod delegate_nft;
 pub mod imprint_rarity;
+pub mod lock_nft;
 pub mod mint_nft;
 pub mod redeem_nft;
+pub mod reroll_rarity;
+pub mod sync_metadata;
 pub mod undelegate_nft;
+pub mod unlock_nft;
@@ -2,10 +2,18 @@ use crate::{
     error::InglError,
     log,
     state::{constants::*, FundsLocation, GeneralData, NftData, ValidatorConfig},
-    utils::{get_clock_data, verify_nft_ownership, AccountInfoHelpers, OptionExt, ResultExt},
+    token_2022::unpack_token_account,
+    utils::{
+        get_clock_data, get_token_program_id, is_token_2022_gem, set_programmable_lock,
+        set_token_2022_freeze, verify_nft_ownership, AccountInfoHelpers, GemAccounts, OptionExt,
+        ProgrammableAccounts, ResultExt,
+    },
 };
 
-use mpl_token_metadata::state::PREFIX;
+use mpl_token_metadata::{
+    instruction::{builders::BurnBuilder, BurnArgs, InstructionBuilder},
+    state::{TokenMetadataAccount, TokenRecord, PREFIX},
+};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
@@ -36,21 +44,29 @@ pub fn redeem_nft(
     let vote_account_info = next_account_info(account_info_iter)?;
     let mint_authority_account_info = next_account_info(account_info_iter)?;
 
+    let nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("@nft_account_info decode_unchecked validation")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
+
     let clock_data =
         get_clock_data(account_info_iter, clock_is_from_account).error_log("sysvar_clock_data")?;
 
-    nft_metadata_account_info
-        .assert_owner(&mpl_token_metadata::id())
-        .error_log("@assert nft metadata account owner")?;
-    edition_account_info
-        .assert_owner(&mpl_token_metadata::id())
-        .error_log("@assert edition account owner")?;
+    let is_token_2022 = is_token_2022_gem(mint_account_info);
+    if !is_token_2022 {
+        nft_metadata_account_info
+            .assert_owner(&mpl_token_metadata::id())
+            .error_log("@assert nft metadata account owner")?;
+        edition_account_info
+            .assert_owner(&mpl_token_metadata::id())
+            .error_log("@assert edition account owner")?;
+    }
     ingl_nft_collection_metadata_account_info
         .assert_owner(&mpl_token_metadata::id())
         .error_log("@assert ingl nft collection metadata account owner")?;
 
     spl_token_program_account_info
-        .assert_key_match(&spl_token::id())
+        .assert_key_match(&get_token_program_id(mint_account_info)?)
         .error_log("spl_token_program_account_info")?;
 
     config_account_info
@@ -127,8 +143,9 @@ pub fn redeem_nft(
             .assert_seed(&mpl_token_metadata_id, metadata_seeds)
             .error_log("@assert ingl nft collection metadata")?;
 
-    let nft_data = NftData::parse(&nft_account_data_info, program_id)
-        .error_log("@nft_account_info decode_unchecked validation")?;
+    if nft_data.lock_until.is_some() {
+        Err(InglError::NftLocked.utilize("Unlock the gem before redeeming it"))?
+    }
     let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
     let general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
 
@@ -180,56 +197,158 @@ pub fn redeem_nft(
     .error_log("@invoke system_intruction transfer")?;
     log!(log_level, 2, "Transfered Funds to user!!!");
 
-    let associated_token_address_data =
-    Account::unpack(&associated_token_account_info.data.borrow())
-        .error_log("failed to unpack associated_token_account_info")?;
+    if let Some(programmable_accounts) = &programmable_accounts {
+        programmable_accounts
+            .verify(mint_account_info.key, associated_token_account_info.key)
+            .error_log("Error: @programmable accounts assertion")?;
+        let token_record = TokenRecord::from_account_info(programmable_accounts.token_record)
+            .error_log("failed to deserialize token_record_account_info")?;
+        if token_record.is_locked() {
+            log!(log_level, 2, "Unlocking the programmable nft ...");
+            set_programmable_lock(
+                false,
+                &GemAccounts {
+                    payer: payer_account_info,
+                    mint_authority: mint_authority_account_info,
+                    mint_authority_bump,
+                    mint: mint_account_info,
+                    token: associated_token_account_info,
+                    metadata: nft_metadata_account_info,
+                    edition: edition_account_info,
+                },
+                programmable_accounts,
+            )?;
+            log!(log_level, 2, "Programmable nft unlocked !!!");
+        }
 
-    if let AccountState::Frozen = associated_token_address_data.state {
-        log!(log_level, 2, "Thawing the token account ...");
-        invoke_signed(
-            &mpl_token_metadata::instruction::thaw_delegated_account(
+        log!(log_level, 2, "Burn the programmable nft ...");
+        let burn_instruction = BurnBuilder::new()
+            .authority(*payer_account_info.key)
+            .collection_metadata(ingl_nft_collection_metadata_key)
+            .metadata(nft_metadata_key)
+            .edition(edition_key)
+            .mint(*mint_account_info.key)
+            .token(*associated_token_account_info.key)
+            .token_record(*programmable_accounts.token_record.key)
+            .build(BurnArgs::V1 { amount: 1 })
+            .map_err(|_| InglError::InvalidData.utilize("Burn instruction"))?
+            .instruction();
+        let mut account_infos = vec![
+            payer_account_info.clone(),
+            ingl_nft_collection_metadata_account_info.clone(),
+            nft_metadata_account_info.clone(),
+            edition_account_info.clone(),
+            mint_account_info.clone(),
+            associated_token_account_info.clone(),
+        ];
+        account_infos.extend(programmable_accounts.infos());
+        invoke(&burn_instruction, &account_infos)
+            .error_log("@invoke mpl_token burn programmable nft")?;
+    } else if is_token_2022 {
+        if unpack_token_account(associated_token_account_info)
+            .error_log("failed to unpack associated_token_account_info")?
+            .is_frozen()
+        {
+            log!(log_level, 2, "Thawing the Token-2022 token account ...");
+            set_token_2022_freeze(
+                false,
+                &GemAccounts {
+                    payer: payer_account_info,
+                    mint_authority: mint_authority_account_info,
+                    mint_authority_bump,
+                    mint: mint_account_info,
+                    token: associated_token_account_info,
+                    metadata: nft_metadata_account_info,
+                    edition: edition_account_info,
+                },
+            )?;
+            log!(log_level, 2, "Token-2022 token account thawed !!!");
+        }
+
+        log!(log_level, 2, "Burn the Token-2022 nft ...");
+        invoke(
+            &spl_token_2022::instruction::burn(
+                &spl_token_2022::id(),
+                associated_token_account_info.key,
+                mint_account_info.key,
+                payer_account_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                associated_token_account_info.clone(),
+                mint_account_info.clone(),
+                payer_account_info.clone(),
+            ],
+        )
+        .error_log("@invoke spl_token_2022 burn nft")?;
+        invoke(
+            &spl_token_2022::instruction::close_account(
+                &spl_token_2022::id(),
+                associated_token_account_info.key,
+                payer_account_info.key,
+                payer_account_info.key,
+                &[],
+            )?,
+            &[
+                associated_token_account_info.clone(),
+                payer_account_info.clone(),
+                payer_account_info.clone(),
+            ],
+        )
+        .error_log("@invoke spl_token_2022 close token account")?;
+    } else {
+        let associated_token_address_data =
+            Account::unpack(&associated_token_account_info.data.borrow())
+                .error_log("failed to unpack associated_token_account_info")?;
+
+        if let AccountState::Frozen = associated_token_address_data.state {
+            log!(log_level, 2, "Thawing the token account ...");
+            invoke_signed(
+                &mpl_token_metadata::instruction::thaw_delegated_account(
+                    mpl_token_metadata_id,
+                    mint_authority_key,
+                    *associated_token_account_info.key,
+                    edition_key,
+                    *mint_account_info.key,
+                ),
+                &[
+                    mint_authority_account_info.clone(),
+                    associated_token_account_info.clone(),
+                    edition_account_info.clone(),
+                    mint_account_info.clone(),
+                    spl_token_program_account_info.clone(),
+                ],
+                &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
+            )
+            .error_log("Error: @ thawing the token account")?;
+            log!(log_level, 2, "Token account thawed !!!");
+        }
+
+        log!(log_level, 2, "Burn the nft ...");
+        invoke(
+            &mpl_token_metadata::instruction::burn_nft(
                 mpl_token_metadata_id,
-                mint_authority_key,
+                nft_metadata_key,
+                *payer_account_info.key,
+                *mint_account_info.key,
                 *associated_token_account_info.key,
                 edition_key,
-                *mint_account_info.key,
+                spl_token::id(),
+                Some(ingl_nft_collection_metadata_key),
             ),
             &[
-                mint_authority_account_info.clone(),
+                nft_metadata_account_info.clone(),
+                payer_account_info.clone(),
+                mint_account_info.clone(),
                 associated_token_account_info.clone(),
                 edition_account_info.clone(),
-                mint_account_info.clone(),
                 spl_token_program_account_info.clone(),
+                ingl_nft_collection_metadata_account_info.clone(),
             ],
-            &[&[INGL_MINT_AUTHORITY_KEY.as_ref(), &[mint_authority_bump]]],
         )
-        .error_log("Error: @ thawing the token account")?;
-        log!(log_level, 2, "Token account thawed !!!");
+        .error_log("@invoke mpl_token burn nft")?;
     }
-
-    log!(log_level, 2, "Burn the nft ...");
-    invoke(
-        &mpl_token_metadata::instruction::burn_nft(
-            mpl_token_metadata_id,
-            nft_metadata_key,
-            *payer_account_info.key,
-            *mint_account_info.key,
-            *associated_token_account_info.key,
-            edition_key,
-            spl_token::id(),
-            Some(ingl_nft_collection_metadata_key),
-        ),
-        &[
-            nft_metadata_account_info.clone(),
-            payer_account_info.clone(),
-            mint_account_info.clone(),
-            associated_token_account_info.clone(),
-            edition_account_info.clone(),
-            spl_token_program_account_info.clone(),
-            ingl_nft_collection_metadata_account_info.clone(),
-        ],
-    )
-    .error_log("@invoke mpl_token burn nft")?;
     log!(log_level, 2, "Burned the nft!!!");
 
     let dest_starting_lamports = payer_account_info.lamports();
@@ -0,0 +1,220 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::{
+        nft_processes::imprint_rarity::{get_rarity_uri, update_gem_uri},
+        rewards_processes::nft_withdraw::has_unclaimed_rewards,
+    },
+    state::{
+        constants::*, get_seed_part, FundsLocation, GeneralData, NftData, UriProof, UrisAccount,
+        ValidatorConfig,
+    },
+    utils::{
+        assert_last_top_level_instruction, get_clock_data, get_latest_slot_hash, is_token_2022_gem,
+        verify_nft_ownership, AccountInfoHelpers, GemAccounts, OptionExt, ProgrammableAccounts,
+        ResultExt,
+    },
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hashv,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+///Charges the reroll fee and imprints a new rarity on an imprinted NFT, updating its metadata uri.
+/// The new rarity is seeded by the newest slot hash, so the reroll must be the last top-level instruction of its
+/// transaction, otherwise a later instruction could revert the rerolls whose outcome the holder doesn't like.
+pub fn reroll_rarity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    uri_proof: Option<UriProof>,
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Rerolling nft rarity ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let nft_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let mint_authority_account_info = next_account_info(account_info_iter)?;
+    let nft_edition_account_info = next_account_info(account_info_iter)?;
+    let nft_metadata_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+    let slot_hashes_account_info = next_account_info(account_info_iter)?;
+    let sysvar_instructions_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_data = NftData::parse(nft_account_info, program_id)
+        .error_log("Error: @nft_account_info deserialization")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+    assert_last_top_level_instruction(sysvar_instructions_account_info)?;
+
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error: @config_account_info pda assertion")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error: @uris_account_info pda assertion")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error: @general_account_info pda assertion")?;
+    let (_mint_authority_key, mint_authority_bump) = mint_authority_account_info
+        .assert_seed(program_id, &[INGL_MINT_AUTHORITY_KEY])
+        .error_log("Error: @mint_authority_account_info pda assertion")?;
+    // Token-2022 gems keep their metadata in their mint and have no edition.
+    if !is_token_2022_gem(mint_account_info) {
+        nft_edition_account_info
+            .assert_owner(&mpl_token_metadata::id())
+            .error_log("Error: @nft_edition_account_info ownership")?;
+        nft_metadata_account_info
+            .assert_key_match(
+                &mpl_token_metadata::pda::find_metadata_account(mint_account_info.key).0,
+            )
+            .error_log("Error: @nft_metadata_account_info assertion")?;
+    }
+    system_program_account_info
+        .assert_key_match(&system_program::id())
+        .error_log("Error: @system_program_account_info")?;
+
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    let uris_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+    if nft_data.lock_until.is_some() {
+        Err(InglError::NftLocked.utilize("Unlock the gem before rerolling it"))?
+    }
+
+    vote_account_info
+        .assert_key_match(&config_data.vote_account)
+        .error_log("Error @ Vote account address verification")?;
+
+    let reroll_fee = config_data
+        .rarity_reroll_fee
+        .error_log("Error: Rarity rerolls are disabled for this validator")?;
+    nft_data
+        .rarity
+        .error_log("Error: Rarity must be imprinted before rerolling")?;
+
+    let now = clock_data.unix_timestamp as u32;
+    if let Some(last_reroll_date) = nft_data.last_reroll_date {
+        if now < last_reroll_date + config_data.rarity_reroll_cooldown {
+            Err(InglError::TooEarly.utilize("Rarity reroll cooldown hasn't elapsed yet"))?
+        }
+    }
+    let is_delegated = matches!(nft_data.funds_location, FundsLocation::Delegated);
+    // The reward weight changes with the rarity, so rewards accrued under the current weight must be withdrawn first.
+    if is_delegated && has_unclaimed_rewards(&nft_data, &general_data)? {
+        Err(InglError::InvalidData
+            .utilize("Withdraw the accrued rewards before rerolling the rarity"))?
+    }
+
+    log!(
+        log_level,
+        2,
+        "Transferring the reroll fee to the vote account ..."
+    );
+    invoke(
+        &system_instruction::transfer(payer_account_info.key, vote_account_info.key, reroll_fee),
+        &[payer_account_info.clone(), vote_account_info.clone()],
+    )
+    .error_log("Error @ reroll fee transfer")?;
+    log!(log_level, 2, "Transferred the reroll fee !!!");
+
+    nft_data.reroll_count = nft_data
+        .reroll_count
+        .checked_add(1)
+        .error_log("Error @ reroll_count increment")?;
+    nft_data.last_reroll_date = Some(now);
+    let (seed_slot, seed, seed_hash) =
+        get_reroll_seed(slot_hashes_account_info, mint_account_info.key, &nft_data)?;
+    let rarity = uris_data.get_rarity(seed);
+    let nft_rarity_uri = get_rarity_uri(
+        program_id,
+        &uris_data,
+        rarity,
+        seed,
+        &seed_hash,
+        uri_proof,
+        account_info_iter,
+    )?;
+
+    log!(log_level, 2, "Updating metadata account ...");
+    update_gem_uri(
+        &GemAccounts {
+            payer: payer_account_info,
+            mint_authority: mint_authority_account_info,
+            mint_authority_bump,
+            mint: mint_account_info,
+            token: associated_token_account_info,
+            metadata: nft_metadata_account_info,
+            edition: nft_edition_account_info,
+        },
+        programmable_accounts.as_ref(),
+        nft_rarity_uri,
+    )?;
+    log!(log_level, 2, "Metadata account updated!!!");
+
+    if is_delegated {
+        let weighted_backing =
+            config_data.get_weighted_backing(uris_data.get_reward_weight(Some(rarity)));
+        general_data.total_weighted_delegated = general_data
+            .total_weighted_delegated
+            .checked_sub(nft_data.weighted_backing)
+            .error_log("Error @ general_data.total_weighted_delegated recalc")?
+            .checked_add(weighted_backing)
+            .error_log("Error @ general_data.total_weighted_delegated recalc")?;
+        nft_data.weighted_backing = weighted_backing;
+    }
+
+    nft_data.rarity = Some(rarity);
+    nft_data.rarity_seed_slot = Some(seed_slot);
+    nft_data.rarity_commitment = None;
+    nft_data
+        .serialize(&mut &mut nft_account_info.data.borrow_mut()[..])
+        .error_log("Error: @nft_account_info serialization")?;
+    general_data
+        .serialize(&mut &mut general_account_info.data.borrow_mut()[..])
+        .error_log("Error: @general_account_info serialization")?;
+
+    log!(log_level, 4, "Rerolled nft rarity !!!");
+    Ok(())
+}
+
+/// Derives the rarity seed of a reroll from the newest slot hash, the mint and the reroll count,
+/// so that each reroll of an NFT draws a different seed. Returns the slot of the hash along with the seed and its hash.
+pub fn get_reroll_seed(
+    slot_hashes_account_info: &AccountInfo,
+    mint_key: &Pubkey,
+    nft_data: &NftData,
+) -> Result<(u64, u16, [u8; 32]), ProgramError> {
+    let (slot, slot_hash) = get_latest_slot_hash(slot_hashes_account_info)?;
+    let seed_hash = hashv(&[
+        slot_hash.as_ref(),
+        mint_key.as_ref(),
+        &nft_data.reroll_count.to_le_bytes(),
+    ])
+    .to_bytes();
+    let seed = get_seed_part(&seed_hash, RARITY_SEED_OFFSET) % CUMMULATED_RARITY as u64;
+    Ok((slot, seed as u16, seed_hash))
+}
@@ -0,0 +1,244 @@
+use crate::{
+    error::InglError,
+    log,
+    state::{constants::*, NftData, UrisAccount},
+    token_2022::{update_token_metadata_field, TokenMetadataField, TokenMetadataFields},
+    utils::{is_token_2022_gem, AccountInfoHelpers, ProgrammableAccounts, ResultExt},
+};
+
+use borsh::BorshDeserialize;
+use mpl_token_metadata::{
+    instruction::{
+        builders::UpdateBuilder, CollectionDetailsToggle, CollectionToggle, InstructionBuilder,
+        RuleSetToggle, UpdateArgs, UsesToggle,
+    },
+    state::{Data, DataV2, Metadata, MAX_NAME_LENGTH, MAX_URI_LENGTH, PREFIX},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+};
+
+///Refreshes a gem's metadata with its on-chain state: the rarity name is appended to its name,
+///and its delegation status, rarity and total withdrawn rewards are set as query attributes of its uri.
+///Permissionless, the payer only pays for the metadata update.
+pub fn sync_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+) -> ProgramResult {
+    log!(log_level, 4, "Sync metadata ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let nft_edition_account_info = next_account_info(account_info_iter)?;
+    let mint_authority_account_info = next_account_info(account_info_iter)?;
+    let uris_account_info = next_account_info(account_info_iter)?;
+
+    let nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error @ nft_account_data_info deserialization")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    nft_account_data_info
+        .assert_seed(
+            program_id,
+            &[NFT_ACCOUNT_CONST, mint_account_info.key.as_ref()],
+        )
+        .error_log("Error @ nft_account_data_info pda assertion")?;
+    uris_account_info
+        .assert_seed(program_id, &[URIS_ACCOUNT_SEED])
+        .error_log("Error @ uris_account_info pda assertion")?;
+    let (mint_authority_key, mint_authority_bump) = mint_authority_account_info
+        .assert_seed(program_id, &[INGL_MINT_AUTHORITY_KEY])
+        .error_log("Error @ mint_authority_account_info pda assertion")?;
+    let mpl_token_metadata_id = mpl_token_metadata::id();
+    let (nft_metadata_key, _nft_metadata_bump) = metadata_account_info
+        .assert_seed(
+            &mpl_token_metadata_id,
+            &[
+                PREFIX.as_bytes(),
+                mpl_token_metadata_id.as_ref(),
+                mint_account_info.key.as_ref(),
+            ],
+        )
+        .error_log("Error @ metadata_account_info pda assertion")?;
+    let (nft_edition_key, _nft_edition_bump) = nft_edition_account_info
+        .assert_seed(
+            &mpl_token_metadata_id,
+            &[
+                PREFIX.as_bytes(),
+                mpl_token_metadata_id.as_ref(),
+                mint_account_info.key.as_ref(),
+                b"edition",
+            ],
+        )
+        .error_log("Error @ nft_edition_account_info pda assertion")?;
+
+    let uris_data = Box::new(UrisAccount::parse(uris_account_info, program_id)?);
+    // Token-2022 gems keep their metadata in their mint.
+    let gem_metadata = if is_token_2022_gem(mint_account_info) {
+        None
+    } else {
+        Some(
+            Metadata::deserialize(&mut &metadata_account_info.data.borrow()[..])
+                .error_log("Error @ metadata deserialization")?,
+        )
+    };
+    let (current_name, current_uri) = match &gem_metadata {
+        Some(gem_metadata) => (
+            gem_metadata.data.name.clone(),
+            gem_metadata.data.uri.clone(),
+        ),
+        None => {
+            let token_metadata = TokenMetadataFields::read(mint_account_info)?;
+            (token_metadata.name, token_metadata.uri)
+        }
+    };
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    let rarity_name = nft_data
+        .rarity
+        .and_then(|rarity| uris_data.rarity_names.get(rarity as usize));
+    let base_name = current_name
+        .trim_matches(char::from(0))
+        .split(" | ")
+        .next()
+        .unwrap_or_default();
+    let name = match rarity_name {
+        Some(rarity_name) if base_name.len() + 3 + rarity_name.len() <= MAX_NAME_LENGTH => {
+            format!("{} | {}", base_name, rarity_name)
+        }
+        _ => base_name.to_string(),
+    };
+    let uri = format!(
+        "{}{}",
+        current_uri
+            .trim_matches(char::from(0))
+            .split('?')
+            .next()
+            .unwrap_or_default(),
+        nft_data.get_metadata_attributes(rarity_name)
+    );
+    if uri.len() > MAX_URI_LENGTH {
+        Err(InglError::InvalidData.utilize("The synced metadata uri is too long"))?
+    }
+    log!(
+        log_level,
+        2,
+        "Syncing metadata: name: {}, uri: {}",
+        name,
+        uri
+    );
+
+    let gem_metadata = match gem_metadata {
+        Some(gem_metadata) => gem_metadata,
+        None => {
+            for (field, value) in [
+                (TokenMetadataField::Name, name),
+                (TokenMetadataField::Uri, uri),
+            ] {
+                invoke_signed(
+                    &update_token_metadata_field(
+                        mint_account_info.key,
+                        &mint_authority_key,
+                        field,
+                        value,
+                    )?,
+                    &[
+                        mint_account_info.clone(),
+                        mint_authority_account_info.clone(),
+                    ],
+                    &[&[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]]],
+                )
+                .error_log("Error: @ updating the token metadata")?;
+            }
+            log!(log_level, 4, "Synced metadata!!!");
+            return Ok(());
+        }
+    };
+
+    if let Some(programmable_accounts) = &programmable_accounts {
+        let mut update_builder = UpdateBuilder::new();
+        update_builder
+            .authority(mint_authority_key)
+            .mint(*mint_account_info.key)
+            .metadata(nft_metadata_key)
+            .edition(nft_edition_key)
+            .payer(*payer_account_info.key);
+        if let Some(rule_set) = programmable_accounts.rule_set() {
+            update_builder.authorization_rules(rule_set);
+        }
+        let update_instruction = update_builder
+            .build(UpdateArgs::V1 {
+                new_update_authority: None,
+                data: Some(Data {
+                    uri,
+                    name,
+                    symbol: gem_metadata.data.symbol,
+                    creators: gem_metadata.data.creators,
+                    seller_fee_basis_points: gem_metadata.data.seller_fee_basis_points,
+                }),
+                primary_sale_happened: None,
+                is_mutable: None,
+                collection: CollectionToggle::None,
+                collection_details: CollectionDetailsToggle::None,
+                uses: UsesToggle::None,
+                rule_set: RuleSetToggle::None,
+                authorization_data: None,
+            })
+            .map_err(|_| InglError::InvalidData.utilize("Update instruction"))?
+            .instruction();
+        let mut account_infos = vec![
+            mint_authority_account_info.clone(),
+            mint_account_info.clone(),
+            metadata_account_info.clone(),
+            nft_edition_account_info.clone(),
+            payer_account_info.clone(),
+        ];
+        account_infos.extend(programmable_accounts.infos());
+        invoke_signed(
+            &update_instruction,
+            &account_infos,
+            &[&[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]]],
+        )
+        .error_log("Error: @ updating programmable metadata account")?;
+    } else {
+        invoke_signed(
+            &mpl_token_metadata::instruction::update_metadata_accounts_v2(
+                mpl_token_metadata_id,
+                nft_metadata_key,
+                mint_authority_key,
+                None,
+                Some(DataV2 {
+                    uri,
+                    name,
+                    uses: gem_metadata.uses,
+                    symbol: gem_metadata.data.symbol,
+                    collection: gem_metadata.collection,
+                    creators: gem_metadata.data.creators,
+                    seller_fee_basis_points: gem_metadata.data.seller_fee_basis_points,
+                }),
+                None,
+                None,
+            ),
+            &[
+                metadata_account_info.clone(),
+                mint_authority_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]]],
+        )
+        .error_log("Error: @ updating metadata account")?;
+    }
+
+    log!(log_level, 4, "Synced metadata!!!");
+    Ok(())
+}
@@ -3,8 +3,11 @@ use std::slice::Iter;
 use crate::{
     error::InglError,
     log,
-    processes::rewards_processes::nft_withdraw::nft_withdraw,
-    state::{constants::*, FundsLocation, GeneralData, GovernanceData, NftData, ValidatorConfig},
+    processes::rewards_processes::nft_withdraw::{has_unclaimed_rewards, nft_withdraw},
+    state::{
+        constants::*, FundsLocation, GeneralData, GovernanceData, GovernanceEvent, NftData,
+        ValidatorConfig,
+    },
     utils::{verify_nft_ownership, AccountInfoHelpers, OptionExt, ResultExt},
 };
 
@@ -33,6 +36,7 @@ pub fn undelegate_nft(
     let general_account_info = next_account_info(account_info_iter)?;
     let system_program_account_info = next_account_info(account_info_iter)?;
     let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let reward_claim_account_info = next_account_info(account_info_iter)?;
 
     system_program_account_info
         .assert_key_match(&solana_program::system_program::id())
@@ -76,10 +80,29 @@ pub fn undelegate_nft(
         associated_token_account_info.clone(),
         mint_account_info.clone(),
         nft_account_data_info.clone(),
-        system_program_account_info.clone(),
+        reward_claim_account_info.clone(),
     ]);
     let mut general_account_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
-    let mut nft_account_data = NftData::parse(&nft_account_data_info, program_id)
+    let nft_account_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error @gem_account_data_info decoding")?;
+    if nft_account_data.lock_until.is_some() {
+        Err(InglError::NftLocked.utilize("Unlock the gem before undelegating it"))?
+    }
+
+    //preventing attempt to withdraw when no rewards are available.
+    if has_unclaimed_rewards(&nft_account_data, &general_account_data)? {
+        nft_withdraw(
+            program_id,
+            &nft_withdraw_accounts,
+            1,
+            log_level,
+            clock_is_from_account,
+            rent_is_from_account,
+        )
+        .error_log("Error: @nft_withdraw")?;
+    }
+    // nft_withdraw updates the gem's withdrawal history and holder, so it's decoded again.
+    let mut nft_account_data = NftData::parse(nft_account_data_info, program_id)
         .error_log("Error @gem_account_data_info decoding")?;
 
     let interested_proposals: Vec<&u32> = general_account_data
@@ -96,43 +119,17 @@ pub fn undelegate_nft(
         log_level,
     )?;
 
-    let interested_epoch = if let Some(tmp) = nft_account_data.last_withdrawal_epoch {
-        tmp.max(
-            nft_account_data
-                .last_delegation_epoch
-                .error_log("Error: Last delegation epoch can't be None at this stage")?,
-        )
-    } else {
-        nft_account_data
-            .last_delegation_epoch
-            .error_log("Error: Last delegation epoch can't be None at this stage")?
-    };
-
-    //preventing attempt to withdraw when no rewards are available.
-    if (general_account_data.vote_rewards.len() > 0)
-        && (general_account_data
-            .vote_rewards
-            .last()
-            .error_log("vote_rewards can't be empty here")?
-            .epoch_number
-            > interested_epoch)
-    {
-        nft_withdraw(
-            program_id,
-            &nft_withdraw_accounts,
-            1,
-            log_level,
-            clock_is_from_account,
-            rent_is_from_account,
-        )
-        .error_log("Error: @nft_withdraw")?;
-    }
     let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
 
     general_account_data.total_delegated = general_account_data
         .total_delegated
         .checked_sub(config_data.unit_backing)
         .error_log("Error: @ general_data.total_delegated recalc")?;
+    general_account_data.total_weighted_delegated = general_account_data
+        .total_weighted_delegated
+        .checked_sub(nft_account_data.weighted_backing)
+        .error_log("Error: @ general_data.total_weighted_delegated recalc")?;
+    nft_account_data.weighted_backing = 0;
 
     if general_account_data.pending_delegation_total >= config_data.unit_backing {
         general_account_data.pending_delegation_total = general_account_data
@@ -203,6 +200,11 @@ fn handle_vote_reversal(
         governance_data
             .serialize(&mut &mut proposal_account_info.data.borrow_mut()[..])
             .error_log("Error: @governance_data serialization")?;
+        GovernanceEvent::VoteWithdrawn {
+            proposal: **proposal_numeration,
+            nft: nft_data.numeration,
+        }
+        .emit()?;
     }
 
     Ok(())
@@ -0,0 +1,169 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::rewards_processes::nft_withdraw::has_unclaimed_rewards,
+    state::{constants::*, GeneralData, NftData},
+    utils::{
+        get_clock_data, get_token_program_id, is_token_2022_gem, set_programmable_lock,
+        set_token_2022_freeze, verify_nft_ownership, AccountInfoHelpers, GemAccounts, OptionExt,
+        ProgrammableAccounts, ResultExt,
+    },
+};
+
+use borsh::BorshSerialize;
+use mpl_token_metadata::{pda::find_metadata_account, state::PREFIX};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+};
+
+///Unlocks a gem whose lock expired, thawing its token account and ending its lock boost.
+pub fn unlock_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+    clock_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Unlock nft ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let mint_authority_account_info = next_account_info(account_info_iter)?;
+    let nft_edition_account_info = next_account_info(account_info_iter)?;
+    let nft_metadata_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error: @nft_account_data_info deserialization")?;
+    let programmable_accounts =
+        ProgrammableAccounts::collect(account_info_iter, nft_data.is_programmable)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error: @general_account_info pda assertion")?;
+    let (mint_authority_key, mint_authority_bump) = mint_authority_account_info
+        .assert_seed(program_id, &[INGL_MINT_AUTHORITY_KEY])
+        .error_log("Error: @mint_authority_account_info pda assertion")?;
+    let mpl_token_metadata_id = mpl_token_metadata::id();
+    let (nft_edition_key, _nft_edition_bump) = nft_edition_account_info
+        .assert_seed(
+            &mpl_token_metadata_id,
+            &[
+                PREFIX.as_bytes(),
+                mpl_token_metadata_id.as_ref(),
+                mint_account_info.key.as_ref(),
+                b"edition",
+            ],
+        )
+        .error_log("Error: @nft_edition_account_info pda assertion")?;
+    spl_token_program_account_info
+        .assert_key_match(&get_token_program_id(mint_account_info)?)
+        .error_log("Error: @spl_token_program_account_info")?;
+
+    let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+
+    let lock_until = nft_data
+        .lock_until
+        .error_log("Error: The gem isn't locked")?;
+    if (clock_data.unix_timestamp as u32) < lock_until {
+        Err(InglError::TooEarly.utilize("The gem's lock hasn't expired yet"))?
+    }
+    // The boost ends with the lock, so rewards accrued under it must be withdrawn first.
+    if has_unclaimed_rewards(&nft_data, &general_data)? {
+        Err(InglError::InvalidData.utilize("Withdraw the accrued rewards before unlocking the gem"))?
+    }
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    general_data.total_boost_weight = general_data
+        .total_boost_weight
+        .checked_sub(nft_data.boost_weight)
+        .error_log("Error @ general_data.total_boost_weight recalc")?;
+    nft_data.lock_until = None;
+    nft_data.lock_boost_tier = None;
+    nft_data.boost_weight = 0;
+
+    if let Some(programmable_accounts) = &programmable_accounts {
+        log!(log_level, 2, "Unlocking the programmable nft ...");
+        nft_metadata_account_info
+            .assert_key_match(&find_metadata_account(mint_account_info.key).0)
+            .error_log("Error: @nft_metadata_account_info")?;
+        programmable_accounts
+            .verify(mint_account_info.key, associated_token_account_info.key)
+            .error_log("Error: @programmable accounts assertion")?;
+        set_programmable_lock(
+            false,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: mint_authority_account_info,
+                mint_authority_bump,
+                mint: mint_account_info,
+                token: associated_token_account_info,
+                metadata: nft_metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+            programmable_accounts,
+        )?;
+        log!(log_level, 2, "Programmable nft unlocked!!!");
+    } else if is_token_2022_gem(mint_account_info) {
+        log!(log_level, 2, "Thawing the Token-2022 token account ...");
+        set_token_2022_freeze(
+            false,
+            &GemAccounts {
+                payer: payer_account_info,
+                mint_authority: mint_authority_account_info,
+                mint_authority_bump,
+                mint: mint_account_info,
+                token: associated_token_account_info,
+                metadata: nft_metadata_account_info,
+                edition: nft_edition_account_info,
+            },
+        )?;
+        log!(log_level, 2, "Token-2022 token account thawed!!!");
+    } else {
+        log!(log_level, 2, "Thawing the token account ...");
+        invoke_signed(
+            &mpl_token_metadata::instruction::thaw_delegated_account(
+                mpl_token_metadata_id,
+                mint_authority_key,
+                *associated_token_account_info.key,
+                nft_edition_key,
+                *mint_account_info.key,
+            ),
+            &[
+                mint_authority_account_info.clone(),
+                associated_token_account_info.clone(),
+                nft_edition_account_info.clone(),
+                mint_account_info.clone(),
+                spl_token_program_account_info.clone(),
+            ],
+            &[&[INGL_MINT_AUTHORITY_KEY, &[mint_authority_bump]]],
+        )
+        .error_log("Error: @ thawing the token account")?;
+        log!(log_level, 2, "Token account thawed!!!");
+    }
+
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("Error: @nft_account_data_info serialization")?;
+    general_data
+        .serialize(&mut &mut general_account_info.data.borrow_mut()[..])
+        .error_log("Error: @general_account_info serialization")?;
+
+    log!(log_level, 4, "Unlocked nft!!!");
+    Ok(())
+}
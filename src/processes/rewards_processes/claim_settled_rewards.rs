@@ -0,0 +1,78 @@
+use crate::{
+    log,
+    state::{constants::*, RewardClaim},
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+///Pays out the rewards settled to a previous holder of a gem and closes their reward claim.
+pub fn claim_settled_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+) -> ProgramResult {
+    log!(log_level, 4, "Claim settled rewards ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let reward_claim_account_info = next_account_info(account_info_iter)?;
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    reward_claim_account_info
+        .assert_seed(
+            program_id,
+            &[
+                REWARD_CLAIM_KEY,
+                mint_account_info.key.as_ref(),
+                payer_account_info.key.as_ref(),
+            ],
+        )
+        .error_log("Error @ reward_claim_account_info pda assertion")?;
+    let (_authorized_withdrawer, authorized_withdrawer_bump) = authorized_withdrawer_info
+        .assert_seed(program_id, &[AUTHORIZED_WITHDRAWER_KEY])
+        .error_log("Error: failed to assert pda input for authorized_withdrawer_info")?;
+
+    let reward_claim = RewardClaim::parse(reward_claim_account_info, program_id)
+        .error_log("Error @ reward_claim_account_info deserialization")?;
+
+    log!(
+        log_level,
+        2,
+        "Transfering {} settled rewards ...",
+        reward_claim.amount
+    );
+    invoke_signed(
+        &system_instruction::transfer(
+            authorized_withdrawer_info.key,
+            payer_account_info.key,
+            reward_claim.amount,
+        ),
+        &[
+            authorized_withdrawer_info.clone(),
+            payer_account_info.clone(),
+        ],
+        &[&[AUTHORIZED_WITHDRAWER_KEY, &[authorized_withdrawer_bump]]],
+    )
+    .error_log("Error: transfer from authorized_withdrawer to payer")?;
+
+    let claim_lamports = reward_claim_account_info.lamports();
+    **reward_claim_account_info.lamports.borrow_mut() = 0;
+    **payer_account_info.lamports.borrow_mut() = payer_account_info
+        .lamports()
+        .checked_add(claim_lamports)
+        .error_log("Error @ closing reward_claim_account_info")?;
+    reward_claim_account_info.data.borrow_mut().fill(0);
+
+    log!(log_level, 4, "Settled rewards claimed!!!");
+    Ok(())
+}
@@ -1,4 +1,7 @@
+pub mod claim_settled_rewards;
 pub mod finalize_rebalance;
 pub mod init_rebalance;
 pub mod nft_withdraw;
 pub mod process_rewards;
+pub mod settle_before_transfer;
+pub mod transfer_hook;
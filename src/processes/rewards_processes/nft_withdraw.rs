@@ -1,10 +1,10 @@
 use crate::{
     error::InglError,
     log,
-    state::{constants::*, FundsLocation, GeneralData, NftData, ValidatorConfig},
+    state::{constants::*, FundsLocation, GeneralData, NftData, RewardClaim, ValidatorConfig},
     utils::{
-        get_clock_data, get_rent_data, verify_nft_ownership, AccountInfoHelpers, OptionExt,
-        ResultExt,
+        create_pda_account, get_clock_data, get_rent_data, get_rent_shortfall,
+        verify_nft_ownership, AccountInfoHelpers, OptionExt, ResultExt,
     },
 };
 
@@ -15,10 +15,13 @@ use solana_program::{
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
-    system_instruction, vote,
+    system_instruction,
+    sysvar::rent::Rent,
+    vote,
 };
 
 ///Withdraws the rewards accrued from the epoch after the gem was delegated to the last epoch the process_rewards instruction was run
+/// If a gem changed hands without being settled, the share of its previous holder is credited to their reward claim.
 pub fn nft_withdraw(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -82,6 +85,7 @@ pub fn nft_withdraw(
         let associated_token_account_info = next_account_info(account_info_iter)?;
         let mint_account_info = next_account_info(account_info_iter)?;
         let nft_account_data_info = next_account_info(account_info_iter)?;
+        let reward_claim_account_info = next_account_info(account_info_iter)?;
 
         verify_nft_ownership(
             payer_account_info,
@@ -120,12 +124,27 @@ pub fn nft_withdraw(
         nft_account_data_info
             .realloc(new_space, false)
             .error_log("Error: @realloc of nft_account_data_info")?;
-        let total_reward =
-            calculate_total_reward(&ingl_nft_data, &general_data, &config_data, log_level)
-                .error_log("Error: @calculate_total_reward")?;
-        ingl_nft_data.last_withdrawal_epoch = Some(clock_data.epoch);
-        ingl_nft_data.all_withdraws.push(total_reward as u64);
-        general_rewards = general_rewards.checked_add(total_reward as u64).unwrap();
+        let settlement = RewardSettlement {
+            mint: mint_account_info.key,
+            reward_claim: reward_claim_account_info,
+            authorized_withdrawer: authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: clock_data.epoch,
+            rent_data: &rent_data,
+        };
+        let holder_reward = settle_rewards(
+            program_id,
+            &mut ingl_nft_data,
+            &general_data,
+            payer_account_info.key,
+            payer_account_info,
+            &settlement,
+            log_level,
+        )
+        .error_log("Error @ settling the gem's rewards")?;
+        general_rewards = general_rewards.checked_add(holder_reward).unwrap();
+        ingl_nft_data.last_known_owner = Some(*payer_account_info.key);
+        ingl_nft_data.last_owner_seen_epoch = Some(clock_data.epoch);
         ingl_nft_data
             .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
             .error_log("Error: @nft_account_data_info serialization")?;
@@ -152,13 +171,193 @@ pub fn nft_withdraw(
     Ok(())
 }
 
+///Settles the rewards a gem accrued since its last withdrawal, which there must be, between its last known owner and its current holder.
+/// The share of a previous holder the gem changed hands from is credited to their reward claim,
+/// while the holder's share is returned for the caller to pay out.
+/// If the previous holder's share can't cover the rent their new reward claim still needs, the payer tops up the difference.
+pub fn settle_rewards<'a>(
+    program_id: &Pubkey,
+    nft_data: &mut NftData,
+    general_data: &GeneralData,
+    holder: &Pubkey,
+    payer_account_info: &AccountInfo<'a>,
+    settlement: &RewardSettlement<'_, 'a>,
+    log_level: u8,
+) -> Result<u64, ProgramError> {
+    let (previous_reward, holder_reward) =
+        split_total_reward(nft_data, general_data, holder, log_level)
+            .error_log("Error: @split_total_reward")?;
+    if previous_reward > 0 {
+        let previous_owner = nft_data
+            .last_known_owner
+            .error_log("Error: Last known owner can't be None at this stage")?;
+        log!(
+            log_level,
+            2,
+            "Gem changed hands, crediting {} to {}",
+            previous_reward,
+            previous_owner
+        );
+        let mut credited_reward = previous_reward;
+        let claim_rent = settlement.claim_rent(program_id);
+        if previous_reward < claim_rent {
+            log!(
+                log_level,
+                2,
+                "Topping up the rent of the new reward claim ..."
+            );
+            invoke(
+                &system_instruction::transfer(
+                    payer_account_info.key,
+                    settlement.authorized_withdrawer.key,
+                    claim_rent - previous_reward,
+                ),
+                &[
+                    payer_account_info.clone(),
+                    settlement.authorized_withdrawer.clone(),
+                ],
+            )
+            .error_log("Error @ topping up the reward claim rent")?;
+            credited_reward = claim_rent;
+        }
+        credit_reward_claim(program_id, settlement, &previous_owner, credited_reward)
+            .error_log("Error @ crediting the previous holder's reward claim")?;
+    }
+    nft_data.last_withdrawal_epoch = Some(settlement.epoch);
+    nft_data.all_withdraws.push(
+        previous_reward
+            .checked_add(holder_reward)
+            .error_log("Error: total_reward")?,
+    );
+    Ok(holder_reward)
+}
+
+///Splits the rewards a gem accrued since its last withdrawal into the share of its last known owner and the share of its current holder.
+/// If the gem changed hands without being settled, its last known owner is only owed the rewards processed up to the last epoch they were seen holding it.
+pub fn split_total_reward(
+    nft_account_data: &NftData,
+    general_data: &GeneralData,
+    holder: &Pubkey,
+    log_level: u8,
+) -> Result<(u64, u64), ProgramError> {
+    let total_reward =
+        calculate_total_reward(nft_account_data, general_data, None, log_level)? as u64;
+    let previous_reward = match nft_account_data.last_known_owner {
+        Some(owner) if owner != *holder => calculate_total_reward(
+            nft_account_data,
+            general_data,
+            Some(nft_account_data.last_owner_seen_epoch.unwrap_or_default()),
+            log_level,
+        )? as u64,
+        _ => 0,
+    };
+    Ok((
+        previous_reward,
+        total_reward
+            .checked_sub(previous_reward)
+            .error_log("Error: holder reward")?,
+    ))
+}
+
+/// Accounts a gem's rewards are credited to the reward claim of one of its holders with, along with the epoch and rent they are settled at.
+pub struct RewardSettlement<'a, 'b> {
+    pub mint: &'a Pubkey,
+    pub reward_claim: &'a AccountInfo<'b>,
+    pub authorized_withdrawer: &'a AccountInfo<'b>,
+    pub authorized_withdrawer_bump: u8,
+    pub epoch: u64,
+    pub rent_data: &'a Rent,
+}
+
+impl RewardSettlement<'_, '_> {
+    ///Returns the lamports a new reward claim still needs to be rent exempt, or 0 if the claim already exists.
+    /// Lamports sent to the claim's address beforehand count towards its rent.
+    pub fn claim_rent(&self, program_id: &Pubkey) -> u64 {
+        if self.reward_claim.owner == program_id {
+            0
+        } else {
+            get_rent_shortfall(self.reward_claim, RewardClaim::get_space(), self.rent_data)
+        }
+    }
+}
+
+///Adds amount to the claim of rewards owed to owner for the gem, creating the claim account if it doesn't exist yet.
+/// A new claim's rent is paid out of the amount by the authorized withdrawer, and is returned to the owner when the claim is closed.
+/// Amounts too small to cover the rent of a new claim are rejected, so that no reward is left unclaimable.
+pub fn credit_reward_claim(
+    program_id: &Pubkey,
+    settlement: &RewardSettlement,
+    owner: &Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    let (_reward_claim_key, reward_claim_bump) = settlement
+        .reward_claim
+        .assert_seed(
+            program_id,
+            &[REWARD_CLAIM_KEY, settlement.mint.as_ref(), owner.as_ref()],
+        )
+        .error_log("Error @ reward_claim_account_info pda assertion")?;
+    let (mut reward_claim, amount) = if settlement.reward_claim.owner != program_id {
+        let rent = settlement.claim_rent(program_id);
+        if amount < rent {
+            Err(InglError::InvalidData
+                .utilize("The credited amount must cover the rent of a new reward claim"))?
+        }
+        create_pda_account(
+            program_id,
+            settlement.authorized_withdrawer,
+            Some(&[
+                AUTHORIZED_WITHDRAWER_KEY,
+                &[settlement.authorized_withdrawer_bump],
+            ]),
+            settlement.reward_claim,
+            &[
+                REWARD_CLAIM_KEY,
+                settlement.mint.as_ref(),
+                owner.as_ref(),
+                &[reward_claim_bump],
+            ],
+            RewardClaim::get_space(),
+            settlement.rent_data,
+        )
+        .error_log("Error @ reward_claim_account_info creation")?;
+        (
+            RewardClaim {
+                validation_phrase: REWARD_CLAIM_VAL_PHRASE,
+                mint: *settlement.mint,
+                owner: *owner,
+                amount: 0,
+            },
+            amount - rent,
+        )
+    } else {
+        (
+            RewardClaim::parse(settlement.reward_claim, program_id)?,
+            amount,
+        )
+    };
+    reward_claim.amount = reward_claim
+        .amount
+        .checked_add(amount)
+        .error_log("Error @ reward_claim.amount increment")?;
+    reward_claim
+        .serialize(&mut &mut settlement.reward_claim.data.borrow_mut()[..])
+        .error_log("Error @ reward_claim serialization")?;
+    Ok(())
+}
+
 ///UNCHECKED. Calculates the total reward for a specific gem, for the epochs that the gem was delegated without rewards being withdrawn.
+/// Each epoch's nft holders reward is shared in proportion of the gem's weighted backing to the epoch's total weighted stake.
+/// A locked gem also earns its share of the epoch's boost reward, in proportion of its boost weight to the epoch's total boost weight.
+/// If until_epoch is set, only the rewards processed up to that epoch are counted.
 pub fn calculate_total_reward(
     nft_account_data: &NftData,
     general_data: &GeneralData,
-    config_data: &ValidatorConfig,
+    until_epoch: Option<u64>,
     log_level: u8,
 ) -> Result<u128, ProgramError> {
+    let weighted_backing = nft_account_data.weighted_backing;
+    let boost_weight = nft_account_data.boost_weight;
     let interested_epoch = if let Some(tmp) = nft_account_data.last_withdrawal_epoch {
         tmp.max(
             nft_account_data
@@ -182,18 +381,51 @@ pub fn calculate_total_reward(
     let mut total_reward: u128 = 0;
     for i in interested_index..general_data.vote_rewards.len() {
         let epoch_reward = general_data.vote_rewards[i];
+        if until_epoch.map_or(false, |until_epoch| epoch_reward.epoch_number > until_epoch) {
+            break;
+        }
         log!(log_level, 1, "epoch_reward: {:?}", epoch_reward);
         total_reward = total_reward
             .checked_add(
-                (epoch_reward
-                    .nft_holders_reward as u128).checked_mul(config_data.unit_backing as u128)
+                (epoch_reward.nft_holders_reward as u128)
+                    .checked_mul(weighted_backing as u128)
                     .error_log("Error @ unit backing multiplication")?
-                    .checked_div(epoch_reward.total_stake as u128)
+                    .checked_div(epoch_reward.total_weighted_stake as u128)
                     .error_log("Error calculating unit reward for an epoch")?
                     as u128
             )
             .error_log("Error: total_reward")?;
+        if boost_weight > 0 && epoch_reward.total_boost_weight > 0 {
+            total_reward = total_reward
+                .checked_add(
+                    (epoch_reward.boost_reward as u128)
+                        .checked_mul(boost_weight as u128)
+                        .error_log("Error @ boost weight multiplication")?
+                        .checked_div(epoch_reward.total_boost_weight as u128)
+                        .error_log("Error calculating boost reward for an epoch")?,
+                )
+                .error_log("Error: total_reward boost")?;
+        }
     }
 
     Ok(total_reward)
 }
+
+///Returns true if rewards were processed for epochs the gem hasn't withdrawn yet.
+pub fn has_unclaimed_rewards(
+    nft_account_data: &NftData,
+    general_data: &GeneralData,
+) -> Result<bool, ProgramError> {
+    let interested_epoch = nft_account_data
+        .last_withdrawal_epoch
+        .unwrap_or_default()
+        .max(
+            nft_account_data
+                .last_delegation_epoch
+                .error_log("Error: Last delegation epoch can't be None at this stage")?,
+        );
+    Ok(match general_data.vote_rewards.last() {
+        Some(vote_reward) => vote_reward.epoch_number > interested_epoch,
+        None => false,
+    })
+}
@@ -31,6 +31,7 @@ pub fn process_rewards(
     let config_account_info = next_account_info(account_info_iter)?;
     let general_account_info = next_account_info(account_info_iter)?;
     let ingl_team_account_info = next_account_info(account_info_iter)?;
+    let treasury_account_info = next_account_info(account_info_iter)?;
 
     let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
 
@@ -59,6 +60,9 @@ pub fn process_rewards(
     ingl_team_account_info
         .assert_key_match(&team::id())
         .error_log("Error @ ingl_team_account key match assertion")?;
+    treasury_account_info
+        .assert_seed(program_id, &[TREASURY_KEY])
+        .error_log("Error @ treasury_account seed assertion")?;
 
     let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
     let mut general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
@@ -146,15 +150,53 @@ pub fn process_rewards(
         "Funds transferred to the ingl team account!!!"
     );
 
+    let remaining_reward = reward_lamports
+        .checked_sub(team_share)
+        .error_log("Error calculating remaining rewards")?;
+    let treasury_share = remaining_reward
+        .checked_div(100)
+        .error_log("Error calculating the treasury's one percent")?
+        .checked_mul(config_data.treasury_share.into())
+        .error_log("Error calculating treasury_share")?;
+    // The treasury is only funded once the transfer can make it rent exempt, the share is distributed otherwise.
+    let treasury_share = if treasury_account_info
+        .lamports()
+        .saturating_add(treasury_share)
+        < rent_data.minimum_balance(0)
+    {
+        0
+    } else {
+        treasury_share
+    };
+    if treasury_share > 0 {
+        log!(log_level, 2, "Transferring the funds to the treasury ...");
+        invoke_signed(
+            &system_instruction::transfer(
+                authorized_withdrawer_info.key,
+                treasury_account_info.key,
+                treasury_share,
+            ),
+            &[
+                authorized_withdrawer_info.clone(),
+                treasury_account_info.clone(),
+            ],
+            &[&[AUTHORIZED_WITHDRAWER_KEY, &[authorized_withdrawer_bump]]],
+        )
+        .error_log(
+            "failed to transfer funds from authorized_withdrawer_info to treasury_account_info",
+        )?;
+        log!(log_level, 2, "Funds transferred to the treasury!!!");
+    }
+
     log!(
         log_level,
         2,
         "Transferring the funds to the validator's account ..."
     );
 
-    let remaining_reward = reward_lamports
-        .checked_sub(team_share)
-        .error_log("Error calculating remaining rewards")?;
+    let remaining_reward = remaining_reward
+        .checked_sub(treasury_share)
+        .error_log("Error calculating remaining rewards after the treasury share")?;
     let r_one_percent = remaining_reward
         .checked_div(100)
         .error_log("Error calculating r_one_percent")?;
@@ -164,6 +206,17 @@ pub fn process_rewards(
     let nft_holders_share = remaining_reward
         .checked_sub(validator_share)
         .error_log("Error calculating nft_holders_share")?;
+    // Locked nfts earn their boost at the regular reward rate, capped to the validator's share which funds it.
+    let boost_reward = if general_data.total_weighted_delegated > 0 {
+        ((nft_holders_share as u128 * general_data.total_boost_weight as u128
+            / general_data.total_weighted_delegated as u128) as u64)
+            .min(validator_share)
+    } else {
+        0
+    };
+    let validator_share = validator_share
+        .checked_sub(boost_reward)
+        .error_log("Error calculating validator_share after lock boosts")?;
     invoke_signed(
         &system_instruction::transfer(
             authorized_withdrawer_info.key,
@@ -183,7 +236,7 @@ pub fn process_rewards(
         "Funds transferred to the validator's account!!!"
     );
 
-    let new_space = general_account_info.data.borrow().len() + 32;
+    let new_space = general_account_info.data.borrow().len() + VoteReward::get_space();
     let lamports = rent_data
         .minimum_balance(new_space)
         .checked_sub(rent_data.minimum_balance(general_account_info.data.borrow().len()))
@@ -204,8 +257,11 @@ pub fn process_rewards(
     general_data.vote_rewards.push(VoteReward {
         epoch_number: clock_data.epoch,
         total_stake: general_data.total_delegated,
+        total_weighted_stake: general_data.total_weighted_delegated,
         total_reward: reward_lamports,
         nft_holders_reward: nft_holders_share,
+        total_boost_weight: general_data.total_boost_weight,
+        boost_reward,
     });
     general_data.last_withdraw_epoch = clock_data.epoch;
 
@@ -0,0 +1,139 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::rewards_processes::nft_withdraw::{
+        has_unclaimed_rewards, settle_rewards, RewardSettlement,
+    },
+    state::{constants::*, FundsLocation, GeneralData, NftData},
+    utils::{get_clock_data, get_rent_data, verify_nft_ownership, AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+///Pays out the rewards a delegated gem accrued so far to its current holder, so they aren't carried over to the gem's next holder.
+/// The share of a previous holder the gem changed hands from without being settled is credited to their reward claim.
+pub fn settle_before_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    log_level: u8,
+    clock_is_from_account: bool,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Settle before transfer ...");
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let reward_claim_account_info = next_account_info(account_info_iter)?;
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    payer_account_info
+        .assert_signer()
+        .error_log("Error: Payer must be Signer, couldn't find its signature")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error: failed to assert pda input for general_account_info")?;
+    let (_authorized_withdrawer, authorized_withdrawer_bump) = authorized_withdrawer_info
+        .assert_seed(program_id, &[AUTHORIZED_WITHDRAWER_KEY])
+        .error_log("Error: failed to assert pda input for authorized_withdrawer_info")?;
+
+    verify_nft_ownership(
+        payer_account_info,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )
+    .error_log("Error @ nft ownership verification")?;
+
+    let general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error: @nft_account_data_info deserialization")?;
+
+    log!(log_level, 0, "Done with account assertions");
+
+    if let FundsLocation::Delegated = nft_data.funds_location {
+    } else {
+        Err(InglError::InvalidFundsLocation.utilize("Gem's fund location"))?
+    }
+
+    if has_unclaimed_rewards(&nft_data, &general_data)? {
+        let new_space = nft_account_data_info.data.borrow().len() + 8;
+        let lamports = rent_data
+            .minimum_balance(new_space)
+            .checked_sub(rent_data.minimum_balance(nft_account_data_info.data.borrow().len()))
+            .unwrap();
+        invoke(
+            &system_instruction::transfer(
+                payer_account_info.key,
+                nft_account_data_info.key,
+                lamports,
+            ),
+            &[payer_account_info.clone(), nft_account_data_info.clone()],
+        )
+        .error_log("Error @ transfer for reallocating nft_account_data_info")?;
+        nft_account_data_info
+            .realloc(new_space, false)
+            .error_log("Error: @realloc of nft_account_data_info")?;
+
+        let settlement = RewardSettlement {
+            mint: mint_account_info.key,
+            reward_claim: reward_claim_account_info,
+            authorized_withdrawer: authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: clock_data.epoch,
+            rent_data: &rent_data,
+        };
+        let holder_reward = settle_rewards(
+            program_id,
+            &mut nft_data,
+            &general_data,
+            payer_account_info.key,
+            payer_account_info,
+            &settlement,
+            log_level,
+        )
+        .error_log("Error @ settling the gem's rewards")?;
+
+        log!(
+            log_level,
+            2,
+            "Transfering {} settled rewards ...",
+            holder_reward
+        );
+        invoke_signed(
+            &system_instruction::transfer(
+                authorized_withdrawer_info.key,
+                payer_account_info.key,
+                holder_reward,
+            ),
+            &[
+                authorized_withdrawer_info.clone(),
+                payer_account_info.clone(),
+            ],
+            &[&[AUTHORIZED_WITHDRAWER_KEY, &[authorized_withdrawer_bump]]],
+        )
+        .error_log("Error: transfer from authorized_withdrawer to payer")?;
+    }
+    nft_data.last_known_owner = None;
+    nft_data.last_owner_seen_epoch = None;
+
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("Error: @nft_account_data_info serialization")?;
+
+    log!(log_level, 4, "Settled before transfer!!!");
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use crate::{
+    error::InglError,
+    log,
+    processes::rewards_processes::nft_withdraw::{
+        calculate_total_reward, credit_reward_claim, has_unclaimed_rewards, RewardSettlement,
+    },
+    state::{constants::*, FundsLocation, GeneralData, NftData},
+    token_2022::{is_transferring, unpack_token_account, EXTRA_ACCOUNT_METAS_SEED},
+    utils::{
+        get_clock_data, get_rent_data, get_rent_shortfall, AccountInfoHelpers, PubkeyHelpers,
+        ResultExt,
+    },
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+///Executes the transfer hook of Token-2022 gems, called by Token-2022 on each of their transfers.
+/// The rewards the gem accrued up to now are checkpointed to the reward claim of the holder it is transferred from,
+/// so they are neither lost by the seller nor collected by the buyer when the gem changes hands mid-epoch.
+/// The checkpoint's rent is paid out of the checkpointed rewards. The hook never fails a valid transfer:
+/// rewards it can't checkpoint are left on the gem for the previous holder.
+pub fn execute_transfer_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    log_level: u8,
+    clock_is_from_account: bool,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Transfer hook ...");
+    let account_info_iter = &mut accounts.iter();
+    let source_token_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let _destination_token_account_info = next_account_info(account_info_iter)?;
+    let _owner_account_info = next_account_info(account_info_iter)?;
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let nft_account_data_info = next_account_info(account_info_iter)?;
+    let general_account_info = next_account_info(account_info_iter)?;
+    let authorized_withdrawer_info = next_account_info(account_info_iter)?;
+    let reward_claim_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+
+    let clock_data = get_clock_data(account_info_iter, clock_is_from_account)?;
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    mint_account_info
+        .assert_owner(&spl_token_2022::id())
+        .error_log("Error: @mint_account_info ownership assertion")?;
+    source_token_account_info
+        .assert_owner(&spl_token_2022::id())
+        .error_log("Error: @source_token_account_info ownership assertion")?;
+    // Token-2022 flags the source while it calls the hook, so the hook can't be called outside of a transfer.
+    if !is_transferring(source_token_account_info)? {
+        Err(InglError::InvalidData
+            .utilize("The transfer hook is only called by Token-2022 transfers"))?
+    }
+    extra_account_metas_info
+        .assert_seed(
+            program_id,
+            &[EXTRA_ACCOUNT_METAS_SEED, mint_account_info.key.as_ref()],
+        )
+        .error_log("Error: @extra_account_metas_info pda assertion")?;
+    nft_account_data_info
+        .assert_seed(
+            program_id,
+            &[NFT_ACCOUNT_CONST, mint_account_info.key.as_ref()],
+        )
+        .error_log("Error: @nft_account_data_info pda assertion")?;
+    general_account_info
+        .assert_seed(program_id, &[GENERAL_ACCOUNT_SEED])
+        .error_log("Error: @general_account_info pda assertion")?;
+    let (_authorized_withdrawer, authorized_withdrawer_bump) = authorized_withdrawer_info
+        .assert_seed(program_id, &[AUTHORIZED_WITHDRAWER_KEY])
+        .error_log("Error: @authorized_withdrawer_info pda assertion")?;
+    system_program_account_info
+        .assert_key_match(&system_program::id())
+        .error_log("Error: @system_program_account_info")?;
+
+    let source_token_account = unpack_token_account(source_token_account_info)
+        .error_log("Error: @source_token_account_info unpacking")?;
+    source_token_account
+        .mint
+        .assert_match(mint_account_info.key)
+        .error_log("Error: @source_token_account mint")?;
+    let previous_holder = source_token_account.owner;
+
+    let mut nft_data = NftData::parse(nft_account_data_info, program_id)
+        .error_log("Error: @nft_account_data_info deserialization")?;
+    let general_data = Box::new(GeneralData::parse(general_account_info, program_id)?);
+
+    log!(log_level, 0, "Done with account assertions ...");
+
+    if amount == 0 {
+        return Ok(());
+    }
+    let is_delegated = matches!(nft_data.funds_location, FundsLocation::Delegated);
+    if is_delegated && has_unclaimed_rewards(&nft_data, &general_data)? {
+        let settlement = RewardSettlement {
+            mint: mint_account_info.key,
+            reward_claim: reward_claim_account_info,
+            authorized_withdrawer: authorized_withdrawer_info,
+            authorized_withdrawer_bump,
+            epoch: clock_data.epoch,
+            rent_data: &rent_data,
+        };
+        checkpoint_rewards(
+            program_id,
+            &mut nft_data,
+            &general_data,
+            nft_account_data_info,
+            &previous_holder,
+            &settlement,
+            log_level,
+        )?;
+    } else {
+        nft_data.last_known_owner = None;
+        nft_data.last_owner_seen_epoch = None;
+    }
+
+    nft_data
+        .serialize(&mut &mut nft_account_data_info.data.borrow_mut()[..])
+        .error_log("Error: @nft_account_data_info serialization")?;
+
+    log!(log_level, 4, "Transfer hook executed!!!");
+    Ok(())
+}
+
+///Credits the rewards the gem accrued since its last withdrawal to the reward claim of the holder it is transferred from.
+/// Rewards that can't cover the checkpoint's rent, or that are owed to an earlier holder whose claim isn't part of the transfer,
+/// are left on the gem with their holder recorded as its last known owner, to be settled by the next withdrawal.
+fn checkpoint_rewards<'a>(
+    program_id: &Pubkey,
+    nft_data: &mut NftData,
+    general_data: &GeneralData,
+    nft_account_data_info: &AccountInfo<'a>,
+    previous_holder: &Pubkey,
+    settlement: &RewardSettlement<'_, 'a>,
+    log_level: u8,
+) -> ProgramResult {
+    let beneficiary = *nft_data.last_known_owner.get_or_insert(*previous_holder);
+    if beneficiary != *previous_holder {
+        log!(
+            log_level,
+            2,
+            "Rewards are owed to {}, leaving them on the gem",
+            beneficiary
+        );
+        return Ok(());
+    }
+    // The holder was seen holding the gem up to its transfer.
+    nft_data.last_owner_seen_epoch = Some(settlement.epoch);
+
+    let total_reward = calculate_total_reward(nft_data, general_data, None, log_level)
+        .error_log("Error: @calculate_total_reward")? as u64;
+
+    // The withdrawal record grows the gem account and a new reward claim needs its rent, both paid out of the rewards.
+    let space = nft_account_data_info.data_len() + 8;
+    let realloc_lamports = get_rent_shortfall(nft_account_data_info, space, settlement.rent_data);
+    let claim_rent = settlement.claim_rent(program_id);
+    if total_reward <= realloc_lamports + claim_rent {
+        log!(
+            log_level,
+            2,
+            "Rewards can't cover the checkpoint's rent, leaving them on the gem"
+        );
+        return Ok(());
+    }
+    let checkpointed_reward = total_reward - realloc_lamports;
+
+    if realloc_lamports > 0 {
+        invoke_signed(
+            &system_instruction::transfer(
+                settlement.authorized_withdrawer.key,
+                nft_account_data_info.key,
+                realloc_lamports,
+            ),
+            &[
+                settlement.authorized_withdrawer.clone(),
+                nft_account_data_info.clone(),
+            ],
+            &[&[
+                AUTHORIZED_WITHDRAWER_KEY,
+                &[settlement.authorized_withdrawer_bump],
+            ]],
+        )
+        .error_log("Error @ transfer for reallocating nft_account_data_info")?;
+    }
+    nft_account_data_info
+        .realloc(space, false)
+        .error_log("Error: @realloc of nft_account_data_info")?;
+
+    log!(
+        log_level,
+        2,
+        "Checkpointing {} to {}",
+        checkpointed_reward,
+        previous_holder
+    );
+    credit_reward_claim(program_id, settlement, previous_holder, checkpointed_reward)
+        .error_log("Error @ checkpointing the holder's rewards")?;
+
+    nft_data.last_withdrawal_epoch = Some(settlement.epoch);
+    nft_data.all_withdraws.push(total_reward);
+    nft_data.last_known_owner = None;
+    nft_data.last_owner_seen_epoch = None;
+    Ok(())
+}
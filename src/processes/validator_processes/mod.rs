@@ -1 +1,4 @@
-pub mod create_vote_account;
\ No newline at end of file
+pub mod create_vote_account;
+pub mod set_mint_phases;
+pub mod set_vrf_oracle;
+pub mod set_emergency_pause;
\ No newline at end of file
@@ -0,0 +1,50 @@
+use crate::{
+    log,
+    state::{constants::*, ValidatorConfig},
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+///Lets the guardian pause or resume minting, delegation and upgrades.
+pub fn set_emergency_pause(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    is_paused: bool,
+    log_level: u8,
+) -> ProgramResult {
+    log!(log_level, 4, "Setting emergency pause ...");
+    let account_info_iter = &mut accounts.iter();
+    let guardian_account_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+
+    guardian_account_info
+        .assert_signer()
+        .error_log("Error: Guardian account is not a signer")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error @ config_account seed assertion")?;
+
+    let mut config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    guardian_account_info
+        .assert_key_match(
+            &config_data
+                .guardian
+                .error_log("This validator instance has no guardian")?,
+        )
+        .error_log("Error: Guardian account is not the config guardian")?;
+
+    config_data.is_paused = is_paused;
+    config_data
+        .serialize(&mut &mut config_account_info.data.borrow_mut()[..])
+        .error_log("Error @ config_data serialization")?;
+
+    log!(log_level, 4, "Emergency pause set to {} !!!", is_paused);
+    Ok(())
+}
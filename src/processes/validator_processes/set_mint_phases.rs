@@ -0,0 +1,111 @@
+use crate::{
+    log,
+    state::{constants::*, MintPhase, MintPhases, ValidatorConfig},
+    utils::{get_rent_data, AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+///Replaces the schedule of mint phases. An empty schedule closes minting until phases are set again.
+///Wallet caps start over with the new schedule, and the minter records of the previous one can be closed.
+pub fn set_mint_phases(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    phases: Vec<MintPhase>,
+    log_level: u8,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Setting mint phases ...");
+    let account_info_iter = &mut accounts.iter();
+    let validator_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let mint_phases_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    validator_info
+        .assert_signer()
+        .error_log("Error: Validator account is not a signer")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error @ config_account seed assertion")?;
+    system_program_account_info
+        .assert_key_match(&system_program::id())
+        .error_log("Error @ system_program_account_info assertion")?;
+    let (mint_phases_key, mint_phases_bump) = mint_phases_account_info
+        .assert_seed(program_id, &[MINT_PHASES_SEED])
+        .error_log("Error @ mint_phases_account_info pda assertion")?;
+
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    validator_info
+        .assert_key_match(&config_data.validator_id)
+        .error_log("Error: Validator account is not the validator_id")?;
+
+    let schedule_version = if mint_phases_account_info.owner == program_id {
+        MintPhases::parse(mint_phases_account_info, program_id)?
+            .schedule_version
+            .wrapping_add(1)
+    } else {
+        0
+    };
+    let mint_phases = MintPhases {
+        validation_phrase: MINT_PHASES_VAL_PHRASE,
+        phases,
+        schedule_version,
+    };
+    mint_phases
+        .validate_data()
+        .error_log("Error @ mint phases validation")?;
+    let space = mint_phases.get_space();
+
+    if mint_phases_account_info.owner != program_id {
+        log!(log_level, 2, "Creating mint phases account ...");
+        invoke_signed(
+            &system_instruction::create_account(
+                validator_info.key,
+                &mint_phases_key,
+                rent_data.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[validator_info.clone(), mint_phases_account_info.clone()],
+            &[&[MINT_PHASES_SEED, &[mint_phases_bump]]],
+        )
+        .error_log("Error @ mint_phases_account_info creation")?;
+        log!(log_level, 2, "Created mint phases account !!!");
+    } else {
+        let lamports = rent_data
+            .minimum_balance(space)
+            .saturating_sub(mint_phases_account_info.lamports());
+        if lamports > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    validator_info.key,
+                    mint_phases_account_info.key,
+                    lamports,
+                ),
+                &[validator_info.clone(), mint_phases_account_info.clone()],
+            )
+            .error_log("Error @ mint_phases_account_info rent transfer")?;
+        }
+        mint_phases_account_info
+            .realloc(space, false)
+            .error_log("Error @ mint_phases_account_info realloc")?;
+    }
+
+    mint_phases
+        .serialize(&mut &mut mint_phases_account_info.data.borrow_mut()[..])
+        .error_log("Error @ mint_phases serialization")?;
+
+    log!(log_level, 4, "Mint phases set !!!");
+    Ok(())
+}
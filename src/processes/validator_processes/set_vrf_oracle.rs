@@ -0,0 +1,83 @@
+use crate::{
+    log,
+    state::{constants::*, ValidatorConfig, VrfState},
+    utils::{get_rent_data, AccountInfoHelpers, ResultExt},
+};
+
+use borsh::BorshSerialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+///Sets the oracle program whose VRF results seed the NFT rarities. None falls back to the SlotHashes entropy.
+/// Only the results fulfilled by the oracle authority are accepted.
+pub fn set_vrf_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    oracle_program: Option<Pubkey>,
+    oracle_authority: Pubkey,
+    log_level: u8,
+    rent_is_from_account: bool,
+) -> ProgramResult {
+    log!(log_level, 4, "Setting VRF oracle ...");
+    let account_info_iter = &mut accounts.iter();
+    let validator_info = next_account_info(account_info_iter)?;
+    let config_account_info = next_account_info(account_info_iter)?;
+    let vrf_state_account_info = next_account_info(account_info_iter)?;
+    let system_program_account_info = next_account_info(account_info_iter)?;
+
+    let rent_data = get_rent_data(account_info_iter, rent_is_from_account)?;
+
+    validator_info
+        .assert_signer()
+        .error_log("Error: Validator account is not a signer")?;
+    config_account_info
+        .assert_seed(program_id, &[INGL_CONFIG_SEED])
+        .error_log("Error @ config_account seed assertion")?;
+    system_program_account_info
+        .assert_key_match(&system_program::id())
+        .error_log("Error @ system_program_account_info assertion")?;
+    let (vrf_state_key, vrf_state_bump) = vrf_state_account_info
+        .assert_seed(program_id, &[VRF_STATE_KEY])
+        .error_log("Error @ vrf_state_account_info pda assertion")?;
+
+    let config_data = Box::new(ValidatorConfig::parse(config_account_info, program_id)?);
+    validator_info
+        .assert_key_match(&config_data.validator_id)
+        .error_log("Error: Validator account is not the validator_id")?;
+
+    if vrf_state_account_info.owner != program_id {
+        let space = VrfState::get_space();
+        log!(log_level, 2, "Creating vrf state account ...");
+        invoke_signed(
+            &system_instruction::create_account(
+                validator_info.key,
+                &vrf_state_key,
+                rent_data.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[validator_info.clone(), vrf_state_account_info.clone()],
+            &[&[VRF_STATE_KEY, &[vrf_state_bump]]],
+        )
+        .error_log("Error @ vrf_state_account_info creation")?;
+        log!(log_level, 2, "Created vrf state account !!!");
+    }
+
+    let vrf_state = VrfState {
+        validation_phrase: VRF_STATE_VAL_PHRASE,
+        oracle_program,
+        oracle_authority,
+    };
+    vrf_state
+        .serialize(&mut &mut vrf_state_account_info.data.borrow_mut()[..])
+        .error_log("Error @ vrf_state serialization")?;
+
+    log!(log_level, 4, "VRF oracle set !!!");
+    Ok(())
+}
@@ -4,20 +4,42 @@ use crate::{
     instruction::{InstructionEnum},
     processes::{
         governance_processes::{
+            cancel_proposal::cancel_proposal, change_vote::change_vote,
+            close_proposal::close_proposal, delegate_vote::delegate_vote,
             execute_governance::execute_governance, finalize_governance::finalize_governance,
-            init_governance::create_governance, vote_governance::vote_governance,
+            init_governance::create_governance, prune_votes::prune_votes,
+            revoke_vote_delegation::revoke_vote_delegation, veto_proposal::veto_proposal,
+            vote_governance::vote_governance, withdraw_vote::withdraw_vote,
+        },
+        init_processes::{
+            add_uploader::add_uploader, commit_uris_root::commit_uris_root,
+            fractionalize_existing::fractionalize, freeze_uris::freeze_uris, init::process_init,
+            migrate_account::migrate_account, remove_uploader::remove_uploader,
+            reset_uris::reset_uris, reset_uris_page::reset_uris_page, set_base_uri::set_base_uri,
+            upload_uris::upload_uris, upload_uris_page::upload_uris_page,
+        },
+        marketplace_processes::{
+            buy_nft::buy_nft, cancel_bid::cancel_bid, delist_nft::delist_nft, list_nft::list_nft,
+            place_bid::place_bid, quote_listing::quote_listing,
         },
-        init_processes::{init::process_init, reset_uris::reset_uris, upload_uris::upload_uris, fractionalize_existing::fractionalize},
         nft_processes::{
-            delegate_nft::delegate_gem, imprint_rarity::process_imprint_rarity,
-            mint_nft::process_mint_nft, redeem_nft::redeem_nft, undelegate_nft::undelegate_nft,
+            close_minter_record::close_minter_record, commit_rarity_seed::commit_rarity_seed,
+            delegate_nft::delegate_gem, imprint_rarity::process_imprint_rarity, lock_nft::lock_nft,
+            mint_nft::process_mint_nft, redeem_nft::redeem_nft, reroll_rarity::reroll_rarity,
+            sync_metadata::sync_metadata, undelegate_nft::undelegate_nft, unlock_nft::unlock_nft,
         },
         rewards_processes::{
-            finalize_rebalance::finalize_rebalance, init_rebalance::init_rebalance,
-            nft_withdraw::nft_withdraw, process_rewards::process_rewards,
+            claim_settled_rewards::claim_settled_rewards, finalize_rebalance::finalize_rebalance,
+            init_rebalance::init_rebalance, nft_withdraw::nft_withdraw,
+            process_rewards::process_rewards, settle_before_transfer::settle_before_transfer,
+            transfer_hook::execute_transfer_hook,
+        },
+        validator_processes::{
+            create_vote_account::create_vote_account, set_emergency_pause::set_emergency_pause,
+            set_mint_phases::set_mint_phases, set_vrf_oracle::set_vrf_oracle,
         },
-        validator_processes::create_vote_account::create_vote_account,
     },
+    token_2022::unpack_execute_amount,
 };
 
 pub fn process_instruction(
@@ -25,6 +47,10 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
+    // Token-2022 calls the transfer hook of gems with the transfer hook interface's Execute instruction, which isn't borsh encoded.
+    if let Some(amount) = unpack_execute_amount(data) {
+        return execute_transfer_hook(program_id, accounts, amount, 4, false, false);
+    }
     match InstructionEnum::decode(data) {
         InstructionEnum::Init(init_args) => process_init(
             program_id,
@@ -35,12 +61,15 @@ pub fn process_instruction(
             create_vote_account(program_id, accounts, log_level, false)?
         }
 
-        InstructionEnum::MintNft { log_level } => {
-            process_mint_nft(program_id, accounts, log_level, false)?
-        }
-        InstructionEnum::ImprintRarity { log_level } => {
-            process_imprint_rarity(program_id, accounts, log_level, false)?
-        }
+        InstructionEnum::MintNft {
+            allowlist_proof,
+            log_level,
+        } => process_mint_nft(program_id, accounts, allowlist_proof, log_level, false)?,
+        InstructionEnum::ImprintRarity {
+            reveal,
+            uri_proof,
+            log_level,
+        } => process_imprint_rarity(program_id, accounts, reveal, uri_proof, log_level, false)?,
 
         InstructionEnum::UploadUris {
             uris,
@@ -50,13 +79,13 @@ pub fn process_instruction(
 
         InstructionEnum::InitGovernance {
             log_level,
-            governance_type,
+            actions,
             title,
             description,
         } => create_governance(
             program_id,
             accounts,
-            governance_type,
+            actions,
             title,
             description,
             log_level,
@@ -80,8 +109,9 @@ pub fn process_instruction(
 
         InstructionEnum::ExecuteGovernance {
             numeration,
+            max_actions,
             log_level,
-        } => execute_governance(program_id, accounts, numeration, log_level)?,
+        } => execute_governance(program_id, accounts, numeration, max_actions, log_level)?,
 
         InstructionEnum::NFTWithdraw { cnt, log_level } => {
             nft_withdraw(program_id, accounts, cnt, log_level, false, false)?
@@ -120,6 +150,174 @@ pub fn process_instruction(
         } => injects::inject_testing_data(program_id, accounts, num_mints, log_level)?,
 
         InstructionEnum::FractionalizeExisting(init_args) => fractionalize(program_id, accounts, init_args)?,
+
+        InstructionEnum::CommitRaritySeed {
+            commitment,
+            log_level,
+        } => commit_rarity_seed(program_id, accounts, commitment, log_level, false, false)?,
+
+        InstructionEnum::SetVrfOracle {
+            oracle_program,
+            oracle_authority,
+            log_level,
+        } => set_vrf_oracle(
+            program_id,
+            accounts,
+            oracle_program,
+            oracle_authority,
+            log_level,
+            false,
+        )?,
+
+        InstructionEnum::RerollRarity {
+            uri_proof,
+            log_level,
+        } => reroll_rarity(program_id, accounts, uri_proof, log_level, false)?,
+
+        InstructionEnum::SetMintPhases { phases, log_level } => {
+            set_mint_phases(program_id, accounts, phases, log_level, false)?
+        }
+
+        InstructionEnum::SettleBeforeTransfer { log_level } => {
+            settle_before_transfer(program_id, accounts, log_level, false, false)?
+        }
+
+        InstructionEnum::ClaimSettledRewards { log_level } => {
+            claim_settled_rewards(program_id, accounts, log_level)?
+        }
+
+        InstructionEnum::ListNft { price, log_level } => {
+            list_nft(program_id, accounts, price, log_level, false, false)?
+        }
+
+        InstructionEnum::DelistNft { log_level } => delist_nft(program_id, accounts, log_level)?,
+
+        InstructionEnum::BuyNft { log_level } => {
+            buy_nft(program_id, accounts, false, log_level, false, false)?
+        }
+
+        InstructionEnum::PlaceBid { amount, log_level } => {
+            place_bid(program_id, accounts, amount, log_level)?
+        }
+
+        InstructionEnum::AcceptBid { log_level } => {
+            buy_nft(program_id, accounts, true, log_level, false, false)?
+        }
+
+        InstructionEnum::QuoteListing { log_level } => {
+            quote_listing(program_id, accounts, log_level)?
+        }
+
+        InstructionEnum::SyncMetadata { log_level } => {
+            sync_metadata(program_id, accounts, log_level)?
+        }
+
+        InstructionEnum::LockNft {
+            duration,
+            log_level,
+        } => lock_nft(program_id, accounts, duration, log_level, false)?,
+
+        InstructionEnum::UnlockNft { log_level } => {
+            unlock_nft(program_id, accounts, log_level, false)?
+        }
+
+        InstructionEnum::UploadUrisPage {
+            rarity,
+            page,
+            uris,
+            log_level,
+        } => upload_uris_page(program_id, accounts, rarity, page, uris, log_level)?,
+
+        InstructionEnum::ResetUrisPage {
+            rarity,
+            page,
+            uris,
+            log_level,
+        } => reset_uris_page(program_id, accounts, rarity, page, uris, log_level)?,
+
+        InstructionEnum::SetBaseUri {
+            base_uri,
+            log_level,
+        } => set_base_uri(program_id, accounts, base_uri, log_level)?,
+
+        InstructionEnum::CommitUrisRoot {
+            rarity,
+            uris_root,
+            log_level,
+        } => commit_uris_root(program_id, accounts, rarity, uris_root, log_level)?,
+
+        InstructionEnum::AddUploader {
+            uploader,
+            log_level,
+        } => add_uploader(program_id, accounts, uploader, log_level)?,
+
+        InstructionEnum::RemoveUploader {
+            uploader,
+            log_level,
+        } => remove_uploader(program_id, accounts, uploader, log_level)?,
+
+        InstructionEnum::FreezeUris { log_level } => freeze_uris(program_id, accounts, log_level)?,
+
+        InstructionEnum::ChangeVote {
+            numeration,
+            vote,
+            cnt,
+            log_level,
+        } => change_vote(
+            program_id, accounts, numeration, vote, cnt, log_level, false,
+        )?,
+
+        InstructionEnum::WithdrawVote {
+            numeration,
+            cnt,
+            log_level,
+        } => withdraw_vote(
+            program_id, accounts, numeration, cnt, log_level, false, false,
+        )?,
+
+        InstructionEnum::DelegateVote {
+            delegate,
+            log_level,
+        } => delegate_vote(program_id, accounts, delegate, log_level, false)?,
+
+        InstructionEnum::RevokeVoteDelegation { log_level } => {
+            revoke_vote_delegation(program_id, accounts, log_level)?
+        }
+
+        InstructionEnum::CancelProposal {
+            numeration,
+            log_level,
+        } => cancel_proposal(program_id, accounts, numeration, log_level, false)?,
+
+        InstructionEnum::CloseProposal {
+            numeration,
+            log_level,
+        } => close_proposal(program_id, accounts, numeration, log_level, false)?,
+
+        InstructionEnum::PruneVotes {
+            numerations,
+            log_level,
+        } => prune_votes(program_id, accounts, numerations, log_level, false)?,
+
+        InstructionEnum::VetoProposal {
+            numeration,
+            log_level,
+        } => veto_proposal(program_id, accounts, numeration, log_level, false)?,
+
+        InstructionEnum::SetEmergencyPause {
+            is_paused,
+            log_level,
+        } => set_emergency_pause(program_id, accounts, is_paused, log_level)?,
+
+        InstructionEnum::CloseMinterRecord { log_level } => {
+            close_minter_record(program_id, accounts, log_level, false)?
+        }
+
+        InstructionEnum::CancelBid { log_level } => cancel_bid(program_id, accounts, log_level)?,
+
+        InstructionEnum::MigrateAccount { log_level } => {
+            migrate_account(program_id, accounts, log_level, false)?
+        }
     }
 
     Ok(())
@@ -146,7 +344,7 @@ pub mod injects {
             constants::{GENERAL_ACCOUNT_SEED, NFT_ACCOUNT_CONST},
             FundsLocation, GeneralData, NftData, VoteReward,
         },
-        utils::{AccountInfoHelpers, ResultExt},
+        utils::{get_token_program_id, AccountInfoHelpers, ResultExt},
     };
     pub fn inject_testing_data(
         program_id: &Pubkey,
@@ -175,7 +373,7 @@ pub mod injects {
                 &[NFT_ACCOUNT_CONST.as_ref(), mint_account_info.key.as_ref()],
             )?;
             nft_account_data_info.assert_owner(program_id)?;
-            mint_account_info.assert_owner(&spl_token::id())?;
+            get_token_program_id(mint_account_info)?;
             let mut nft_account_data = NftData::parse(nft_account_data_info, program_id)?;
 
             if let FundsLocation::Undelegated = nft_account_data.funds_location {
@@ -202,20 +400,29 @@ pub mod injects {
         general_data.vote_rewards.push(VoteReward {
             epoch_number: chosen_epoch - 2,
             total_stake: general_data.total_delegated,
+            total_weighted_stake: general_data.total_weighted_delegated,
             nft_holders_reward: LAMPORTS_PER_SOL - (0.1 * LAMPORTS_PER_SOL as f64) as u64,
             total_reward: 1 * LAMPORTS_PER_SOL,
+            total_boost_weight: 0,
+            boost_reward: 0,
         });
         general_data.vote_rewards.push(VoteReward {
             epoch_number: chosen_epoch - 1,
             total_stake: general_data.total_delegated,
+            total_weighted_stake: general_data.total_weighted_delegated,
             nft_holders_reward: LAMPORTS_PER_SOL - (0.1 * LAMPORTS_PER_SOL as f64) as u64,
             total_reward: 1 * LAMPORTS_PER_SOL,
+            total_boost_weight: 0,
+            boost_reward: 0,
         });
         general_data.vote_rewards.push(VoteReward {
             epoch_number: chosen_epoch,
             total_stake: general_data.total_delegated,
+            total_weighted_stake: general_data.total_weighted_delegated,
             nft_holders_reward: 2 * (LAMPORTS_PER_SOL - (0.1 * LAMPORTS_PER_SOL as f64) as u64),
             total_reward: 2 * LAMPORTS_PER_SOL,
+            total_boost_weight: 0,
+            boost_reward: 0,
         });
         general_data.last_withdraw_epoch = chosen_epoch - 1;
 
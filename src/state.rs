@@ -4,8 +4,10 @@ use std::collections::{BTreeMap, BTreeSet};
 use crate::{
     colored_log,
     error::InglError,
-    utils::{AccountInfoHelpers, ResultExt},
+    instruction::InitArgs,
+    utils::{AccountInfoHelpers, OptionExt, ResultExt},
 };
+use arrayref::array_ref;
 use borsh::{BorshDeserialize, BorshSerialize};
 use ingl_macros::Validate;
 use serde_derive::{Deserialize, Serialize};
@@ -13,6 +15,8 @@ use solana_program::{
     account_info::AccountInfo,
     borsh::try_from_slice_unchecked,
     entrypoint::ProgramResult,
+    hash::hashv,
+    log::sol_log_data,
     msg,
     native_token::LAMPORTS_PER_SOL,
     program_error::ProgramError,
@@ -31,14 +35,38 @@ pub const LOG_LEVEL: u8 = 5;
 pub mod constants {
 
     pub const CUMMULATED_RARITY: u16 = 10000;
+    pub const REWARD_WEIGHT_BASE: u16 = 100;
+    pub const MAX_REWARD_WEIGHT: u16 = 1000;
     pub const RARITY_IMPRINT_WAIT_SLOTS: u64 = 20;
     pub const INGL_VRF_MAX_RESULT: u64 = 10000;
+    // Offsets of the parts of an NFT's seed hash its rarity, uris page and uri are picked from,
+    // so that the three picks are independent of each other.
+    pub const RARITY_SEED_OFFSET: usize = 0;
+    pub const PAGE_SEED_OFFSET: usize = 8;
+    pub const URI_SEED_OFFSET: usize = 16;
 
-    pub const INGL_CONFIG_VAL_PHRASE: u32 = 739_215_648;
-    pub const URIS_ACCOUNT_VAL_PHRASE: u32 = 382_916_043;
-    pub const GENERAL_ACCOUNT_VAL_PHRASE: u32 = 836_438_471;
-    pub const NFT_DATA_VAL_PHRASE: u32 = 271_832_912;
-    pub const GOVERNANCE_DATA_VAL_PHRASE: u32 = 675_549_872;
+    // Bumped whenever an account layout changes, so that accounts of older instances fail
+    // validation instead of being misread until MigrateAccount converts them.
+    pub const INGL_CONFIG_VAL_PHRASE: u32 = 739_215_649;
+    pub const URIS_ACCOUNT_VAL_PHRASE: u32 = 382_916_044;
+    pub const GENERAL_ACCOUNT_VAL_PHRASE: u32 = 836_438_472;
+    pub const NFT_DATA_VAL_PHRASE: u32 = 271_832_913;
+    pub const GOVERNANCE_DATA_VAL_PHRASE: u32 = 675_549_873;
+    pub const VRF_STATE_VAL_PHRASE: u32 = 564_209_381;
+    pub const MINT_PHASES_VAL_PHRASE: u32 = 918_375_024;
+    pub const MINTER_RECORD_VAL_PHRASE: u32 = 447_120_935;
+    pub const REFERRER_RECORD_VAL_PHRASE: u32 = 603_418_772;
+    pub const REWARD_CLAIM_VAL_PHRASE: u32 = 381_920_467;
+    pub const LISTING_VAL_PHRASE: u32 = 752_064_318;
+    pub const URIS_PAGE_VAL_PHRASE: u32 = 219_647_053;
+    pub const UPLOADERS_VAL_PHRASE: u32 = 508_371_926;
+    pub const VOTE_DELEGATION_VAL_PHRASE: u32 = 694_205_817;
+    // Phrases of the layouts MigrateAccount converts from.
+    pub const LEGACY_INGL_CONFIG_VAL_PHRASE: u32 = 739_215_648;
+    pub const LEGACY_URIS_ACCOUNT_VAL_PHRASE: u32 = 382_916_043;
+    pub const LEGACY_GENERAL_ACCOUNT_VAL_PHRASE: u32 = 836_438_471;
+    pub const LEGACY_NFT_DATA_VAL_PHRASE: u32 = 271_832_912;
+    pub const LEGACY_GOVERNANCE_DATA_VAL_PHRASE: u32 = 675_549_872;
 
     pub const INGL_CONFIG_SEED: &[u8] = b"ingl_config";
     pub const URIS_ACCOUNT_SEED: &[u8] = b"uris_account";
@@ -54,13 +82,41 @@ pub mod constants {
     pub const INGL_PROGRAM_AUTHORITY_KEY: &[u8] = b"ingl_program_authority";
     pub const INGL_PROPOSAL_KEY: &[u8] = b"ingl_proposal";
     pub const VRF_STATE_KEY: &[u8] = b"ingl_vrf_state_key";
+    pub const VRF_RESULT_KEY: &[u8] = b"vrf_result";
     pub const VALIDATOR_ID_SEED: &[u8] = b"validator_ID___________________";
     pub const T_STAKE_ACCOUNT_KEY: &[u8] = b"t_stake_account_key";
     pub const T_WITHDRAW_KEY: &[u8] = b"t_withdraw_key";
+    pub const MINT_PHASES_SEED: &[u8] = b"mint_phases";
+    pub const MINTER_RECORD_KEY: &[u8] = b"minter_record";
+    pub const REFERRER_RECORD_KEY: &[u8] = b"referrer_record";
+    pub const REWARD_CLAIM_KEY: &[u8] = b"reward_claim";
+    pub const LISTING_KEY: &[u8] = b"listing";
+    pub const URIS_PAGE_KEY: &[u8] = b"uris_page";
+    pub const UPLOADERS_SEED: &[u8] = b"uploaders";
+    pub const VOTE_DELEGATION_KEY: &[u8] = b"vote_delegation";
+    pub const TREASURY_KEY: &[u8] = b"treasury";
+
+    pub const MAX_MINT_PHASES: usize = 10;
+    pub const MAX_LOCK_BOOST_TIERS: usize = 5;
+    pub const MAX_LOCK_DURATION: u32 = 86400 * 365 * 4; // 4 years
+    pub const MAX_URIS_PAGE_SPACE: usize = 10000;
+    pub const MAX_BASE_URI_LENGTH: usize = 100;
+    pub const MAX_UPLOADERS: usize = 10;
+    pub const BASIS_POINTS: u64 = 10_000;
+    pub const MAX_TREASURY_SHARE: u8 = 20;
+    pub const MAX_TREASURY_SPEND_SHARE: u8 = 25; // 25%, maximum share of the treasury a single proposal can spend
+    pub const MAX_TREASURY_MEMO_LENGTH: usize = 100;
 
     pub const FEELESS_REDEMPTION_PERIOD: u32 = 86400 * 30; // 1 month
-    pub const GOVERNANCE_EXECUTION_THRESHOLD: f64 = 4.0 / 5.0; // 80%
+    pub const GOVERNANCE_EXECUTION_THRESHOLD: u8 = 80; // 80%, minimum approval of program upgrades and validator id changes
+    pub const MIN_CRITICAL_PROPOSAL_QUORUM: u8 = 65;
+    pub const MIN_PROPOSAL_QUORUM: u8 = 10;
+    pub const MIN_PROPOSAL_APPROVAL: u8 = 51;
+    pub const MAX_PROPOSAL_ACTIONS: usize = 10;
+    pub const MIN_PROPOSAL_DEPOSIT: u64 = 2 * super::LAMPORTS_PER_SOL; // keeps proposal spam costly
+    pub const MAX_PROPOSAL_DEPOSIT: u64 = 100 * super::LAMPORTS_PER_SOL;
     pub const GOVERNANCE_SAFETY_LEEWAY: u32 = 86400 * 30; // 1 month
+    pub const PROPOSAL_RETENTION_PERIOD: u32 = 86400 * 90; // 3 months
     pub mod initializer {
         solana_program::declare_id!("62uPowNXr22WPw7XghajJkWMBJ2fnv1oGthxqHYYPHie");
     }
@@ -70,14 +126,8 @@ pub mod constants {
     }
 
     pub mod team {
-        use solana_program::pubkey;
-        use solana_program::pubkey::Pubkey;
-
         pub const TEAM_SHARE: u64 = 10;
         solana_program::declare_id!("Et2tm6NsfBZJbEYXtWTv9k51V4tWtQvufexSgXoDRGVA");
-
-        pub const UPLOADERS: [Pubkey; 1] =
-            [pubkey!("8BjrFS4cYrHqy4S2hdhr7QAvmVrfquuo88kSjicMkek5")];
     }
 }
 
@@ -85,7 +135,128 @@ pub fn get_min_stake_account_lamports() -> u64 {
     LAMPORTS_PER_SOL + Rent::default().minimum_balance(std::mem::size_of::<StakeState>() as usize)
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug)]
+/// Reward boost of the NFTs locked for at least min_duration seconds.
+pub struct LockBoostTier {
+    pub min_duration: u32,
+    /// Share of an NFT's regular reward added on top of it, in basis points.
+    pub boost_bps: u16,
+}
+impl LockBoostTier {
+    pub fn get_space() -> usize {
+        // 4 + 2
+        6
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+/// What the proposal quorum is a percentage of.
+pub enum QuorumBasis {
+    /// Every NFT the instance could sell, i.e. max_primary_stake / unit_backing.
+    MaxPrimaryStake,
+    /// The NFTs delegated when the proposal was created.
+    DelegatedNfts,
+}
+impl QuorumBasis {
+    /// Returns the number of votes a proposal created now expects, which its quorum is computed from.
+    /// At least one vote is always expected, so a proposal made while nothing is delegated
+    /// can't pass on a quorum of zero.
+    pub fn get_expected_votes(
+        &self,
+        max_primary_stake: u64,
+        total_delegated: u64,
+        unit_backing: u64,
+    ) -> Option<u32> {
+        let stake = match self {
+            QuorumBasis::MaxPrimaryStake => max_primary_stake,
+            QuorumBasis::DelegatedNfts => total_delegated,
+        };
+        stake.checked_div(unit_backing).map(|x| (x as u32).max(1))
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProposalThreshold {
+    /// Percentage of the expected votes that must be exceeded for the proposal to be decided.
+    pub quorum: u8,
+    /// Percentage of the cast votes that must approve the proposal for it to pass.
+    pub approval: u8,
+}
+impl ProposalThreshold {
+    pub fn get_space() -> usize {
+        // 1 + 1
+        2
+    }
+
+    pub fn verify(&self, min_quorum: u8, min_approval: u8) -> ProgramResult {
+        if self.quorum > 100 || self.quorum < min_quorum {
+            Err(InglError::InvalidConfigData
+                .utilize("Proposal quorum must be between its minimum and 100%"))?
+        }
+        if self.approval > 100 || self.approval < min_approval {
+            Err(InglError::InvalidConfigData
+                .utilize("Proposal approval must be between its minimum and 100%"))?
+        }
+        Ok(())
+    }
+
+    /// Returns the strictest of the thresholds, which a proposal holding all their actions must meet.
+    pub fn strictest(thresholds: impl Iterator<Item = ProposalThreshold>) -> Self {
+        thresholds.fold(
+            ProposalThreshold {
+                quorum: 0,
+                approval: 0,
+            },
+            |strictest, threshold| ProposalThreshold {
+                quorum: strictest.quorum.max(threshold.quorum),
+                approval: strictest.approval.max(threshold.approval),
+            },
+        )
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+/// Thresholds of each kind of proposal.
+pub struct GovernanceThresholds {
+    pub program_upgrade: ProposalThreshold,
+    pub validator_id: ProposalThreshold,
+    pub commission: ProposalThreshold,
+    /// Stake, share and redemption fee changes.
+    pub economics: ProposalThreshold,
+    /// Validator name, twitter handle and discord invite changes.
+    pub profile: ProposalThreshold,
+    pub uploaders: ProposalThreshold,
+    pub uris: ProposalThreshold,
+    pub treasury: ProposalThreshold,
+    /// Changes to these thresholds.
+    pub thresholds: ProposalThreshold,
+}
+impl GovernanceThresholds {
+    pub fn get_space() -> usize {
+        // ProposalThreshold::get_space() * 9
+        18
+    }
+
+    pub fn verify(&self) -> ProgramResult {
+        use constants::*;
+        for critical in [&self.program_upgrade, &self.validator_id, &self.thresholds] {
+            critical.verify(MIN_CRITICAL_PROPOSAL_QUORUM, GOVERNANCE_EXECUTION_THRESHOLD)?;
+        }
+        for other in [
+            &self.commission,
+            &self.economics,
+            &self.profile,
+            &self.uploaders,
+            &self.uris,
+            &self.treasury,
+        ] {
+            other.verify(MIN_PROPOSAL_QUORUM, MIN_PROPOSAL_APPROVAL)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Validate)]
 #[validation_phrase(crate::state::constants::INGL_CONFIG_VAL_PHRASE)]
 pub struct ValidatorConfig {
     pub validation_phrase: u32,
@@ -95,12 +266,35 @@ pub struct ValidatorConfig {
     pub initial_redemption_fee: u8,
     pub unit_backing: u64,
     pub redemption_fee_duration: u32,
-    pub proposal_quorum: u8,
+    pub governance_thresholds: GovernanceThresholds,
     pub creator_royalties: u16,
     pub commission: u8,
     pub validator_id: Pubkey,
     pub vote_account: Pubkey,
     pub governance_expiration_time: u32,
+    /// Fee paid to the vote account to reroll the rarity of an NFT. None disables rerolls.
+    pub rarity_reroll_fee: Option<u64>,
+    /// Minimum time between two rerolls of the same NFT.
+    pub rarity_reroll_cooldown: u32,
+    /// Fee paid to the vote account on each mint, on top of the unit backing.
+    pub mint_fee: u64,
+    /// Share of the mint fee paid to the referrer of a mint, in basis points.
+    pub referral_fee_bps: u16,
+    /// Rule set of the programmable NFTs minted from now on. None mints legacy NFTs.
+    pub programmable_rule_set: Option<Pubkey>,
+    /// Mints Token-2022 gems, whose transfer hook checkpoints their rewards to the previous holder. Chosen at init.
+    pub uses_token_2022: bool,
+    /// Boost tiers of locked NFTs, sorted by increasing min_duration. Boosts are funded from the validator's share of the rewards.
+    pub lock_boost_tiers: Vec<LockBoostTier>,
+    pub quorum_basis: QuorumBasis,
+    /// Deposit escrowed in each proposal, refunded to the proposer if it reaches quorum and paid to the vote account otherwise.
+    pub proposal_deposit: u64,
+    /// Percentage of the rewards, after the team share, paid into the treasury.
+    pub treasury_share: u8,
+    /// Multisig able to veto passed proposals during the governance safety leeway and to pause the program.
+    pub guardian: Option<Pubkey>,
+    /// Blocks minting, delegation and upgrades. Redemptions and withdrawals are always permitted.
+    pub is_paused: bool,
     pub default_uri: String,
     pub validator_name: String,
     pub twitter_handle: String,
@@ -110,9 +304,10 @@ pub struct ValidatorConfig {
 
 impl ValidatorConfig {
     pub fn get_space(&self) -> usize {
-        // 4 + 1 + 8 + 1 + 1 + 8 + 4 + 1 + 2 + 1 + 32 + 32 + 4 + (self.collection_uri.len() + 4) + (self.validator_name.len() + 4) + (self.twitter_handle.len() + 4) + (self.discord_invite.len() + 4) + (self.website.len() + 4)
-        // 4 + 1 + 8 + 1 + 1 + 8 + 4 + 1 + 2 + 1 + 32 + 32 + 4 + 4 + 4 + 4 + 4 + 4  = 119
-        119 + self.default_uri.len()
+        // 4 + 1 + 8 + 1 + 1 + 8 + 4 + GovernanceThresholds::get_space() + 2 + 1 + 32 + 32 + 4 + (1 + 8) + 4 + 8 + 2 + (1 + 32) + 1 + (LockBoostTier::get_space() * self.lock_boost_tiers.len() + 4) + 1 + 8 + 1 + (1 + 32) + 1 + (self.collection_uri.len() + 4) + (self.validator_name.len() + 4) + (self.twitter_handle.len() + 4) + (self.discord_invite.len() + 4) + (self.website.len() + 4)
+        // 4 + 1 + 8 + 1 + 1 + 8 + 4 + 18 + 2 + 1 + 32 + 32 + 4 + 9 + 4 + 8 + 2 + 33 + 1 + 4 + 1 + 8 + 1 + 33 + 1 + 4 + 4 + 4 + 4 + 4  = 241
+        241 + (LockBoostTier::get_space() * self.lock_boost_tiers.len())
+            + self.default_uri.len()
             + self.validator_name.len()
             + self.twitter_handle.len()
             + self.discord_invite.len()
@@ -120,24 +315,13 @@ impl ValidatorConfig {
     }
 
     pub fn validate_data(&self) -> ProgramResult {
+        if self.validation_phrase != constants::INGL_CONFIG_VAL_PHRASE {
+            Err(InglError::InvalidConfigData.utilize("Validation phrase is incorrect"))?
+        }
         if !self.is_validator_id_switchable && self.initial_redemption_fee != 0 {
             Err(InglError::InvalidConfigData
                 .utilize("Validator id must be switchable if there exists any redemption fee"))?
         }
-        if self.redemption_fee_duration > 86400 * 365 * 2 {
-            Err(InglError::InvalidConfigData
-                .utilize("Redemption fee duration must be less than 2 years"))?
-        }
-        if self.nft_holders_share > 100 {
-            Err(InglError::InvalidConfigData.utilize("NFT holders share must be less than 100%"))?
-        }
-        if self.nft_holders_share < 50 {
-            Err(InglError::InvalidConfigData.utilize("NFT holders share must be greater than 50%"))?
-        }
-        if self.initial_redemption_fee > 25 {
-            Err(InglError::InvalidConfigData
-                .utilize("Initial redemption fee must be less than 25%"))?
-        }
         if self.unit_backing < get_min_stake_account_lamports() {
             Err(InglError::InvalidConfigData.utilize("Unit backing must be greater than 1.03 Sol"))?
         }
@@ -145,96 +329,259 @@ impl ValidatorConfig {
             Err(InglError::InvalidConfigData
                 .utilize("Max primary stake must be greater unit backing."))?
         }
-        if self.validation_phrase != constants::INGL_CONFIG_VAL_PHRASE {
-            Err(InglError::InvalidConfigData.utilize("Validation phrase is incorrect"))?
+        if self.uses_token_2022 && self.programmable_rule_set.is_some() {
+            Err(InglError::InvalidConfigData.utilize("Token-2022 gems can't be programmable NFTs"))?
         }
-        if self.proposal_quorum > 100 {
+        Self::verify_redemption_fee_duration(self.redemption_fee_duration)?;
+        Self::verify_nft_holders_share(self.nft_holders_share)?;
+        Self::verify_initial_redemption_fee(self.initial_redemption_fee)?;
+        self.governance_thresholds.verify()?;
+        Self::verify_creator_royalties(self.creator_royalties)?;
+        Self::verify_commission(self.commission)?;
+        Self::verify_validator_name(&self.validator_name)?;
+        Self::verify_twitter_handle(&self.twitter_handle)?;
+        Self::verify_discord_invite(&self.discord_invite)?;
+        Self::verify_website(&self.website)?;
+        Self::verify_governance_expiration_time(self.governance_expiration_time)?;
+        Self::verify_rarity_reroll_cooldown(self.rarity_reroll_cooldown)?;
+        Self::verify_referral_fee_bps(self.referral_fee_bps)?;
+        Self::verify_lock_boost_tiers(&self.lock_boost_tiers)?;
+        Self::verify_proposal_deposit(self.proposal_deposit)?;
+        Self::verify_treasury_share(self.treasury_share)?;
+        Self::verify_default_uri(&self.default_uri)?;
+        Ok(())
+    }
+
+    // The field validations below are shared by validate_data and the config governance types.
+
+    pub fn verify_redemption_fee_duration(redemption_fee_duration: u32) -> ProgramResult {
+        if redemption_fee_duration > 86400 * 365 * 2 {
             Err(InglError::InvalidConfigData
-                .utilize("Program upgrade threshold must be less than 100%"))?
+                .utilize("Redemption fee duration must be less than 2 years"))?
         }
-        if self.proposal_quorum < 65 {
+        Ok(())
+    }
+
+    pub fn verify_nft_holders_share(nft_holders_share: u8) -> ProgramResult {
+        if nft_holders_share > 100 {
+            Err(InglError::InvalidConfigData.utilize("NFT holders share must be less than 100%"))?
+        }
+        if nft_holders_share < 50 {
+            Err(InglError::InvalidConfigData.utilize("NFT holders share must be greater than 50%"))?
+        }
+        Ok(())
+    }
+
+    pub fn verify_initial_redemption_fee(initial_redemption_fee: u8) -> ProgramResult {
+        if initial_redemption_fee > 25 {
             Err(InglError::InvalidConfigData
-                .utilize("Program upgrade threshold must be less than 65%"))?
+                .utilize("Initial redemption fee must be less than 25%"))?
         }
-        if self.creator_royalties > 200 {
+        Ok(())
+    }
+
+    pub fn verify_creator_royalties(creator_royalties: u16) -> ProgramResult {
+        if creator_royalties > 200 {
             Err(InglError::InvalidConfigData.utilize("Creator royalties must be less than 2%"))?
         }
-        if self.commission > 100 {
+        Ok(())
+    }
+
+    pub fn verify_commission(commission: u8) -> ProgramResult {
+        if commission > 100 {
             Err(InglError::InvalidConfigData.utilize("Commission must be less than 100%"))?
         }
-        if self.validator_name.len() > 32 {
+        Ok(())
+    }
+
+    pub fn verify_validator_name(validator_name: &str) -> ProgramResult {
+        if validator_name.len() > 32 {
             Err(InglError::InvalidConfigData
                 .utilize("Validator name must be less than 32 characters"))?
         }
-        if self.twitter_handle.len() > 32 {
+        Ok(())
+    }
+
+    pub fn verify_twitter_handle(twitter_handle: &str) -> ProgramResult {
+        if twitter_handle.len() > 32 {
             Err(InglError::InvalidConfigData
                 .utilize("Twitter handle must be less than 32 characters"))?
         }
-        if self.discord_invite.len() > 32 {
+        Ok(())
+    }
+
+    pub fn verify_discord_invite(discord_invite: &str) -> ProgramResult {
+        if discord_invite.len() > 32 {
             Err(InglError::InvalidConfigData
                 .utilize("Discord invite must be less than 32 characters"))?
         }
-        if self.website.len() > 64 {
-            Err(InglError::InvalidConfigData.utilize("Website must be less than 32 characters"))?
+        Ok(())
+    }
+
+    pub fn verify_website(website: &str) -> ProgramResult {
+        if website.len() > 64 {
+            Err(InglError::InvalidConfigData.utilize("Website must be less than 64 characters"))?
         }
-        if self.governance_expiration_time < 86400 * 35 {
+        Ok(())
+    }
+
+    pub fn verify_governance_expiration_time(governance_expiration_time: u32) -> ProgramResult {
+        if governance_expiration_time < 86400 * 35 {
             Err(InglError::InvalidConfigData
                 .utilize("Governance expiration time must be greater than 35 days"))?
         }
-        if self.governance_expiration_time > 86400 * 365 {
+        if governance_expiration_time > 86400 * 365 {
             Err(InglError::InvalidConfigData
                 .utilize("Governance expiration time must be less than 1 year"))?
         }
-        if self.default_uri.len() > 75 {
+        Ok(())
+    }
+
+    pub fn verify_rarity_reroll_cooldown(rarity_reroll_cooldown: u32) -> ProgramResult {
+        if rarity_reroll_cooldown > 86400 * 365 {
+            Err(InglError::InvalidConfigData
+                .utilize("Rarity reroll cooldown must be less than 1 year"))?
+        }
+        Ok(())
+    }
+
+    pub fn verify_referral_fee_bps(referral_fee_bps: u16) -> ProgramResult {
+        if referral_fee_bps as u64 > constants::BASIS_POINTS {
+            Err(InglError::InvalidConfigData
+                .utilize("Referral fee can't exceed 100% of the mint fee"))?
+        }
+        Ok(())
+    }
+
+    pub fn verify_lock_boost_tiers(lock_boost_tiers: &[LockBoostTier]) -> ProgramResult {
+        if lock_boost_tiers.len() > constants::MAX_LOCK_BOOST_TIERS {
+            Err(InglError::InvalidConfigData
+                .utilize("Lock boost tiers must be less than MAX_LOCK_BOOST_TIERS"))?
+        }
+        for (i, tier) in lock_boost_tiers.iter().enumerate() {
+            if tier.boost_bps as u64 > constants::BASIS_POINTS {
+                Err(InglError::InvalidConfigData
+                    .utilize("Lock boosts must be less than 100% of the regular reward"))?
+            }
+            if tier.min_duration > constants::MAX_LOCK_DURATION {
+                Err(InglError::InvalidConfigData
+                    .utilize("Lock boost tier durations must be less than MAX_LOCK_DURATION"))?
+            }
+            if i > 0 && tier.min_duration <= lock_boost_tiers[i - 1].min_duration {
+                Err(InglError::InvalidConfigData
+                    .utilize("Lock boost tiers must be sorted by increasing duration"))?
+            }
+        }
+        Ok(())
+    }
+
+    pub fn verify_proposal_deposit(proposal_deposit: u64) -> ProgramResult {
+        if proposal_deposit < constants::MIN_PROPOSAL_DEPOSIT {
+            Err(InglError::InvalidConfigData
+                .utilize("Proposal deposit must be at least MIN_PROPOSAL_DEPOSIT"))?
+        }
+        if proposal_deposit > constants::MAX_PROPOSAL_DEPOSIT {
+            Err(InglError::InvalidConfigData
+                .utilize("Proposal deposit must be less than MAX_PROPOSAL_DEPOSIT"))?
+        }
+        Ok(())
+    }
+
+    pub fn assert_not_paused(&self) -> ProgramResult {
+        if self.is_paused {
+            Err(InglError::ProgramPaused.utilize("Only redemptions and withdrawals are permitted"))?
+        }
+        Ok(())
+    }
+
+    pub fn verify_treasury_share(treasury_share: u8) -> ProgramResult {
+        if treasury_share > constants::MAX_TREASURY_SHARE {
+            Err(InglError::InvalidConfigData
+                .utilize("Treasury share must be less than MAX_TREASURY_SHARE"))?
+        }
+        Ok(())
+    }
+
+    pub fn verify_default_uri(default_uri: &str) -> ProgramResult {
+        if default_uri.len() > 75 {
             Err(InglError::InvalidConfigData
-                .utilize("Collection URI must be less than 64 characters"))?
+                .utilize("Collection URI must be less than 75 characters"))?
         }
         Ok(())
     }
 
     pub fn new(
-        is_validator_id_switchable: bool,
-        max_primary_stake: u64,
-        nft_holders_share: u8,
-        initial_redemption_fee: u8,
-        unit_backing: u64,
-        redemption_fee_duration: u32,
-        proposal_quorum: u8,
-        creator_royalties: u16,
-        commission: u8,
+        init_args: &InitArgs,
         validator_id: Pubkey,
         vote_account: Pubkey,
-        governance_expiration_time: u32,
-        default_uri: String,
-        validator_name: String,
-        twitter_handle: String,
-        discord_invite: String,
-        website: String,
     ) -> Result<Self, ProgramError> {
         let i = Self {
             validation_phrase: constants::INGL_CONFIG_VAL_PHRASE,
-            is_validator_id_switchable,
-            max_primary_stake,
-            nft_holders_share,
-            initial_redemption_fee,
-            unit_backing,
-            redemption_fee_duration,
-            proposal_quorum,
-            creator_royalties,
-            commission,
+            is_validator_id_switchable: init_args.is_validator_id_switchable,
+            max_primary_stake: init_args.max_primary_stake,
+            nft_holders_share: init_args.nft_holders_share,
+            initial_redemption_fee: init_args.initial_redemption_fee,
+            unit_backing: init_args.unit_backing,
+            redemption_fee_duration: init_args.redemption_fee_duration,
+            governance_thresholds: init_args.governance_thresholds,
+            creator_royalties: init_args.creator_royalties,
+            commission: init_args.init_commission,
             validator_id,
             vote_account,
-            governance_expiration_time,
-            default_uri,
-            validator_name,
-            twitter_handle,
-            discord_invite,
-            website,
+            governance_expiration_time: init_args.governance_expiration_time,
+            rarity_reroll_fee: init_args.rarity_reroll_fee,
+            rarity_reroll_cooldown: init_args.rarity_reroll_cooldown,
+            mint_fee: init_args.mint_fee,
+            referral_fee_bps: init_args.referral_fee_bps,
+            programmable_rule_set: init_args.programmable_rule_set,
+            uses_token_2022: init_args.uses_token_2022,
+            lock_boost_tiers: init_args.lock_boost_tiers.clone(),
+            quorum_basis: init_args.quorum_basis,
+            proposal_deposit: init_args.proposal_deposit,
+            treasury_share: init_args.treasury_share,
+            guardian: init_args.guardian,
+            is_paused: false,
+            default_uri: init_args.default_uri.clone(),
+            validator_name: init_args.validator_name.clone(),
+            twitter_handle: init_args.twitter_handle.clone(),
+            discord_invite: init_args.discord_invite.clone(),
+            website: init_args.website.clone(),
         };
         i.validate_data()
             .error_log("Error @ Config Data Validation")?;
         Ok(i)
     }
+    /// Returns the stake an NFT of the given reward weight accounts for in the rewards distribution.
+    pub fn get_weighted_backing(&self, reward_weight: u16) -> u64 {
+        (self.unit_backing as u128 * reward_weight as u128 / constants::REWARD_WEIGHT_BASE as u128)
+            as u64
+    }
+
+    /// Returns the share of the mint fee paid to the referrer of a mint.
+    pub fn get_referral_fee(&self) -> u64 {
+        (self.mint_fee as u128 * self.referral_fee_bps as u128 / constants::BASIS_POINTS as u128)
+            as u64
+    }
+
+    /// Returns the index of the best boost tier a lock of the given duration qualifies for.
+    pub fn get_lock_boost_tier(&self, duration: u32) -> Option<u8> {
+        self.lock_boost_tiers
+            .iter()
+            .rposition(|tier| tier.min_duration <= duration)
+            .map(|index| index as u8)
+    }
+
+    /// Returns the stake a locked NFT accounts for in the distribution of the lock boosts.
+    pub fn get_boost_weight(&self, reward_weight: u16, boost_tier: Option<u8>) -> u64 {
+        match boost_tier.and_then(|tier| self.lock_boost_tiers.get(tier as usize)) {
+            Some(tier) => {
+                (self.get_weighted_backing(reward_weight) as u128 * tier.boost_bps as u128
+                    / constants::BASIS_POINTS as u128) as u64
+            }
+            None => 0,
+        }
+    }
+
     pub fn get_redeem_fee(&self, age: u32) -> u64 {
         if age > self.redemption_fee_duration {
             return 0;
@@ -262,30 +609,37 @@ pub struct UrisAccount {
     /// then the vector will be [6000, 9000, 10000]
     pub rarities: Vec<u16>,
     pub rarity_names: Vec<String>,
+    /// Reward weight of each rarity, where REWARD_WEIGHT_BASE is the share of a regular NFT.
+    /// i.e. [100, 150, 300] gives the third rarity thrice the rewards of the first one.
+    /// Empty if rewards are not weighted by rarity.
+    pub reward_weights: Vec<u16>,
     pub uris: Vec<Vec<String>>,
+    /// Number of UrisPage accounts of each rarity. A rarity with pages takes its uris from them instead of the uris vector.
+    pub uri_pages: Vec<u16>,
+    /// Prefix shared by all the uris, so only their content identifiers are stored.
+    /// i.e. "https://arweave.net/" with uris stored as arweave transaction ids.
+    pub base_uri: String,
+    /// Merkle commitment to the uris of each rarity, whose uris are then supplied with a proof at imprint time instead of being stored.
+    pub uri_roots: Vec<Option<UrisRoot>>,
+    /// Set by FreezeUris or the first imprint. Frozen uris and rarities can only be changed through governance.
+    pub is_frozen: bool,
 }
 impl UrisAccount {
-    pub fn new(rarities: Vec<u16>, names: Vec<String>) -> Result<Self, ProgramError> {
-        if rarities.iter().sum::<u16>() != CUMMULATED_RARITY {
-            Err(InglError::InvalidUrisAccountData.utilize("Rarities must sum to 10000"))?
-        }
-        let mut new_rarities = Vec::new();
-        for i in rarities {
-            if i == 0 {
-                Err(InglError::InvalidUrisAccountData.utilize("Rarities must be greater than 0"))?
-            }
-            if new_rarities.len() == 0 {
-                new_rarities.push(i);
-            } else {
-                new_rarities.push(i + new_rarities[new_rarities.len() - 1]);
-            }
-        }
-
+    pub fn new(
+        rarities: Vec<u16>,
+        names: Vec<String>,
+        reward_weights: Vec<u16>,
+    ) -> Result<Self, ProgramError> {
         let uri_account = Self {
             validation_phrase: constants::URIS_ACCOUNT_VAL_PHRASE,
             rarity_names: names,
-            rarities: new_rarities,
+            rarities: Self::get_cumulated_rarities(&rarities)?,
+            reward_weights,
             uris: Vec::new(),
+            uri_pages: Vec::new(),
+            base_uri: String::new(),
+            uri_roots: Vec::new(),
+            is_frozen: false,
         };
         uri_account
             .validate_data()
@@ -293,6 +647,22 @@ impl UrisAccount {
         Ok(uri_account)
     }
 
+    /// Converts the share of each rarity to the cumulated rarities the uris account stores.
+    /// i.e. [6000, 3000, 1000] gives [6000, 9000, 10000]
+    pub fn get_cumulated_rarities(rarities: &[u16]) -> Result<Vec<u16>, ProgramError> {
+        if rarities.iter().map(|x| *x as u32).sum::<u32>() != CUMMULATED_RARITY as u32 {
+            Err(InglError::InvalidUrisAccountData.utilize("Rarities must sum to 10000"))?
+        }
+        let mut new_rarities: Vec<u16> = Vec::new();
+        for i in rarities {
+            if *i == 0 {
+                Err(InglError::InvalidUrisAccountData.utilize("Rarities must be greater than 0"))?
+            }
+            new_rarities.push(i + new_rarities.last().unwrap_or(&0));
+        }
+        Ok(new_rarities)
+    }
+
     pub fn validate_data(&self) -> ProgramResult {
         if self.validation_phrase != constants::URIS_ACCOUNT_VAL_PHRASE {
             Err(InglError::InvalidUrisAccountData.utilize("Validation phrase is incorrect"))?
@@ -307,6 +677,22 @@ impl UrisAccount {
             Err(InglError::InvalidUrisAccountData
                 .utilize("Rarity names vector length must be equal to rarities vector length"))?
         }
+        if !self.reward_weights.is_empty() && self.reward_weights.len() != self.rarities.len() {
+            Err(InglError::InvalidUrisAccountData
+                .utilize("Reward weights vector length must be equal to rarities vector length"))?
+        }
+        if self
+            .reward_weights
+            .iter()
+            .any(|x| *x < constants::REWARD_WEIGHT_BASE || *x > constants::MAX_REWARD_WEIGHT)
+        {
+            Err(InglError::InvalidUrisAccountData
+                .utilize("Reward weights must be between 1x and 10x the base weight"))?
+        }
+        if self.base_uri.len() > constants::MAX_BASE_URI_LENGTH {
+            Err(InglError::InvalidUrisAccountData
+                .utilize("Base uri must be less than MAX_BASE_URI_LENGTH characters"))?
+        }
         Ok(())
     }
 
@@ -324,29 +710,86 @@ impl UrisAccount {
         Ok(())
     }
 
-    pub fn get_uri(&self, mut seed: u16) -> (String, u8) {
+    pub fn get_uri(&self, mut seed: u16, seed_hash: &[u8; 32]) -> (String, u8) {
         seed = seed % 10000;
         let ind = self.rarities.iter().position(|x| *x > seed).unwrap();
+        let uri_index =
+            get_seed_part(seed_hash, constants::URI_SEED_OFFSET) % self.uris[ind].len() as u64;
         (
-            self.uris[ind][seed as usize % self.uris[ind].len()].clone(),
+            self.get_full_uri(&self.uris[ind][uri_index as usize]),
             ind as u8,
         )
     }
+    pub fn assert_not_frozen(&self) -> ProgramResult {
+        if self.is_frozen {
+            Err(InglError::UrisFrozen.utilize("Uris can only be changed through governance"))?
+        }
+        Ok(())
+    }
+    /// Rebuilds a full uri from its stored content identifier.
+    pub fn get_full_uri(&self, uri: &str) -> String {
+        format!("{}{}", self.base_uri, uri)
+    }
+    /// Returns the merkle commitment to the uris of the given rarity, if any.
+    pub fn get_uris_root(&self, rarity: u8) -> Option<UrisRoot> {
+        self.uri_roots.get(rarity as usize).copied().flatten()
+    }
+    /// Returns the rarity the seed falls in, as get_uri does.
+    pub fn get_rarity(&self, seed: u16) -> u8 {
+        self.rarities
+            .iter()
+            .position(|x| *x > seed % 10000)
+            .unwrap() as u8
+    }
+    /// Returns the number of UrisPage accounts of the given rarity.
+    pub fn get_page_count(&self, rarity: u8) -> u16 {
+        *self.uri_pages.get(rarity as usize).unwrap_or(&0)
+    }
+    /// Returns the page the uri of the given seed hash is taken from, None if the rarity has no pages.
+    pub fn get_page(&self, rarity: u8, seed_hash: &[u8; 32]) -> Option<u16> {
+        match self.get_page_count(rarity) {
+            0 => None,
+            page_count => Some(
+                (get_seed_part(seed_hash, constants::PAGE_SEED_OFFSET) % page_count as u64) as u16,
+            ),
+        }
+    }
+    /// Returns the reward weight of an NFT of the given rarity. Unimprinted NFTs have the base weight.
+    pub fn get_reward_weight(&self, rarity: Option<u8>) -> u16 {
+        match rarity {
+            Some(rarity) => *self
+                .reward_weights
+                .get(rarity as usize)
+                .unwrap_or(&constants::REWARD_WEIGHT_BASE),
+            None => constants::REWARD_WEIGHT_BASE,
+        }
+    }
     pub fn default() -> Self {
         Self {
             validation_phrase: constants::URIS_ACCOUNT_VAL_PHRASE,
             rarities: Vec::new(),
             rarity_names: Vec::new(),
+            reward_weights: Vec::new(),
             uris: Vec::new(),
+            uri_pages: Vec::new(),
+            base_uri: String::new(),
+            uri_roots: Vec::new(),
+            is_frozen: false,
         }
     }
     pub fn get_space(&self) -> usize {
         let mut space = 4;
         space += self.rarities.len() * 2 + 4;
-        self.rarity_names.iter().for_each(|x| {
-            space += x.len() + 4;
-        });
-        space += 8;
+        space += self.rarity_names.iter().map(|x| x.len() + 4).sum::<usize>() + 4;
+        space += self.reward_weights.len() * 2 + 4;
+        space += self.uri_pages.len() * 2 + 4;
+        space += self.base_uri.len() + 4;
+        space += 4;
+        for i in self.uri_roots.iter() {
+            space += 1 + i.map_or(0, |_| UrisRoot::get_space());
+        }
+        space += 1;
+        space += 4;
         for i in self.uris.iter() {
             space += 4;
             for j in i.iter() {
@@ -366,13 +809,19 @@ pub struct VoteReward {
     pub total_reward: u64,
     /// This is the total primary staked nft sol of the vote account before the process_rewards.
     pub total_stake: u64,
+    /// This is the total_stake with each nft's backing weighted by its rarity's reward weight.
+    pub total_weighted_stake: u64,
     /// This is the total reward that will be distributed to primary stakers.
     pub nft_holders_reward: u64,
+    /// This is the total boost weight of the locked nfts before the process_rewards.
+    pub total_boost_weight: u64,
+    /// This is the part of the validator's share distributed to locked nfts in proportion of their boost weight.
+    pub boost_reward: u64,
 }
 
 impl VoteReward {
     pub fn get_space() -> usize {
-        32
+        56
     }
 }
 
@@ -410,6 +859,9 @@ pub struct GeneralData {
     pub pending_delegation_total: u64,
     pub dealloced: u64,
     pub total_delegated: u64,
+    pub total_weighted_delegated: u64,
+    /// Sum of the boost weights of the locked nfts.
+    pub total_boost_weight: u64,
     pub last_withdraw_epoch: u64, //TODO: This isn't necessary right? What are the intentions behind this
     pub last_total_staked: u64,
     pub is_t_stake_initialized: bool,
@@ -422,9 +874,9 @@ pub struct GeneralData {
 }
 impl GeneralData {
     pub fn get_space(&self) -> usize {
-        // 4 + 4 + 8 + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 4 + RebalancingData::get_space() + (unfinalized_proposals.len() * 4 + 4) + (VoteReward::get_space() * self.vote_rewards.len() + 4)
-        // 4 + 4 + 8 + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 4 + 4 + 4 = 69
-        69 + RebalancingData::get_space()
+        // 4 + 4 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 4 + RebalancingData::get_space() + (unfinalized_proposals.len() * 4 + 4) + (VoteReward::get_space() * self.vote_rewards.len() + 4)
+        // 4 + 4 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 4 + 4 + 4 = 85
+        85 + RebalancingData::get_space()
             + (VoteReward::get_space() * self.vote_rewards.len())
             + (self.unfinalized_proposals.len() * 4)
     }
@@ -438,6 +890,8 @@ impl Default for GeneralData {
             pending_delegation_total: 0,
             dealloced: 0,
             total_delegated: 0,
+            total_weighted_delegated: 0,
+            total_boost_weight: 0,
             last_withdraw_epoch: 0,
             last_total_staked: 0,
             is_t_stake_initialized: false,
@@ -471,13 +925,400 @@ pub struct NftData {
     pub last_delegation_epoch: Option<u64>,
     pub all_withdraws: Vec<u64>,
     pub all_votes: BTreeMap<u32, bool>,
+    /// Hash of a secret the holder committed to before the rarity seed slot, revealed at imprint time.
+    pub rarity_commitment: Option<[u8; 32]>,
+    pub reroll_count: u32,
+    pub last_reroll_date: Option<u32>,
+    /// Whether the NFT was minted as a Metaplex programmable NFT.
+    pub is_programmable: bool,
+    /// Holder the rewards accrued since the last withdrawal are owed to.
+    /// None once they were settled, so the next holder to interact with the NFT earns from the settlement on.
+    pub last_known_owner: Option<Pubkey>,
+    /// Date before which the NFT can't be unlocked. None if the NFT isn't locked.
+    pub lock_until: Option<u32>,
+    /// Index of the lock boost tier the NFT earns while locked.
+    pub lock_boost_tier: Option<u8>,
+    /// Last epoch last_known_owner was seen holding the NFT.
+    /// If the NFT changed hands without being settled, they are only owed the rewards processed up to this epoch.
+    pub last_owner_seen_epoch: Option<u64>,
+    /// Weighted backing the NFT added to general_data.total_weighted_delegated when it was delegated or imprinted. 0 if undelegated.
+    pub weighted_backing: u64,
+    /// Boost weight the NFT added to general_data.total_boost_weight when it was locked. 0 if unlocked.
+    pub boost_weight: u64,
 }
 impl NftData {
     pub fn get_space(&self) -> usize {
-        // 4 + (1 + 1) + (1 + 8) + 1 + 4 + 4 + (1 + 8) + (1 + 8) + (8 * self.all_withdraws.len() + 4) + (5 * self.all_votes.len() + 4)
-        // 4 + 1 + 1 + 1 + 8 + 1 + 4 + 4 + 9 + 9 + 4 + 4 = 40
-        60 + (8 * self.all_withdraws.len()) + (5 * self.all_votes.len())
+        // 4 + (1 + 1) + (1 + 8) + 1 + 4 + 4 + (1 + 8) + (1 + 8) + (8 * self.all_withdraws.len() + 4) + (5 * self.all_votes.len() + 4) + (1 + 32) + 4 + (1 + 4) + 1 + (1 + 32) + (1 + 4) + (1 + 1) + (1 + 8) + 8 + 8
+        // 4 + 2 + 9 + 1 + 4 + 4 + 9 + 9 + 4 + 4 + 33 + 4 + 5 + 1 + 33 + 5 + 2 + 9 + 8 + 8 = 158, plus the 10 spare bytes NFT accounts always had = 168
+        168 + (8 * self.all_withdraws.len()) + (5 * self.all_votes.len())
+    }
+
+    /// Query string appended to the gem's metadata uri by SyncMetadata, exposing its on-chain state as attributes.
+    /// i.e. "?status=delegated&rarity=Gold&withdrawn=1500000"
+    pub fn get_metadata_attributes(&self, rarity_name: Option<&String>) -> String {
+        let status = match self.funds_location {
+            FundsLocation::Delegated => "delegated",
+            FundsLocation::Undelegated => "undelegated",
+        };
+        let rarity = match rarity_name {
+            Some(name) => name
+                .bytes()
+                .map(|byte| match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                        (byte as char).to_string()
+                    }
+                    _ => format!("%{:02X}", byte),
+                })
+                .collect(),
+            None => "unrevealed".to_string(),
+        };
+        format!(
+            "?status={}&rarity={}&withdrawn={}",
+            status,
+            rarity,
+            self.all_withdraws.iter().sum::<u64>()
+        )
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::VRF_STATE_VAL_PHRASE)]
+/// Selects the oracle program whose VRF results are used to seed rarities.
+pub struct VrfState {
+    pub validation_phrase: u32,
+    /// The program owning the VRF result accounts. None disables the VRF path.
+    pub oracle_program: Option<Pubkey>,
+    /// The signer the oracle program records on the VRF results it fulfils.
+    pub oracle_authority: Pubkey,
+}
+impl VrfState {
+    pub fn get_space() -> usize {
+        // 4 + (1 + 32) + 32
+        69
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+/// A window of time during which minting is open, with its own access rules.
+pub struct MintPhase {
+    pub start_time: u32,
+    /// None if the phase lasts until the next one starts.
+    pub end_time: Option<u32>,
+    /// Merkle root of the allowlisted minter pubkeys. None if the phase is public.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Maximum number of mints per wallet during this phase. None if uncapped.
+    pub wallet_cap: Option<u32>,
+    /// Lamports paid to the vote account on top of the unit backing for each mint.
+    pub price_premium: u64,
+}
+impl MintPhase {
+    pub fn get_space(&self) -> usize {
+        // 4 + (1 + 4) + (1 + 32) + (1 + 4) + 8
+        55
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::MINT_PHASES_VAL_PHRASE)]
+/// The schedule of mint phases. Minting is open to everyone when the account doesn't exist.
+pub struct MintPhases {
+    pub validation_phrase: u32,
+    pub phases: Vec<MintPhase>,
+    /// Incremented by every SetMintPhases, so that minter records of a replaced schedule don't carry over.
+    pub schedule_version: u32,
+}
+impl MintPhases {
+    pub fn get_space(&self) -> usize {
+        4 + 4 + self.phases.iter().map(|x| x.get_space()).sum::<usize>() + 4
+    }
+
+    pub fn validate_data(&self) -> ProgramResult {
+        if self.phases.len() > constants::MAX_MINT_PHASES {
+            Err(InglError::InvalidData.utilize("Too many mint phases"))?
+        }
+        for (i, phase) in self.phases.iter().enumerate() {
+            if let Some(end_time) = phase.end_time {
+                if end_time <= phase.start_time {
+                    Err(InglError::InvalidData.utilize("Mint phase must end after it starts"))?
+                }
+            }
+            if i > 0 && phase.start_time <= self.phases[i - 1].start_time {
+                Err(InglError::InvalidData
+                    .utilize("Mint phases must be sorted by increasing start time"))?
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the index and phase open at the given time, if any.
+    pub fn get_active_phase(&self, now: u32) -> Option<(u8, &MintPhase)> {
+        let index = self.phases.iter().rposition(|x| x.start_time <= now)?;
+        let phase = &self.phases[index];
+        match phase.end_time {
+            Some(end_time) if end_time <= now => None,
+            _ => Some((index as u8, phase)),
+        }
+    }
+
+    /// Whether the phase at the given index has ended, or was never part of the schedule.
+    pub fn is_phase_over(&self, index: u8, now: u32) -> bool {
+        let index = index as usize;
+        match self.phases.get(index) {
+            Some(phase) => {
+                phase.end_time.map_or(false, |end_time| end_time <= now)
+                    || self
+                        .phases
+                        .get(index + 1)
+                        .map_or(false, |next| next.start_time <= now)
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::MINTER_RECORD_VAL_PHRASE)]
+/// Counts the mints of a wallet during a mint phase of a schedule version.
+pub struct MinterRecord {
+    pub validation_phrase: u32,
+    pub minted: u32,
+    pub minter: Pubkey,
+    pub schedule_version: u32,
+    pub phase_index: u8,
+}
+impl MinterRecord {
+    pub fn get_space() -> usize {
+        // 4 + 4 + 32 + 4 + 1
+        45
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::REFERRER_RECORD_VAL_PHRASE)]
+/// Referral totals of a referrer, used for leaderboards.
+pub struct ReferrerRecord {
+    pub validation_phrase: u32,
+    pub referrer: Pubkey,
+    pub referrals: u32,
+    pub total_fees_earned: u64,
+}
+impl ReferrerRecord {
+    pub fn get_space() -> usize {
+        // 4 + 32 + 4 + 8
+        48
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::REWARD_CLAIM_VAL_PHRASE)]
+/// Rewards settled to a previous holder of an NFT, claimable from the authorized withdrawer.
+pub struct RewardClaim {
+    pub validation_phrase: u32,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+impl RewardClaim {
+    pub fn get_space() -> usize {
+        // 4 + 32 + 32 + 8
+        76
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+/// An account authorized to upload uris on behalf of the validator.
+pub struct Uploader {
+    pub key: Pubkey,
+    /// Rarities the uploader may upload to. None allows all rarities, as well as changes spanning all of them.
+    pub rarities: Option<Vec<u8>>,
+}
+impl Uploader {
+    pub fn get_space(&self) -> usize {
+        32 + 1 + self.rarities.as_ref().map_or(0, |x| 4 + x.len())
+    }
+
+    pub fn verify(&self) -> ProgramResult {
+        if self.rarities.as_ref().map_or(false, |x| x.is_empty()) {
+            Err(InglError::InvalidData
+                .utilize("A scoped uploader must be allowed at least one rarity"))?
+        }
+        Ok(())
+    }
+
+    /// Returns whether the uploader may upload to the rarity, or to all rarities when None.
+    pub fn is_in_scope(&self, rarity: Option<u8>) -> bool {
+        match (&self.rarities, rarity) {
+            (None, _) => true,
+            (Some(rarities), Some(rarity)) => rarities.contains(&rarity),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::UPLOADERS_VAL_PHRASE)]
+/// Accounts authorized by the validator, or through governance, to upload uris.
+pub struct UploadersAccount {
+    pub validation_phrase: u32,
+    pub uploaders: Vec<Uploader>,
+}
+impl Default for UploadersAccount {
+    fn default() -> Self {
+        Self {
+            validation_phrase: constants::UPLOADERS_VAL_PHRASE,
+            uploaders: Vec::new(),
+        }
+    }
+}
+impl UploadersAccount {
+    pub fn get_space(&self) -> usize {
+        let mut space = 4 + 4;
+        for i in self.uploaders.iter() {
+            space += i.get_space();
+        }
+        space
+    }
+
+    pub fn get_uploader(&self, key: &Pubkey) -> Option<&Uploader> {
+        self.uploaders.iter().find(|x| x.key == *key)
+    }
+
+    /// Adds the uploader, or replaces the scope of an existing one.
+    pub fn add_uploader(&mut self, uploader: Uploader) -> ProgramResult {
+        uploader.verify()?;
+        match self.uploaders.iter_mut().find(|x| x.key == uploader.key) {
+            Some(existing) => existing.rarities = uploader.rarities,
+            None => {
+                if self.uploaders.len() >= constants::MAX_UPLOADERS {
+                    Err(InglError::InvalidData
+                        .utilize("Uploaders can't be more than MAX_UPLOADERS"))?
+                }
+                self.uploaders.push(uploader)
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_uploader(&mut self, key: &Pubkey) -> ProgramResult {
+        let index = self
+            .uploaders
+            .iter()
+            .position(|x| x.key == *key)
+            .error_log("Error: uploader is not authorized")?;
+        self.uploaders.remove(index);
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug)]
+/// Merkle root of the uris of a rarity. Leaves are hashv(&[index.to_le_bytes(), uri]), for the uris as stored.
+pub struct UrisRoot {
+    pub root: [u8; 32],
+    pub count: u32,
+}
+impl UrisRoot {
+    pub fn get_space() -> usize {
+        // 32 + 4
+        36
+    }
+
+    /// Returns the index of the committed uri picked by the given seed hash.
+    pub fn get_index(&self, seed_hash: &[u8; 32]) -> u32 {
+        (get_seed_part(seed_hash, constants::URI_SEED_OFFSET) % self.count as u64) as u32
+    }
+
+    pub fn get_leaf(index: u32, uri: &str) -> [u8; 32] {
+        hashv(&[&index.to_le_bytes(), uri.as_bytes()]).to_bytes()
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+/// A committed uri supplied at imprint time, with its merkle proof.
+pub struct UriProof {
+    pub uri: String,
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::URIS_PAGE_VAL_PHRASE)]
+/// A page of the uris of a rarity, for collections whose uris don't fit in the UrisAccount.
+pub struct UrisPage {
+    pub validation_phrase: u32,
+    pub rarity: u8,
+    pub page: u16,
+    pub uris: Vec<String>,
+}
+impl UrisPage {
+    pub fn get_space(&self) -> usize {
+        // 4 + 1 + 2 + (sum of (uri.len() + 4) + 4)
+        // 4 + 1 + 2 + 4 = 11
+        11 + self.uris.iter().map(|uri| uri.len() + 4).sum::<usize>()
+    }
+
+    /// Picks the uri of the given seed hash, after its page was picked with UrisAccount::get_page.
+    pub fn get_uri(&self, seed_hash: &[u8; 32]) -> String {
+        let uri_index =
+            get_seed_part(seed_hash, constants::URI_SEED_OFFSET) % self.uris.len() as u64;
+        self.uris[uri_index as usize].clone()
+    }
+}
+
+/// Reads the u64 at the given offset of an NFT's seed hash.
+pub fn get_seed_part(seed_hash: &[u8; 32], offset: usize) -> u64 {
+    u64::from_le_bytes(*array_ref![seed_hash, offset, 8])
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug)]
+pub struct Bid {
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::LISTING_VAL_PHRASE)]
+/// A gem on sale on the marketplace. The gem is escrowed in the listing's associated token account,
+/// and the highest bid's lamports are held by the listing account itself.
+pub struct Listing {
+    pub validation_phrase: u32,
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub highest_bid: Option<Bid>,
+}
+impl Listing {
+    pub fn get_space() -> usize {
+        // 4 + 32 + 32 + 8 + (1 + 32 + 8)
+        117
     }
+
+    pub fn get_royalty(&self, amount: u64, config_data: &ValidatorConfig) -> u64 {
+        (amount as u128 * config_data.creator_royalties as u128 / constants::BASIS_POINTS as u128)
+            as u64
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+/// Returned by the QuoteListing instruction.
+pub struct ListingQuote {
+    pub price: u64,
+    pub royalty: u64,
+    pub highest_bid: Option<Bid>,
+    /// Rewards accrued by the gem that will be settled to its previous holder at sale time.
+    pub claimable_rewards: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+/// Layout of a fulfilled VRF result account, as written by the oracle program for a given NFT mint.
+/// The account is the oracle program's PDA of [VRF_RESULT_KEY, mint].
+pub struct VrfResult {
+    /// The signer that fulfilled the request, which must be the oracle authority of the VRF state.
+    pub authority: Pubkey,
+    /// The NFT mint this randomness was requested for.
+    pub mint: Pubkey,
+    /// The slot the request was fulfilled at.
+    pub fulfilled_slot: u64,
+    /// The verified random output.
+    pub result: [u8; 32],
 }
 
 pub enum LogColors {
@@ -496,49 +1337,99 @@ pub enum ConfigAccountType {
     ValidatorName(String),
     TwitterHandle(String),
     DiscordInvite(String),
+    GovernanceThresholds(GovernanceThresholds),
+    Website(String),
+    DefaultUri(String),
+    CreatorRoyalties(u16),
+    GovernanceExpirationTime(u32),
+    IsValidatorIdSwitchable(bool),
+    RarityRerollFee(Option<u64>),
+    RarityRerollCooldown(u32),
+    MintFee(u64),
+    ReferralFeeBps(u16),
+    ProgrammableRuleSet(Option<Pubkey>),
+    LockBoostTiers(Vec<LockBoostTier>),
+    QuorumBasis(QuorumBasis),
+    ProposalDeposit(u64),
+    TreasuryShare(u8),
+    Guardian(Option<Pubkey>),
+    IsPaused(bool),
 }
 impl ConfigAccountType {
+    /// Checks the proposed value alone. Checks across fields are left to ValidatorConfig::validate_data.
     pub fn verify(&self) -> ProgramResult {
         match self {
             ConfigAccountType::MaxPrimaryStake(_) => (),
-            ConfigAccountType::NftHolderShare(x) => {
-                if *x > 100 {
-                    Err(InglError::InvalidData.utilize("NFT Holder Share must be 100 or less"))?
-                }
-            }
+            ConfigAccountType::NftHolderShare(x) => ValidatorConfig::verify_nft_holders_share(*x)?,
             ConfigAccountType::InitialRedemptionFee(x) => {
-                if *x > 100 {
-                    Err(InglError::InvalidData
-                        .utilize("Initial Redemption Fee must be 100 or less"))?
-                }
+                ValidatorConfig::verify_initial_redemption_fee(*x)?
             }
             ConfigAccountType::RedemptionFeeDuration(x) => {
-                if *x > 86400 * 365 * 2 {
-                    Err(InglError::InvalidData
-                        .utilize("Early Redemption Fee cannot exceed 2 years"))?
-                }
+                ValidatorConfig::verify_redemption_fee_duration(*x)?
             }
-            ConfigAccountType::ValidatorName(x) => {
-                if x.len() > 32 {
-                    Err(InglError::InvalidData
-                        .utilize("Validator Name Can't be more than 32 characters"))?
-                }
+            ConfigAccountType::ValidatorName(x) => ValidatorConfig::verify_validator_name(x)?,
+            ConfigAccountType::TwitterHandle(x) => ValidatorConfig::verify_twitter_handle(x)?,
+            ConfigAccountType::DiscordInvite(x) => ValidatorConfig::verify_discord_invite(x)?,
+            ConfigAccountType::GovernanceThresholds(x) => x.verify()?,
+            ConfigAccountType::Website(x) => ValidatorConfig::verify_website(x)?,
+            ConfigAccountType::DefaultUri(x) => ValidatorConfig::verify_default_uri(x)?,
+            ConfigAccountType::CreatorRoyalties(x) => {
+                ValidatorConfig::verify_creator_royalties(*x)?
             }
-            ConfigAccountType::TwitterHandle(x) => {
-                if x.len() > 32 {
-                    Err(InglError::InvalidData
-                        .utilize("Twitter Handle Can't be more than 32 characters"))?
-                }
+            ConfigAccountType::GovernanceExpirationTime(x) => {
+                ValidatorConfig::verify_governance_expiration_time(*x)?
             }
-            ConfigAccountType::DiscordInvite(x) => {
-                if x.len() > 32 {
-                    Err(InglError::InvalidData
-                        .utilize("Discord Invite Can't be more than 32 characters"))?
-                }
+            ConfigAccountType::IsValidatorIdSwitchable(_) => (),
+            ConfigAccountType::RarityRerollFee(_) => (),
+            ConfigAccountType::RarityRerollCooldown(x) => {
+                ValidatorConfig::verify_rarity_reroll_cooldown(*x)?
             }
+            ConfigAccountType::MintFee(_) => (),
+            ConfigAccountType::ReferralFeeBps(x) => ValidatorConfig::verify_referral_fee_bps(*x)?,
+            ConfigAccountType::ProgrammableRuleSet(_) => (),
+            ConfigAccountType::LockBoostTiers(x) => ValidatorConfig::verify_lock_boost_tiers(x)?,
+            ConfigAccountType::QuorumBasis(_) => (),
+            ConfigAccountType::ProposalDeposit(x) => ValidatorConfig::verify_proposal_deposit(*x)?,
+            ConfigAccountType::TreasuryShare(x) => ValidatorConfig::verify_treasury_share(*x)?,
+            ConfigAccountType::Guardian(_) => (),
+            ConfigAccountType::IsPaused(_) => (),
         };
         Ok(())
     }
+
+    /// Writes the proposed value into the config. The caller must run validate_data afterwards.
+    pub fn apply(self, config_data: &mut ValidatorConfig) {
+        match self {
+            ConfigAccountType::MaxPrimaryStake(x) => config_data.max_primary_stake = x,
+            ConfigAccountType::NftHolderShare(x) => config_data.nft_holders_share = x,
+            ConfigAccountType::InitialRedemptionFee(x) => config_data.initial_redemption_fee = x,
+            ConfigAccountType::RedemptionFeeDuration(x) => config_data.redemption_fee_duration = x,
+            ConfigAccountType::ValidatorName(x) => config_data.validator_name = x,
+            ConfigAccountType::TwitterHandle(x) => config_data.twitter_handle = x,
+            ConfigAccountType::DiscordInvite(x) => config_data.discord_invite = x,
+            ConfigAccountType::GovernanceThresholds(x) => config_data.governance_thresholds = x,
+            ConfigAccountType::Website(x) => config_data.website = x,
+            ConfigAccountType::DefaultUri(x) => config_data.default_uri = x,
+            ConfigAccountType::CreatorRoyalties(x) => config_data.creator_royalties = x,
+            ConfigAccountType::GovernanceExpirationTime(x) => {
+                config_data.governance_expiration_time = x
+            }
+            ConfigAccountType::IsValidatorIdSwitchable(x) => {
+                config_data.is_validator_id_switchable = x
+            }
+            ConfigAccountType::RarityRerollFee(x) => config_data.rarity_reroll_fee = x,
+            ConfigAccountType::RarityRerollCooldown(x) => config_data.rarity_reroll_cooldown = x,
+            ConfigAccountType::MintFee(x) => config_data.mint_fee = x,
+            ConfigAccountType::ReferralFeeBps(x) => config_data.referral_fee_bps = x,
+            ConfigAccountType::ProgrammableRuleSet(x) => config_data.programmable_rule_set = x,
+            ConfigAccountType::LockBoostTiers(x) => config_data.lock_boost_tiers = x,
+            ConfigAccountType::QuorumBasis(x) => config_data.quorum_basis = x,
+            ConfigAccountType::ProposalDeposit(x) => config_data.proposal_deposit = x,
+            ConfigAccountType::TreasuryShare(x) => config_data.treasury_share = x,
+            ConfigAccountType::Guardian(x) => config_data.guardian = x,
+            ConfigAccountType::IsPaused(x) => config_data.is_paused = x,
+        }
+    }
 }
 
 #[derive(BorshSerialize, Clone, BorshDeserialize)]
@@ -558,6 +1449,130 @@ impl VoteAccountGovernance {
         };
         Ok(())
     }
+
+    /// Mirrors the change made to the vote account into the config.
+    pub fn apply(self, config_data: &mut ValidatorConfig) {
+        match self {
+            VoteAccountGovernance::ValidatorID(x) => config_data.validator_id = x,
+            VoteAccountGovernance::Commission(x) => config_data.commission = x,
+        }
+    }
+}
+
+#[derive(BorshSerialize, Clone, BorshDeserialize)]
+pub enum UploaderGovernance {
+    AddUploader(Uploader),
+    RemoveUploader(Pubkey),
+}
+impl UploaderGovernance {
+    pub fn verify(&self) -> ProgramResult {
+        match self {
+            UploaderGovernance::AddUploader(x) => x.verify()?,
+            UploaderGovernance::RemoveUploader(_) => (),
+        };
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, Clone, BorshDeserialize)]
+/// Changes to the uris once they are frozen.
+pub enum UrisGovernance {
+    ReplaceUris {
+        rarity: u8,
+        uris: Vec<String>,
+    },
+    SetBaseUri(String),
+    CommitUrisRoot {
+        rarity: u8,
+        uris_root: Option<UrisRoot>,
+    },
+    /// Replaces the uris of an existing UrisPage account. The page count of a rarity can't change once frozen.
+    ReplaceUrisPage {
+        rarity: u8,
+        page: u16,
+        uris: Vec<String>,
+    },
+    /// Sets the share and name of each of the existing rarities. The number of rarities can't change once frozen.
+    SetRarities {
+        rarities: Vec<u16>,
+        rarity_names: Vec<String>,
+    },
+    /// Sets the reward weight of each rarity. NFTs already delegated keep the weighted backing they were delegated with
+    /// until they are undelegated, rerolled or imprinted.
+    SetRewardWeights(Vec<u16>),
+}
+impl UrisGovernance {
+    pub fn verify(&self) -> ProgramResult {
+        match self {
+            UrisGovernance::ReplaceUris { uris, .. } => {
+                if uris.is_empty() {
+                    Err(InglError::InvalidData.utilize("Replacement uris can't be empty"))?
+                }
+            }
+            UrisGovernance::SetBaseUri(x) => {
+                if x.len() > constants::MAX_BASE_URI_LENGTH {
+                    Err(InglError::InvalidData
+                        .utilize("Base uri must be less than MAX_BASE_URI_LENGTH characters"))?
+                }
+            }
+            UrisGovernance::CommitUrisRoot { uris_root, .. } => {
+                if let Some(UrisRoot { count: 0, .. }) = uris_root {
+                    Err(InglError::InvalidData
+                        .utilize("A committed rarity must have at least one uri"))?
+                }
+            }
+            UrisGovernance::ReplaceUrisPage { uris, .. } => {
+                if uris.is_empty() {
+                    Err(InglError::InvalidData.utilize("Replacement uris can't be empty"))?
+                }
+            }
+            UrisGovernance::SetRarities {
+                rarities,
+                rarity_names,
+            } => {
+                UrisAccount::get_cumulated_rarities(rarities)?;
+                if rarity_names.len() != rarities.len() {
+                    Err(InglError::InvalidData.utilize(
+                        "Rarity names vector length must be equal to rarities vector length",
+                    ))?
+                }
+            }
+            UrisGovernance::SetRewardWeights(reward_weights) => {
+                if reward_weights.iter().any(|x| {
+                    *x < constants::REWARD_WEIGHT_BASE || *x > constants::MAX_REWARD_WEIGHT
+                }) {
+                    Err(InglError::InvalidData
+                        .utilize("Reward weights must be between 1x and 10x the base weight"))?
+                }
+            }
+        };
+        Ok(())
+    }
+
+    pub fn get_space(&self) -> usize {
+        match self {
+            UrisGovernance::ReplaceUris { uris, .. } => {
+                1 + 1 + 4 + uris.iter().map(|x| 4 + x.len()).sum::<usize>()
+            }
+            UrisGovernance::SetBaseUri(x) => 1 + 4 + x.len(),
+            UrisGovernance::CommitUrisRoot { uris_root, .. } => {
+                1 + 1 + 1 + uris_root.map_or(0, |_| UrisRoot::get_space())
+            }
+            UrisGovernance::ReplaceUrisPage { uris, .. } => {
+                1 + 1 + 2 + 4 + uris.iter().map(|x| 4 + x.len()).sum::<usize>()
+            }
+            UrisGovernance::SetRarities {
+                rarities,
+                rarity_names,
+            } => {
+                1 + 4
+                    + 2 * rarities.len()
+                    + 4
+                    + rarity_names.iter().map(|x| 4 + x.len()).sum::<usize>()
+            }
+            UrisGovernance::SetRewardWeights(reward_weights) => 1 + 4 + 2 * reward_weights.len(),
+        }
+    }
 }
 
 #[derive(BorshSerialize, Clone, BorshDeserialize)]
@@ -568,13 +1583,134 @@ pub enum GovernanceType {
         code_link: String,
     },
     VoteAccountGovernance(VoteAccountGovernance),
+    UploaderGovernance(UploaderGovernance),
+    UrisGovernance(UrisGovernance),
+    /// Transfer from the treasury, capped to MAX_TREASURY_SPEND_SHARE of its balance at execution. A proposal holds at most one.
+    TreasurySpend {
+        recipient: Pubkey,
+        lamports: u64,
+        memo: String,
+    },
 }
 impl GovernanceType {
+    /// Returns whether executing the action takes accounts other than the proposal, config and general accounts.
+    pub fn needs_extra_accounts(&self) -> bool {
+        !matches!(self, GovernanceType::ConfigAccount(_))
+    }
+
+    /// Returns the threshold proposals of this type must meet to pass.
+    pub fn get_threshold(&self, thresholds: &GovernanceThresholds) -> ProposalThreshold {
+        match self {
+            GovernanceType::ConfigAccount(x) => match x {
+                ConfigAccountType::MaxPrimaryStake(_)
+                | ConfigAccountType::NftHolderShare(_)
+                | ConfigAccountType::InitialRedemptionFee(_)
+                | ConfigAccountType::RedemptionFeeDuration(_) => thresholds.economics,
+                ConfigAccountType::ValidatorName(_)
+                | ConfigAccountType::TwitterHandle(_)
+                | ConfigAccountType::DiscordInvite(_) => thresholds.profile,
+                ConfigAccountType::CreatorRoyalties(_)
+                | ConfigAccountType::RarityRerollFee(_)
+                | ConfigAccountType::RarityRerollCooldown(_)
+                | ConfigAccountType::MintFee(_)
+                | ConfigAccountType::ReferralFeeBps(_)
+                | ConfigAccountType::ProgrammableRuleSet(_)
+                | ConfigAccountType::LockBoostTiers(_)
+                | ConfigAccountType::ProposalDeposit(_)
+                | ConfigAccountType::TreasuryShare(_) => thresholds.economics,
+                ConfigAccountType::Website(_) | ConfigAccountType::DefaultUri(_) => {
+                    thresholds.profile
+                }
+                ConfigAccountType::GovernanceThresholds(_)
+                | ConfigAccountType::GovernanceExpirationTime(_)
+                | ConfigAccountType::QuorumBasis(_) => thresholds.thresholds,
+                ConfigAccountType::IsValidatorIdSwitchable(_) => thresholds.validator_id,
+                ConfigAccountType::Guardian(_) | ConfigAccountType::IsPaused(_) => {
+                    thresholds.program_upgrade
+                }
+            },
+            GovernanceType::ProgramUpgrade { .. } => thresholds.program_upgrade,
+            GovernanceType::VoteAccountGovernance(x) => match x {
+                VoteAccountGovernance::ValidatorID(_) => thresholds.validator_id,
+                VoteAccountGovernance::Commission(_) => thresholds.commission,
+            },
+            GovernanceType::UploaderGovernance(_) => thresholds.uploaders,
+            GovernanceType::UrisGovernance(_) => thresholds.uris,
+            GovernanceType::TreasurySpend { .. } => thresholds.treasury,
+        }
+    }
+
+    pub fn get_space(&self) -> usize {
+        1 + match self.clone() {
+            GovernanceType::ConfigAccount(tmp) => match tmp {
+                ConfigAccountType::MaxPrimaryStake(_) => 1 + 8,
+                ConfigAccountType::NftHolderShare(_) => 1 + 1,
+                ConfigAccountType::InitialRedemptionFee(_) => 1 + 1,
+                ConfigAccountType::RedemptionFeeDuration(_) => 1 + 4,
+                ConfigAccountType::ValidatorName(item) => 1 + 4 + item.len(),
+                ConfigAccountType::TwitterHandle(item) => 1 + 4 + item.len(),
+                ConfigAccountType::DiscordInvite(item) => 1 + 4 + item.len(),
+                ConfigAccountType::GovernanceThresholds(_) => 1 + GovernanceThresholds::get_space(),
+                ConfigAccountType::Website(item) => 1 + 4 + item.len(),
+                ConfigAccountType::DefaultUri(item) => 1 + 4 + item.len(),
+                ConfigAccountType::CreatorRoyalties(_) => 1 + 2,
+                ConfigAccountType::GovernanceExpirationTime(_) => 1 + 4,
+                ConfigAccountType::IsValidatorIdSwitchable(_) => 1 + 1,
+                ConfigAccountType::RarityRerollFee(item) => 1 + 1 + item.map_or(0, |_| 8),
+                ConfigAccountType::RarityRerollCooldown(_) => 1 + 4,
+                ConfigAccountType::MintFee(_) => 1 + 8,
+                ConfigAccountType::ReferralFeeBps(_) => 1 + 2,
+                ConfigAccountType::ProgrammableRuleSet(item) => 1 + 1 + item.map_or(0, |_| 32),
+                ConfigAccountType::LockBoostTiers(item) => {
+                    1 + 4 + item.len() * LockBoostTier::get_space()
+                }
+                ConfigAccountType::QuorumBasis(_) => 1 + 1,
+                ConfigAccountType::ProposalDeposit(_) => 1 + 8,
+                ConfigAccountType::TreasuryShare(_) => 1 + 1,
+                ConfigAccountType::Guardian(item) => 1 + 1 + item.map_or(0, |_| 32),
+                ConfigAccountType::IsPaused(_) => 1 + 1,
+            },
+            GovernanceType::ProgramUpgrade {
+                buffer_account: _,
+                code_link,
+            } => 32 + 4 + code_link.len(),
+
+            GovernanceType::VoteAccountGovernance(tmp) => match tmp {
+                VoteAccountGovernance::ValidatorID(_) => 1 + 32,
+                VoteAccountGovernance::Commission(_) => 1 + 1,
+            },
+
+            GovernanceType::UploaderGovernance(tmp) => match tmp {
+                UploaderGovernance::AddUploader(item) => 1 + item.get_space(),
+                UploaderGovernance::RemoveUploader(_) => 1 + 32,
+            },
+
+            GovernanceType::UrisGovernance(tmp) => tmp.get_space(),
+            GovernanceType::TreasurySpend {
+                recipient: _,
+                lamports: _,
+                memo,
+            } => 32 + 8 + 4 + memo.len(),
+        }
+    }
+
     pub fn verify(&self) -> ProgramResult {
         match self {
             GovernanceType::ConfigAccount(x) => x.verify(),
             GovernanceType::ProgramUpgrade { .. } => Ok(()),
             GovernanceType::VoteAccountGovernance(x) => x.verify(),
+            GovernanceType::UploaderGovernance(x) => x.verify(),
+            GovernanceType::UrisGovernance(x) => x.verify(),
+            GovernanceType::TreasurySpend { lamports, memo, .. } => {
+                if *lamports == 0 {
+                    Err(InglError::InvalidData.utilize("Treasury spend must be greater than 0"))?
+                }
+                if memo.len() > constants::MAX_TREASURY_MEMO_LENGTH {
+                    Err(InglError::InvalidData
+                        .utilize("Memo must be less than MAX_TREASURY_MEMO_LENGTH characters"))?
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -591,47 +1727,112 @@ pub struct GovernanceData {
     pub title: String,
     pub description: String,
     pub votes: BTreeMap<u32, bool>,
-    pub governance_type: GovernanceType,
+    /// Actions executed in order once the proposal passes.
+    pub actions: Vec<GovernanceType>,
+    /// Number of actions already executed, execution resumes from there.
+    pub executed_actions: u8,
+    /// Votes expected when the proposal was created, per the quorum basis of the config.
+    pub expected_votes: u32,
+    /// Threshold of the governance type when the proposal was created.
+    pub threshold: ProposalThreshold,
+    pub proposer: Pubkey,
+    /// Deposit escrowed in the proposal account until the proposal is finalized or cancelled.
+    pub deposit: u64,
+    pub is_cancelled: bool,
+    /// Set when the guardian vetoes the proposal, which can then no longer be executed.
+    pub is_vetoed: bool,
+    /// Set when the config resulting from the actions is invalid. The remaining actions are then never executed.
+    pub is_execution_failed: bool,
 }
 impl GovernanceData {
     pub fn get_space(&self) -> usize {
-        let mut space = 4 + 4 + 1 + 5 + 2 + 1;
+        let mut space =
+            4 + 4 + 1 + 5 + 2 + 1 + 1 + 4 + ProposalThreshold::get_space() + 32 + 8 + 1 + 1 + 1;
         space += 4 + self.votes.len() * 5;
         space += 4 + self.title.len();
         space += 4 + self.description.len();
 
-        space += 1 + match self.governance_type.clone() {
-            GovernanceType::ConfigAccount(tmp) => match tmp {
-                ConfigAccountType::MaxPrimaryStake(_) => 1 + 8,
-                ConfigAccountType::NftHolderShare(_) => 1 + 1,
-                ConfigAccountType::InitialRedemptionFee(_) => 1 + 1,
-                ConfigAccountType::RedemptionFeeDuration(_) => 1 + 4,
-                ConfigAccountType::ValidatorName(item) => 1 + 4 + item.len(),
-                ConfigAccountType::TwitterHandle(item) => 1 + 4 + item.len(),
-                ConfigAccountType::DiscordInvite(item) => 1 + 4 + item.len(),
-            },
-            GovernanceType::ProgramUpgrade {
-                buffer_account: _,
-                code_link,
-            } => 32 + 4 + code_link.len(),
-
-            GovernanceType::VoteAccountGovernance(tmp) => match tmp {
-                VoteAccountGovernance::ValidatorID(_) => 1 + 32,
-                VoteAccountGovernance::Commission(_) => 1 + 1,
-            },
-        };
+        space += 4 + self
+            .actions
+            .iter()
+            .map(|action| action.get_space())
+            .sum::<usize>();
 
         space
     }
 
+    /// Returns whether more than the quorum percentage of the expected votes were cast.
+    pub fn has_reached_quorum(&self) -> bool {
+        self.votes.len() as u64 * 100 > self.threshold.quorum as u64 * self.expected_votes as u64
+    }
+
+    /// Returns whether at least the approval percentage of the cast votes approve the proposal.
+    pub fn has_reached_approval(&self) -> bool {
+        let approvals = self.votes.values().filter(|x| **x).count() as u64;
+        approvals * 100 >= self.threshold.approval as u64 * self.votes.len() as u64
+    }
+
     pub fn verify(&self) -> ProgramResult {
         if self.title.len() > 100 {
-            Err(InglError::InvalidData.utilize("Title can't be more than 150 characters"))?
+            Err(InglError::InvalidData.utilize("Title can't be more than 100 characters"))?
         }
         if self.description.len() > 350 {
-            Err(InglError::InvalidData.utilize("Description can't be more than 1000 characters"))?
+            Err(InglError::InvalidData.utilize("Description can't be more than 350 characters"))?
+        }
+        if self.actions.is_empty() {
+            Err(InglError::InvalidData.utilize("A proposal must have at least one action"))?
+        }
+        if self.actions.len() > constants::MAX_PROPOSAL_ACTIONS {
+            Err(InglError::InvalidData
+                .utilize("A proposal can't have more than MAX_PROPOSAL_ACTIONS actions"))?
+        }
+        // Each spend is capped against what the previous ones left, so a proposal holds a single one for the cap to bound it.
+        if self
+            .actions
+            .iter()
+            .filter(|action| matches!(action, GovernanceType::TreasurySpend { .. }))
+            .count()
+            > 1
+        {
+            Err(InglError::InvalidData
+                .utilize("A proposal can't spend from the treasury more than once"))?
         }
-        self.governance_type.verify()
+        for action in self.actions.iter() {
+            action.verify()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Validate)]
+#[validation_phrase(crate::state::constants::VOTE_DELEGATION_VAL_PHRASE)]
+/// Governance voting power of an NFT delegated by its holder. It lapses once the NFT changes hands.
+pub struct VoteDelegation {
+    pub validation_phrase: u32,
+    pub mint: Pubkey,
+    /// Holder of the NFT when the vote was delegated.
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+}
+impl VoteDelegation {
+    pub fn get_space() -> usize {
+        // 4 + 32 + 32 + 32
+        100
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+/// Governance events, logged as program data for indexers.
+pub enum GovernanceEvent {
+    VoteCast { proposal: u32, nft: u32, vote: bool },
+    VoteChanged { proposal: u32, nft: u32, vote: bool },
+    VoteWithdrawn { proposal: u32, nft: u32 },
+    ProposalVetoed { proposal: u32 },
+}
+impl GovernanceEvent {
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
     }
 }
 
@@ -0,0 +1,396 @@
+//! Instructions and account layouts of the Token-2022 extensions gems are minted with.
+//! spl-token-2022 0.6 predates the transfer hook, metadata pointer and token metadata extensions, so they are encoded here.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use solana_program::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token::state::Account;
+
+use crate::{
+    error::InglError,
+    state::constants::{
+        AUTHORIZED_WITHDRAWER_KEY, GENERAL_ACCOUNT_SEED, NFT_ACCOUNT_CONST, REWARD_CLAIM_KEY,
+    },
+    utils::{OptionExt, ResultExt},
+};
+
+/// sha256("spl-transfer-hook-interface:execute")[..8], prefixing the Execute instruction Token-2022 sends the transfer hook.
+pub const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+/// sha256("spl_token_metadata_interface:initialize_account")[..8]
+const INITIALIZE_TOKEN_METADATA_DISCRIMINATOR: [u8; 8] = [210, 225, 30, 162, 88, 184, 77, 141];
+/// sha256("spl_token_metadata_interface:updating_field")[..8]
+const UPDATE_TOKEN_METADATA_FIELD_DISCRIMINATOR: [u8; 8] = [221, 233, 49, 45, 181, 202, 220, 200];
+
+const TRANSFER_HOOK_EXTENSION_INSTRUCTION: u8 = 36;
+const METADATA_POINTER_EXTENSION_INSTRUCTION: u8 = 39;
+/// Index of the Initialize instruction of both the transfer hook and the metadata pointer extensions.
+const INITIALIZE_EXTENSION_INSTRUCTION: u8 = 0;
+
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+const TRANSFER_HOOK_ACCOUNT_EXTENSION: u16 = 15;
+const TOKEN_METADATA_EXTENSION: u16 = 19;
+
+/// Space of a gem mint with its transfer hook and metadata pointer, before its token metadata is initialized.
+// Account::LEN + 1 + (2 + 2 + 32 + 32) + (2 + 2 + 32 + 32)
+pub const GEM_MINT_SPACE: usize = 302;
+/// Size of an entry of the extra account metas account.
+const EXTRA_ACCOUNT_META_LEN: usize = 35;
+/// Seed of the extra account metas account of a mint, a pda of the transfer hook program.
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// Name, symbol and uri of a Token-2022 gem, stored in its mint by the token metadata extension.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct TokenMetadataFields {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+impl TokenMetadataFields {
+    /// Space the token metadata extension takes in the mint, without additional metadata.
+    pub fn get_space(&self) -> usize {
+        // 2 + 2 + 32 + 32 + (4 + name) + (4 + symbol) + (4 + uri) + 4
+        84 + self.name.len() + self.symbol.len() + self.uri.len()
+    }
+
+    /// Space the token metadata extension takes in the mint with the longest name, symbol and uri metaplex allows.
+    /// Gem mints are funded for it at mint, so that updating their metadata never needs a rent top up.
+    pub fn get_max_space() -> usize {
+        84 + MAX_NAME_LENGTH + MAX_SYMBOL_LENGTH + MAX_URI_LENGTH
+    }
+
+    /// Reads the token metadata stored in a gem mint.
+    pub fn read(mint_account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = mint_account_info.data.borrow();
+        let metadata = get_extension(&data, ACCOUNT_TYPE_MINT, TOKEN_METADATA_EXTENSION)?
+            .error_log("Error: the mint has no token metadata")?;
+        // Skips the update authority and the mint.
+        let mut fields = metadata
+            .get(64..)
+            .error_log("Error: token metadata is too short")?;
+        Ok(Self::deserialize(&mut fields).error_log("Error @ token metadata deserialization")?)
+    }
+}
+
+/// A field of the token metadata, as encoded by the token metadata interface.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum TokenMetadataField {
+    Name,
+    Symbol,
+    Uri,
+    Key(String),
+}
+
+/// Initializes the transfer hook of a mint, called on every transfer of its tokens. Precedes the mint's initialization.
+pub fn initialize_transfer_hook(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    hook_program_id: &Pubkey,
+) -> Instruction {
+    initialize_extension(
+        TRANSFER_HOOK_EXTENSION_INSTRUCTION,
+        mint,
+        authority,
+        hook_program_id,
+    )
+}
+
+/// Points the metadata of a mint to the given account. Precedes the mint's initialization.
+pub fn initialize_metadata_pointer(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    metadata: &Pubkey,
+) -> Instruction {
+    initialize_extension(
+        METADATA_POINTER_EXTENSION_INSTRUCTION,
+        mint,
+        authority,
+        metadata,
+    )
+}
+
+fn initialize_extension(
+    extension_instruction: u8,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    address: &Pubkey,
+) -> Instruction {
+    let mut data = vec![extension_instruction, INITIALIZE_EXTENSION_INSTRUCTION];
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(address.as_ref());
+    Instruction {
+        program_id: spl_token_2022::id(),
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+/// Stores the token metadata in a mint whose metadata pointer points to itself, signed by its mint authority.
+/// The mint must hold the lamports of its grown size beforehand.
+pub fn initialize_token_metadata(
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    mint_authority: &Pubkey,
+    fields: &TokenMetadataFields,
+) -> Result<Instruction, ProgramError> {
+    let mut data = INITIALIZE_TOKEN_METADATA_DISCRIMINATOR.to_vec();
+    fields.serialize(&mut data)?;
+    Ok(Instruction {
+        program_id: spl_token_2022::id(),
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+        ],
+        data,
+    })
+}
+
+/// Sets a field of the token metadata stored in a mint, signed by its update authority.
+/// The mint must hold the lamports of its grown size beforehand.
+pub fn update_token_metadata_field(
+    mint: &Pubkey,
+    update_authority: &Pubkey,
+    field: TokenMetadataField,
+    value: String,
+) -> Result<Instruction, ProgramError> {
+    let mut data = UPDATE_TOKEN_METADATA_FIELD_DISCRIMINATOR.to_vec();
+    field.serialize(&mut data)?;
+    value.serialize(&mut data)?;
+    Ok(Instruction {
+        program_id: spl_token_2022::id(),
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+        data,
+    })
+}
+
+/// Returns the amount of an Execute instruction sent by Token-2022 to the transfer hook, None for any other instruction.
+pub fn unpack_execute_amount(data: &[u8]) -> Option<u64> {
+    if data.len() != 16 || data[..8] != EXECUTE_DISCRIMINATOR {
+        return None;
+    }
+    Some(u64::from_le_bytes(*arrayref::array_ref![data, 8, 8]))
+}
+
+/// Data of the extra account metas account of a gem mint, listing the accounts Token-2022 adds to the Execute instruction:
+/// the gem's data account, the general account, the authorized withdrawer,
+/// the reward claim of the source's owner and the system program.
+/// The accounts of the transfer itself are indexed from 0: the source, the mint, the destination, the owner and the extra account metas.
+pub fn get_extra_account_metas_data() -> Vec<u8> {
+    // Seeds of a pda of the hook program, as [literal, len, bytes], [account key, index] or [account data, index, offset, len].
+    let literal = |seed: &[u8]| [&[1, seed.len() as u8], seed].concat();
+    let mint_key = vec![3, 1];
+    let source_owner = vec![4, 0, 32, 32];
+    let metas: [(u8, Vec<u8>, bool); 5] = [
+        (
+            1,
+            [literal(NFT_ACCOUNT_CONST), mint_key.clone()].concat(),
+            true,
+        ),
+        (1, literal(GENERAL_ACCOUNT_SEED), false),
+        (1, literal(AUTHORIZED_WITHDRAWER_KEY), true),
+        (
+            1,
+            [literal(REWARD_CLAIM_KEY), mint_key, source_owner].concat(),
+            true,
+        ),
+        (0, system_program::id().to_bytes().to_vec(), false),
+    ];
+    let mut data = EXECUTE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&((4 + metas.len() * EXTRA_ACCOUNT_META_LEN) as u32).to_le_bytes());
+    data.extend_from_slice(&(metas.len() as u32).to_le_bytes());
+    for (discriminator, address_config, is_writable) in metas {
+        let mut config = [0; 32];
+        config[..address_config.len()].copy_from_slice(&address_config);
+        data.push(discriminator);
+        data.extend_from_slice(&config);
+        data.push(false as u8);
+        data.push(is_writable as u8);
+    }
+    data
+}
+
+/// Unpacks a token account of either token program, ignoring the extensions of Token-2022 accounts.
+pub fn unpack_token_account(token_account_info: &AccountInfo) -> Result<Account, ProgramError> {
+    let data = token_account_info.data.borrow();
+    if *token_account_info.owner != spl_token_2022::id() {
+        return Account::unpack(&data);
+    }
+    get_extension(&data, ACCOUNT_TYPE_ACCOUNT, TRANSFER_HOOK_ACCOUNT_EXTENSION)?;
+    let account = Account::unpack_unchecked(&data[..Account::LEN])?;
+    if account.state == spl_token::state::AccountState::Uninitialized {
+        Err(ProgramError::UninitializedAccount)?
+    }
+    Ok(account)
+}
+
+/// Whether Token-2022 is transferring out of the token account, which it flags while calling the transfer hook.
+pub fn is_transferring(token_account_info: &AccountInfo) -> Result<bool, ProgramError> {
+    let data = token_account_info.data.borrow();
+    Ok(
+        get_extension(&data, ACCOUNT_TYPE_ACCOUNT, TRANSFER_HOOK_ACCOUNT_EXTENSION)?
+            .map_or(false, |transferring| transferring.first() == Some(&1)),
+    )
+}
+
+/// Returns the value of an extension of a Token-2022 mint or token account, None if the account doesn't have it.
+fn get_extension(
+    data: &[u8],
+    account_type: u8,
+    extension: u16,
+) -> Result<Option<&[u8]>, ProgramError> {
+    if data.len() < Account::LEN {
+        Err(InglError::InvalidData.utilize("Token-2022 account is too short"))?
+    }
+    if data.len() == Account::LEN {
+        return Ok(None);
+    }
+    if data[Account::LEN] != account_type {
+        Err(InglError::InvalidData.utilize("Unexpected Token-2022 account type"))?
+    }
+    let mut offset = Account::LEN + 1;
+    while offset + 4 <= data.len() {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        let value = data
+            .get(offset + 4..offset + 4 + length)
+            .error_log("Error: Token-2022 extension overruns the account")?;
+        if extension_type == extension {
+            return Ok(Some(value));
+        }
+        offset += 4 + length;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::InstructionEnum;
+    use spl_token::state::AccountState;
+
+    /// Data of a Token-2022 token account holding the gem, with its transfer hook account extension.
+    fn token_2022_account_data(mint: Pubkey, owner: Pubkey, transferring: bool) -> Vec<u8> {
+        let mut data = vec![0; Account::LEN];
+        Account::pack(
+            Account {
+                mint,
+                owner,
+                amount: 1,
+                state: AccountState::Initialized,
+                ..Account::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        data.push(ACCOUNT_TYPE_ACCOUNT);
+        data.extend_from_slice(&TRANSFER_HOOK_ACCOUNT_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.push(transferring as u8);
+        data
+    }
+
+    #[test]
+    fn only_execute_instructions_are_routed_to_the_transfer_hook() {
+        let mut data = EXECUTE_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&1u64.to_le_bytes());
+        assert_eq!(unpack_execute_amount(&data), Some(1));
+
+        let claim = InstructionEnum::ClaimSettledRewards { log_level: 0 }
+            .try_to_vec()
+            .unwrap();
+        assert_eq!(unpack_execute_amount(&claim), None);
+        assert_eq!(unpack_execute_amount(&data[..12]), None);
+    }
+
+    #[test]
+    fn token_2022_accounts_unpack_and_flag_transfers() {
+        let (key, mint, owner, token_program) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            spl_token_2022::id(),
+        );
+        let (mut lamports, mut other_lamports) = (0, 0);
+        let mut data = token_2022_account_data(mint, owner, true);
+        let mut other_data = token_2022_account_data(mint, owner, false);
+        let transferring = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &token_program,
+            false,
+            0,
+        );
+        let idle = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut other_lamports,
+            &mut other_data,
+            &token_program,
+            false,
+            0,
+        );
+
+        let account = unpack_token_account(&transferring).unwrap();
+        assert_eq!(
+            (account.mint, account.owner, account.amount),
+            (mint, owner, 1)
+        );
+        assert!(is_transferring(&transferring).unwrap());
+        assert!(!is_transferring(&idle).unwrap());
+    }
+
+    #[test]
+    fn token_metadata_is_read_from_the_mint() {
+        let fields = TokenMetadataFields {
+            name: "Ingl #1".to_string(),
+            symbol: "Ingl#1".to_string(),
+            uri: "https://ingl.io/1.json".to_string(),
+        };
+        let mut value = [
+            Pubkey::new_unique().to_bytes(),
+            Pubkey::new_unique().to_bytes(),
+        ]
+        .concat();
+        fields.serialize(&mut value).unwrap();
+        // No additional metadata.
+        value.extend_from_slice(&0u32.to_le_bytes());
+        let mut data = vec![0; Account::LEN];
+        data.push(ACCOUNT_TYPE_MINT);
+        // An extension preceding the token metadata is skipped.
+        data.extend_from_slice(&[18, 0, 64, 0]);
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(&TOKEN_METADATA_EXTENSION.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(&value);
+        assert_eq!(value.len() + 4, fields.get_space());
+
+        let (key, token_program, mut lamports) = (Pubkey::new_unique(), spl_token_2022::id(), 0);
+        let mint = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &token_program,
+            false,
+            0,
+        );
+        assert_eq!(TokenMetadataFields::read(&mint).unwrap(), fields);
+    }
+}
@@ -1,19 +1,41 @@
+use arrayref::array_ref;
+use mpl_token_metadata::{
+    instruction::{
+        builders::{DelegateBuilder, LockBuilder, UnlockBuilder},
+        DelegateArgs, InstructionBuilder, LockArgs, UnlockArgs,
+    },
+    pda::{find_master_edition_account, find_metadata_account, find_token_record_account},
+};
 use solana_program::{
-    account_info::AccountInfo,
+    account_info::{next_account_info, AccountInfo},
+    clock::Slot,
     entrypoint::ProgramResult,
+    hash::{hashv, Hash},
+    instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::{self, clock::Clock, rent::Rent, Sysvar},
+    system_instruction, system_program,
+    sysvar::{
+        self,
+        clock::Clock,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+        Sysvar,
+    },
 };
-use spl_associated_token_account::get_associated_token_address;
-use spl_token::state::Account;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use std::slice::Iter;
 
 use crate::{
     colored_log,
     error::InglError,
-    state::{constants::NFT_ACCOUNT_CONST, LogColors::*, NftData},
+    state::{
+        constants::{INGL_MINT_AUTHORITY_KEY, NFT_ACCOUNT_CONST, VOTE_DELEGATION_KEY},
+        LogColors::*,
+        NftData, VoteDelegation,
+    },
+    token_2022::unpack_token_account,
 };
 pub trait PubkeyHelpers {
     fn assert_match(&self, a: &Pubkey) -> ProgramResult;
@@ -131,6 +153,138 @@ pub fn get_rent_data_from_account(sysvar_rent_info: &AccountInfo) -> Result<Rent
         .error_log("Error: There are some issues getting rent details")
 }
 
+///Returns the lamports an account lacks to be rent exempt with the given space.
+pub fn get_rent_shortfall(account_info: &AccountInfo, space: usize, rent_data: &Rent) -> u64 {
+    rent_data
+        .minimum_balance(space)
+        .saturating_sub(account_info.lamports())
+}
+
+///Creates the pda of the given seeds with space bytes owned by the program, and returns the lamports the payer funded it with.
+/// The pda is funded, allocated and assigned separately, as lamports sent to its address beforehand would fail a create_account.
+/// payer_seeds sign for the payer when it is a pda itself.
+pub fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    payer_seeds: Option<&[&[u8]]>,
+    pda: &AccountInfo<'a>,
+    pda_seeds: &[&[u8]],
+    space: usize,
+    rent_data: &Rent,
+) -> Result<u64, ProgramError> {
+    let lamports = get_rent_shortfall(pda, space, rent_data);
+    let payer_signers: Vec<&[&[u8]]> = payer_seeds.into_iter().collect();
+    if lamports > 0 {
+        invoke_signed(
+            &system_instruction::transfer(payer.key, pda.key, lamports),
+            &[payer.clone(), pda.clone()],
+            &payer_signers,
+        )
+        .error_log("Error @ pda funding")?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(pda.key, space as u64),
+        std::slice::from_ref(pda),
+        &[pda_seeds],
+    )
+    .error_log("Error @ pda allocation")?;
+    invoke_signed(
+        &system_instruction::assign(pda.key, program_id),
+        std::slice::from_ref(pda),
+        &[pda_seeds],
+    )
+    .error_log("Error @ pda assignment")?;
+    Ok(lamports)
+}
+
+/// Get the hash of a specific slot from the SlotHashes sysvar account.
+/// Returns None if the slot is not (or no longer) part of the sysvar.
+pub fn get_slot_hash(
+    slot_hashes_info: &AccountInfo,
+    slot: Slot,
+) -> Result<Option<Hash>, ProgramError> {
+    slot_hashes_info
+        .assert_key_match(&sysvar::slot_hashes::id())
+        .error_log("Error: Error @ slot_hashes_info assertion.")?;
+    slot_hashes_info
+        .assert_owner(&sysvar::id())
+        .error_log("Error: Error @ slot_hashes_info ownership assertion.")?;
+
+    // The sysvar is a bincode Vec<(Slot, Hash)>, sorted from the newest slot to the oldest.
+    let data = slot_hashes_info.data.borrow();
+    if data.len() < 8 {
+        Err(ProgramError::InvalidAccountData).error_log("Error: slot_hashes data too short")?
+    }
+    let entries = (u64::from_le_bytes(*array_ref![data, 0, 8]) as usize).min((data.len() - 8) / 40);
+    let (mut low, mut high) = (0, entries);
+    while low < high {
+        let mid = (low + high) / 2;
+        let offset = 8 + mid * 40;
+        let entry_slot = u64::from_le_bytes(*array_ref![data, offset, 8]);
+        if entry_slot == slot {
+            return Ok(Some(Hash::new_from_array(*array_ref![
+                data,
+                offset + 8,
+                32
+            ])));
+        } else if entry_slot > slot {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the newest entry of the SlotHashes sysvar.
+pub fn get_latest_slot_hash(slot_hashes_info: &AccountInfo) -> Result<(Slot, Hash), ProgramError> {
+    slot_hashes_info
+        .assert_key_match(&sysvar::slot_hashes::id())
+        .error_log("Error: Error @ slot_hashes_info assertion.")?;
+    slot_hashes_info
+        .assert_owner(&sysvar::id())
+        .error_log("Error: Error @ slot_hashes_info ownership assertion.")?;
+
+    let data = slot_hashes_info.data.borrow();
+    if data.len() < 48 || u64::from_le_bytes(*array_ref![data, 0, 8]) == 0 {
+        Err(ProgramError::InvalidAccountData).error_log("Error: slot_hashes has no entries")?
+    }
+    Ok((
+        u64::from_le_bytes(*array_ref![data, 8, 8]),
+        Hash::new_from_array(*array_ref![data, 16, 32]),
+    ))
+}
+
+/// Asserts that the executing instruction is the last top-level instruction of its transaction,
+/// so that no later instruction can inspect its outcome and revert it.
+pub fn assert_last_top_level_instruction(sysvar_instructions_info: &AccountInfo) -> ProgramResult {
+    sysvar_instructions_info
+        .assert_key_match(&sysvar::instructions::id())
+        .error_log("Error: Error @ sysvar_instructions_info assertion.")?;
+    if get_stack_height() > TRANSACTION_LEVEL_STACK_HEIGHT {
+        Err(InglError::InvalidData.utilize("The instruction can't be invoked by another program"))?
+    }
+    let current_index = load_current_index_checked(sysvar_instructions_info)
+        .error_log("Error: failed to load the current instruction index")?;
+    if load_instruction_at_checked(current_index as usize + 1, sysvar_instructions_info).is_ok() {
+        Err(InglError::InvalidData.utilize("The instruction must be the last of its transaction"))?
+    }
+    Ok(())
+}
+
+/// Verifies that a leaf is part of the merkle tree with the given root.
+/// Pairs of nodes are hashed in sorted order, so the proof doesn't need to carry positions.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |computed, node| {
+        if computed <= *node {
+            hashv(&[&computed, node]).to_bytes()
+        } else {
+            hashv(&[node, &computed]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
 /// LEVEL 5: These logs will always run, regardless of state.rs' log level. .
 /// LEVEL 4: These logs are used to log entry and exits of functions.
 /// LEVEL 3: .
@@ -239,12 +393,87 @@ impl<T> OptionExt<T> for Option<T> {
     }
 }
 
+/// Returns the token program owning the gem's mint, spl_token for legacy and programmable gems or Token-2022.
+pub fn get_token_program_id(mint_account_info: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if *mint_account_info.owner != spl_token::id() && !is_token_2022_gem(mint_account_info) {
+        Err(InglError::AddressMismatch.utilize("mint_account_info is not owned by a token program"))?
+    }
+    Ok(*mint_account_info.owner)
+}
+
+/// Whether the gem was minted with Token-2022, keeping its metadata in its mint instead of a metaplex account.
+pub fn is_token_2022_gem(mint_account_info: &AccountInfo) -> bool {
+    *mint_account_info.owner == spl_token_2022::id()
+}
+
 pub fn verify_nft_ownership(
     payer_account_info: &AccountInfo,
     mint_account_info: &AccountInfo,
     nft_account_data_info: &AccountInfo,
     associated_token_account_info: &AccountInfo,
     program_id: &Pubkey,
+) -> ProgramResult {
+    payer_account_info
+        .assert_signer()
+        .error_log("payer_account_info is not a signer")?;
+    verify_nft_holder(
+        payer_account_info.key,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )
+}
+
+/// Verifies that the payer may vote with the NFT, either as its holder or as the vote delegate named by its holder.
+/// When the payer isn't the holder, the NFT's vote delegation account is the next account.
+pub fn verify_voting_authority(
+    payer_account_info: &AccountInfo,
+    mint_account_info: &AccountInfo,
+    nft_account_data_info: &AccountInfo,
+    associated_token_account_info: &AccountInfo,
+    account_info_iter: &mut Iter<AccountInfo>,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    payer_account_info
+        .assert_signer()
+        .error_log("payer_account_info is not a signer")?;
+    associated_token_account_info
+        .assert_owner(&get_token_program_id(mint_account_info)?)
+        .error_log("associated_token_account_info is not owned by the gem's token program")?;
+    let holder = unpack_token_account(associated_token_account_info)
+        .error_log("failed to unpack associated_token_account_info")?
+        .owner;
+    if holder != *payer_account_info.key {
+        let vote_delegation_account_info = next_account_info(account_info_iter)?;
+        vote_delegation_account_info
+            .assert_seed(
+                program_id,
+                &[VOTE_DELEGATION_KEY, mint_account_info.key.as_ref()],
+            )
+            .error_log("failed to assert pda input for vote_delegation_account_info")?;
+        let vote_delegation = VoteDelegation::parse(vote_delegation_account_info, program_id)?;
+        if vote_delegation.owner != holder || vote_delegation.delegate != *payer_account_info.key {
+            Err(InglError::AddressMismatch
+                .utilize("payer is neither the holder of the NFT nor its vote delegate"))?
+        }
+    }
+    verify_nft_holder(
+        &holder,
+        mint_account_info,
+        nft_account_data_info,
+        associated_token_account_info,
+        program_id,
+    )
+}
+
+/// Verifies that the holder's associated token account holds the NFT.
+pub fn verify_nft_holder(
+    holder: &Pubkey,
+    mint_account_info: &AccountInfo,
+    nft_account_data_info: &AccountInfo,
+    associated_token_account_info: &AccountInfo,
+    program_id: &Pubkey,
 ) -> ProgramResult {
     let (_nft_account_pubkey, _nft_account_bump) = nft_account_data_info
         .assert_seed(
@@ -257,26 +486,21 @@ pub fn verify_nft_ownership(
     nft_account_data_info
         .assert_owner(program_id)
         .error_log("nft_account_data_info is not owned by ingl's program")?;
-    mint_account_info
-        .assert_owner(&spl_token::id())
-        .error_log("mint_account_info is not owned by spl_token")?;
+    let token_program_id = get_token_program_id(mint_account_info)?;
     associated_token_account_info
-        .assert_owner(&spl_token::id())
-        .error_log("associated_token_account_info is not owned by spl_program")?;
-    payer_account_info
-        .assert_signer()
-        .error_log("payer_account_info is not a signer")?;
+        .assert_owner(&token_program_id)
+        .error_log("associated_token_account_info is not owned by the gem's token program")?;
     let _nft_data = NftData::parse(nft_account_data_info, program_id)?;
 
     associated_token_account_info
-        .assert_key_match(&get_associated_token_address(
-            payer_account_info.key,
+        .assert_key_match(&get_associated_token_address_with_program_id(
+            holder,
             mint_account_info.key,
+            &token_program_id,
         ))
         .error_log("sent associated_token_address is dissimilar to the expected one")?;
-    let associated_token_address_data =
-        Account::unpack(&associated_token_account_info.data.borrow())
-            .error_log("failed to unpack associated_token_account_info")?;
+    let associated_token_address_data = unpack_token_account(associated_token_account_info)
+        .error_log("failed to unpack associated_token_account_info")?;
     if associated_token_address_data.amount != 1 {
         Err(InglError::NFTBalanceCheckError
             .utilize("associated_token_address_data.amount is not 1"))?
@@ -284,3 +508,229 @@ pub fn verify_nft_ownership(
 
     Ok(())
 }
+
+/// Accounts used by the token-metadata instructions of programmable NFTs, on top of the accounts of the NFT itself.
+/// They are only sent for programmable NFTs, and are omitted from the accounts of legacy NFTs.
+pub struct ProgrammableAccounts<'a, 'b> {
+    pub system_program: &'a AccountInfo<'b>,
+    pub spl_token_program: &'a AccountInfo<'b>,
+    pub sysvar_instructions: &'a AccountInfo<'b>,
+    pub token_record: &'a AccountInfo<'b>,
+    pub token_metadata_program: &'a AccountInfo<'b>,
+    pub spl_ata_program: &'a AccountInfo<'b>,
+    pub authorization_rules_program: &'a AccountInfo<'b>,
+    pub authorization_rules: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> ProgrammableAccounts<'a, 'b> {
+    ///Reads the accounts if the NFT is programmable, and none otherwise.
+    pub fn collect(
+        iter: &mut Iter<'a, AccountInfo<'b>>,
+        is_programmable: bool,
+    ) -> Result<Option<Self>, ProgramError> {
+        if !is_programmable {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            system_program: next_account_info(iter)?,
+            spl_token_program: next_account_info(iter)?,
+            sysvar_instructions: next_account_info(iter)?,
+            token_record: next_account_info(iter)?,
+            token_metadata_program: next_account_info(iter)?,
+            spl_ata_program: next_account_info(iter)?,
+            authorization_rules_program: next_account_info(iter)?,
+            authorization_rules: next_account_info(iter)?,
+        }))
+    }
+
+    ///Asserts the accounts before they are used for the programmable NFT of the given mint and token account.
+    pub fn verify(&self, mint: &Pubkey, token: &Pubkey) -> ProgramResult {
+        self.system_program
+            .assert_key_match(&system_program::id())
+            .error_log("Error: @system_program_account_info assertion")?;
+        self.spl_token_program
+            .assert_key_match(&spl_token::id())
+            .error_log("Error: @spl_token_program_account_info assertion")?;
+        self.sysvar_instructions
+            .assert_key_match(&sysvar::instructions::id())
+            .error_log("Error: @sysvar_instructions_account_info assertion")?;
+        self.token_metadata_program
+            .assert_key_match(&mpl_token_metadata::id())
+            .error_log("Error: @token_metadata_program_account_info assertion")?;
+        self.spl_ata_program
+            .assert_key_match(&spl_associated_token_account::id())
+            .error_log("Error: @spl_ata_program_account_info assertion")?;
+        self.token_record
+            .assert_key_match(&find_token_record_account(mint, token).0)
+            .error_log("Error: @token_record_account_info assertion")?;
+        Ok(())
+    }
+
+    /// The rule set governing the NFT, None if the token metadata program id was sent in its place.
+    pub fn rule_set(&self) -> Option<Pubkey> {
+        if *self.authorization_rules.key == mpl_token_metadata::id() {
+            None
+        } else {
+            Some(*self.authorization_rules.key)
+        }
+    }
+
+    pub fn infos(&self) -> Vec<AccountInfo<'b>> {
+        vec![
+            self.system_program.clone(),
+            self.spl_token_program.clone(),
+            self.sysvar_instructions.clone(),
+            self.token_record.clone(),
+            self.token_metadata_program.clone(),
+            self.spl_ata_program.clone(),
+            self.authorization_rules_program.clone(),
+            self.authorization_rules.clone(),
+        ]
+    }
+}
+
+/// Accounts of a gem acted on by the mint authority, its freeze authority, update authority and utility delegate.
+pub struct GemAccounts<'a, 'b> {
+    pub payer: &'a AccountInfo<'b>,
+    pub mint_authority: &'a AccountInfo<'b>,
+    pub mint_authority_bump: u8,
+    pub mint: &'a AccountInfo<'b>,
+    pub token: &'a AccountInfo<'b>,
+    pub metadata: &'a AccountInfo<'b>,
+    pub edition: &'a AccountInfo<'b>,
+}
+
+///Locks or unlocks a programmable NFT, signed by the mint authority as its utility delegate.
+/// This is the programmable counterpart of freezing and thawing the token account.
+pub fn set_programmable_lock<'b>(
+    lock: bool,
+    gem: &GemAccounts<'_, 'b>,
+    programmable_accounts: &ProgrammableAccounts<'_, 'b>,
+) -> ProgramResult {
+    let instruction = if lock {
+        let mut builder = LockBuilder::new();
+        builder
+            .authority(*gem.mint_authority.key)
+            .token_owner(*gem.payer.key)
+            .token(*gem.token.key)
+            .mint(*gem.mint.key)
+            .metadata(*gem.metadata.key)
+            .edition(*gem.edition.key)
+            .token_record(*programmable_accounts.token_record.key)
+            .payer(*gem.payer.key)
+            .spl_token_program(spl_token::id());
+        if let Some(rule_set) = programmable_accounts.rule_set() {
+            builder.authorization_rules(rule_set);
+        }
+        builder
+            .build(LockArgs::V1 {
+                authorization_data: None,
+            })
+            .map_err(|_| InglError::InvalidData.utilize("Lock instruction"))?
+            .instruction()
+    } else {
+        let mut builder = UnlockBuilder::new();
+        builder
+            .authority(*gem.mint_authority.key)
+            .token_owner(*gem.payer.key)
+            .token(*gem.token.key)
+            .mint(*gem.mint.key)
+            .metadata(*gem.metadata.key)
+            .edition(*gem.edition.key)
+            .token_record(*programmable_accounts.token_record.key)
+            .payer(*gem.payer.key)
+            .spl_token_program(spl_token::id());
+        if let Some(rule_set) = programmable_accounts.rule_set() {
+            builder.authorization_rules(rule_set);
+        }
+        builder
+            .build(UnlockArgs::V1 {
+                authorization_data: None,
+            })
+            .map_err(|_| InglError::InvalidData.utilize("Unlock instruction"))?
+            .instruction()
+    };
+    let mut account_infos = vec![
+        gem.mint_authority.clone(),
+        gem.payer.clone(),
+        gem.token.clone(),
+        gem.mint.clone(),
+        gem.metadata.clone(),
+        gem.edition.clone(),
+    ];
+    account_infos.extend(programmable_accounts.infos());
+    invoke_signed(
+        &instruction,
+        &account_infos,
+        &[&[INGL_MINT_AUTHORITY_KEY, &[gem.mint_authority_bump]]],
+    )
+    .error_log("Error: @ setting the programmable nft lock")
+}
+
+///Freezes or thaws the token account of a Token-2022 gem, signed by the mint authority as the freeze authority of its mint.
+/// This is the Token-2022 counterpart of freezing and thawing the token account through the edition of a legacy NFT.
+pub fn set_token_2022_freeze(freeze: bool, gem: &GemAccounts) -> ProgramResult {
+    let instruction = if freeze {
+        spl_token_2022::instruction::freeze_account(
+            &spl_token_2022::id(),
+            gem.token.key,
+            gem.mint.key,
+            gem.mint_authority.key,
+            &[],
+        )?
+    } else {
+        spl_token_2022::instruction::thaw_account(
+            &spl_token_2022::id(),
+            gem.token.key,
+            gem.mint.key,
+            gem.mint_authority.key,
+            &[],
+        )?
+    };
+    invoke_signed(
+        &instruction,
+        &[
+            gem.token.clone(),
+            gem.mint.clone(),
+            gem.mint_authority.clone(),
+        ],
+        &[&[INGL_MINT_AUTHORITY_KEY, &[gem.mint_authority_bump]]],
+    )
+    .error_log("Error: @ setting the Token-2022 gem freeze")
+}
+
+///Delegates the utility of a programmable NFT to the given delegate, signed by the token owner.
+/// This is the programmable counterpart of approving a delegate on the token account.
+pub fn delegate_programmable_nft(
+    owner_account_info: &AccountInfo,
+    delegate: &Pubkey,
+    mint: &Pubkey,
+    token: &Pubkey,
+    programmable_accounts: &ProgrammableAccounts,
+    account_infos: &[AccountInfo],
+) -> ProgramResult {
+    let mut builder = DelegateBuilder::new();
+    builder
+        .delegate(*delegate)
+        .metadata(find_metadata_account(mint).0)
+        .master_edition(find_master_edition_account(mint).0)
+        .token_record(*programmable_accounts.token_record.key)
+        .mint(*mint)
+        .token(*token)
+        .authority(*owner_account_info.key)
+        .payer(*owner_account_info.key)
+        .spl_token_program(spl_token::id());
+    if let Some(rule_set) = programmable_accounts.rule_set() {
+        builder
+            .authorization_rules_program(*programmable_accounts.authorization_rules_program.key)
+            .authorization_rules(rule_set);
+    }
+    let instruction = builder
+        .build(DelegateArgs::UtilityV1 {
+            amount: 1,
+            authorization_data: None,
+        })
+        .map_err(|_| InglError::InvalidData.utilize("Delegate instruction"))?
+        .instruction();
+    invoke(&instruction, account_infos).error_log("Error: @ delegating the programmable nft")
+}
@@ -0,0 +1,114 @@
+//! Stand-in for the VRF oracle program, for local clusters and tests.
+//! It writes VRF result accounts in the layout imprint_rarity reads, with whatever result its authority gives it.
+//! It verifies nothing but the authority's signature, so it must never be set as the oracle program of a live validator instance.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+
+solana_program::declare_id!("4tYHREpb1hBy7y4Wjv7ki5FqyyqMoKjd22TneYici8mx");
+
+pub const VRF_RESULT_KEY: &[u8] = b"vrf_result";
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+/// Same layout as the VrfResult of the validator program.
+pub struct VrfResult {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub fulfilled_slot: u64,
+    pub result: [u8; 32],
+}
+impl VrfResult {
+    pub fn get_space() -> usize {
+        // 32 + 32 + 8 + 32
+        104
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum OracleInstruction {
+    /// Writes the result of the VRF request of a mint, at the current slot, recording the authority that signed it.
+    /// Accounts: payer (signer), authority (signer), mint, vrf_result (writable PDA), system program.
+    Fulfill { result: [u8; 32] },
+}
+
+pub fn get_vrf_result_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VRF_RESULT_KEY, mint.as_ref()], &id())
+}
+
+pub fn fulfill(payer: Pubkey, authority: Pubkey, mint: Pubkey, result: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(get_vrf_result_address(&mint).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: OracleInstruction::Fulfill { result }.try_to_vec().unwrap(),
+    }
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let OracleInstruction::Fulfill { result } = OracleInstruction::try_from_slice(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let account_info_iter = &mut accounts.iter();
+    let payer_account_info = next_account_info(account_info_iter)?;
+    let authority_account_info = next_account_info(account_info_iter)?;
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let vrf_result_account_info = next_account_info(account_info_iter)?;
+
+    let (vrf_result_key, vrf_result_bump) = get_vrf_result_address(mint_account_info.key);
+    if *vrf_result_account_info.key != vrf_result_key
+        || !payer_account_info.is_signer
+        || !authority_account_info.is_signer
+    {
+        Err(ProgramError::InvalidArgument)?
+    }
+
+    if vrf_result_account_info.owner != program_id {
+        let space = VrfResult::get_space();
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account_info.key,
+                &vrf_result_key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[payer_account_info.clone(), vrf_result_account_info.clone()],
+            &[&[
+                VRF_RESULT_KEY,
+                mint_account_info.key.as_ref(),
+                &[vrf_result_bump],
+            ]],
+        )?;
+    }
+
+    VrfResult {
+        authority: *authority_account_info.key,
+        mint: *mint_account_info.key,
+        fulfilled_slot: Clock::get()?.slot,
+        result,
+    }
+    .serialize(&mut &mut vrf_result_account_info.data.borrow_mut()[..])?;
+    Ok(())
+}